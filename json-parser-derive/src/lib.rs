@@ -0,0 +1,108 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{ext::IdentExt, parse_macro_input, Data, DeriveInput, Fields, Ident};
+
+/// Encodes a struct with named fields as an object keyed by field name.
+#[proc_macro_derive(ToValue)]
+pub fn derive_to_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let fields = match named_fields(&input) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let keys = fields.iter().map(key);
+
+    quote! {
+        impl #impl_generics ::json_parser::encode::ToValue for #name #ty_generics #where_clause {
+            fn to_value(&self) -> ::json_parser::parsing::Value {
+                let mut members = ::json_parser::map::Map::new();
+                #(
+                    members.insert(
+                        #keys.to_string(),
+                        ::json_parser::encode::ToValue::to_value(&self.#fields),
+                    );
+                )*
+                ::json_parser::parsing::Value::Object(members)
+            }
+        }
+    }
+    .into()
+}
+
+/// Decodes a struct with named fields from an object. Missing members decode as `null`, so
+/// `Option` fields may be omitted; if that fails, the error suggests the closest key that is
+/// not a field, as the member was probably misspelled.
+#[proc_macro_derive(FromValue)]
+pub fn derive_from_value(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let fields = match named_fields(&input) {
+        Ok(fields) => fields,
+        Err(error) => return error.to_compile_error().into(),
+    };
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let keys = fields.iter().map(key).collect::<Vec<String>>();
+    let decoded = fields.iter().map(|field| decode_field(field, &keys));
+
+    quote! {
+        impl #impl_generics ::json_parser::decode::FromValue for #name #ty_generics #where_clause {
+            fn from_value(
+                value: &::json_parser::parsing::Value,
+            ) -> ::std::result::Result<Self, ::json_parser::decode::DecodeError> {
+                match value {
+                    ::json_parser::parsing::Value::Object(members) => Ok(#name {
+                        #(#fields: #decoded,)*
+                    }),
+                    _ => Err(::json_parser::decode::DecodeError::new("an object", value)),
+                }
+            }
+        }
+    }
+    .into()
+}
+
+/// The member key of a field, without the `r#` of a raw identifier such as `r#type`.
+fn key(field: &Ident) -> String {
+    field.unraw().to_string()
+}
+
+fn decode_field(field: &Ident, keys: &[String]) -> TokenStream2 {
+    let key = key(field);
+    quote! {
+        match members.get(#key) {
+            Some(member) => ::json_parser::decode::FromValue::from_value(member)
+                .map_err(|e| e.at(#key))?,
+            None => ::json_parser::decode::FromValue::from_value(
+                &::json_parser::parsing::Value::Null,
+            )
+            .map_err(|e| {
+                let unknown = members.keys().filter(|k| ![#(#keys),*].contains(k));
+                e.at(#key)
+                    .with_suggestion(::json_parser::map::closest_key(#key, unknown))
+            })?,
+        }
+    }
+}
+
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<Ident>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => Ok(fields
+                .named
+                .iter()
+                .filter_map(|field| field.ident.clone())
+                .collect()),
+            _ => Err(syn::Error::new_spanned(
+                &input.ident,
+                "only structs with named fields are supported",
+            )),
+        },
+        _ => Err(syn::Error::new_spanned(
+            &input.ident,
+            "only structs with named fields are supported",
+        )),
+    }
+}
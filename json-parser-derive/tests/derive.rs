@@ -0,0 +1,75 @@
+use json_parser::{decode::FromValue, encode::ToValue, parsing::Value};
+use json_parser_derive::{FromValue, ToValue};
+
+#[derive(Debug, PartialEq, ToValue, FromValue)]
+struct User {
+    name: String,
+    followers: u32,
+    location: Option<String>,
+}
+
+#[derive(Debug, PartialEq, ToValue, FromValue)]
+struct Status {
+    id: u64,
+    user: User,
+    tags: Vec<String>,
+}
+
+#[test]
+fn round_trip_struct() {
+    let status = Status {
+        id: 1,
+        user: User {
+            name: "a".to_string(),
+            followers: 2,
+            location: None,
+        },
+        tags: vec!["x".to_string()],
+    };
+    let value = status.to_value();
+    assert_eq!(
+        r#"{"id":1,"user":{"name":"a","followers":2,"location":null},"tags":["x"]}"#
+            .parse::<Value>(),
+        Ok(value.clone())
+    );
+    assert_eq!(Ok(status), value.decode::<Status>());
+}
+
+#[test]
+fn fail_with_field_path() {
+    let value = r#"{"id": 1, "user": {"name": "a"}, "tags": []}"#.parse::<Value>().unwrap();
+    let error = Status::from_value(&value).unwrap_err();
+    assert_eq!("/user/followers", error.path());
+    assert_eq!(None, error.suggestion());
+
+    let value = r#"{"id": 1, "user": {"name": "a", "folowers": 2}, "tags": []}"#
+        .parse::<Value>()
+        .unwrap();
+    assert_eq!(
+        "Expected an integer in range of u32, found null at /user/followers, did you mean \"folowers\"?",
+        Status::from_value(&value).unwrap_err().to_string()
+    );
+}
+
+#[derive(Debug, PartialEq, ToValue, FromValue)]
+struct Token {
+    r#type: String,
+    r#ref: Option<u32>,
+}
+
+#[test]
+fn round_trip_raw_identifiers() {
+    let token = Token {
+        r#type: "a".to_string(),
+        r#ref: Some(1),
+    };
+    let value = token.to_value();
+    assert_eq!(
+        Ok(value.clone()),
+        r#"{"type": "a", "ref": 1}"#.parse::<Value>()
+    );
+    assert_eq!(Ok(token), value.decode::<Token>());
+
+    let value = r#"{"ref": 1}"#.parse::<Value>().unwrap();
+    assert_eq!("/type", Token::from_value(&value).unwrap_err().path());
+}
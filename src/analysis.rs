@@ -0,0 +1,575 @@
+use std::{fmt::Write, ops::Range};
+
+use crate::{
+    lexical::{string_contents, unescape, Reader, Span, Token},
+    parsing::escape_key,
+};
+
+/// A token that may come next.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Expected {
+    Key,
+    /// Any value: an object, array, string, number, `true`, `false`, or `null`.
+    Value,
+    Colon,
+    Comma,
+    CloseObject,
+    CloseArray,
+    /// Nothing, as the document is complete.
+    End,
+}
+
+/// What [`completions_at`] found at a cursor.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Completions {
+    expected: Vec<Expected>,
+    pointer: String,
+    keys: Vec<String>,
+    replace: Range<usize>,
+}
+
+impl Completions {
+    pub fn expected(&self) -> &[Expected] {
+        &self.expected
+    }
+
+    /// JSON pointer to the value expected at the cursor, or to the object whose key or the
+    /// container whose punctuation is expected, for looking up what a schema allows there.
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// The keys the object at the cursor already has, before or after it, which a key
+    /// completion would normally leave out.
+    pub fn keys(&self) -> &[String] {
+        &self.keys
+    }
+
+    /// The bytes of the partly typed token under the cursor, such as `"na` or `tr`, which a
+    /// completion replaces. Empty, at the cursor, if there is none.
+    pub fn replace(&self) -> Range<usize> {
+        self.replace.clone()
+    }
+}
+
+enum Frame {
+    Object {
+        keys: Vec<String>,
+        /// The key of the member being read, once it has been read.
+        key: Option<String>,
+        after_colon: bool,
+        after_value: bool,
+    },
+    Array {
+        len: usize,
+        after_value: bool,
+    },
+}
+
+/// A pushdown reader of tokens that recovers from unexpected ones by skipping them, so it
+/// can describe documents that are still being typed.
+struct Structure {
+    frames: Vec<Frame>,
+    has_root: bool,
+    /// The container closed last.
+    closed: Option<Frame>,
+}
+
+impl Structure {
+    fn expected(&self) -> Vec<Expected> {
+        match self.frames.last() {
+            None if self.has_root => vec![Expected::End],
+            None => vec![Expected::Value],
+            Some(Frame::Object {
+                keys,
+                key,
+                after_colon,
+                after_value,
+            }) => match (key, after_colon, after_value) {
+                (_, _, true) => vec![Expected::Comma, Expected::CloseObject],
+                (Some(_), true, _) => vec![Expected::Value],
+                (Some(_), false, _) => vec![Expected::Colon],
+                (None, _, _) if keys.is_empty() => vec![Expected::Key, Expected::CloseObject],
+                (None, _, _) => vec![Expected::Key],
+            },
+            Some(Frame::Array { len, after_value }) => match (len, after_value) {
+                (_, true) => vec![Expected::Comma, Expected::CloseArray],
+                (0, false) => vec![Expected::Value, Expected::CloseArray],
+                (_, false) => vec![Expected::Value],
+            },
+        }
+    }
+
+    /// The pointer to the container the cursor is in and, if a value is expected, the member
+    /// or element it would be.
+    fn pointer(&self) -> String {
+        let mut pointer = String::new();
+        for (i, frame) in self.frames.iter().enumerate() {
+            let is_last = i + 1 == self.frames.len();
+            match frame {
+                Frame::Object {
+                    key: Some(key),
+                    after_colon,
+                    after_value,
+                    ..
+                } if !is_last || (*after_colon && !after_value) => {
+                    write!(pointer, "/{}", escape_key(key)).unwrap();
+                }
+                Frame::Array { len, after_value } if !is_last || !after_value => {
+                    write!(pointer, "/{len}").unwrap();
+                }
+                _ => {}
+            }
+        }
+        pointer
+    }
+
+    fn read(&mut self, token: &Token) {
+        let Some(frame) = self.frames.last_mut() else {
+            if !self.has_root {
+                self.open(token);
+            }
+            return;
+        };
+        match (frame, token) {
+            // A container closes even where a key or value is missing, such as after a
+            // trailing comma.
+            (Frame::Object { .. }, Token::Punctuation('}'))
+            | (Frame::Array { .. }, Token::Punctuation(']')) => self.close(),
+            (
+                Frame::Object {
+                    key, after_value, ..
+                },
+                Token::Punctuation(','),
+            ) if *after_value => {
+                *key = None;
+                *after_value = false;
+            }
+            (Frame::Array { len, after_value }, Token::Punctuation(',')) if *after_value => {
+                *len += 1;
+                *after_value = false;
+            }
+            (
+                Frame::Object {
+                    keys,
+                    key: key @ None,
+                    after_value: false,
+                    ..
+                },
+                Token::String(s),
+            ) => {
+                let name = string_contents(s)
+                    .and_then(|contents| unescape(contents).ok())
+                    .unwrap_or_default();
+                keys.push(name.clone());
+                *key = Some(name);
+            }
+            (
+                Frame::Object {
+                    key: Some(_),
+                    after_colon: after_colon @ false,
+                    ..
+                },
+                Token::Punctuation(':'),
+            ) => *after_colon = true,
+            (
+                Frame::Object {
+                    key: Some(_),
+                    after_colon: true,
+                    after_value: false,
+                    ..
+                }
+                | Frame::Array {
+                    after_value: false, ..
+                },
+                token,
+            ) => self.open(token),
+            // Anything else is out of place, and skipped.
+            _ => {}
+        }
+    }
+
+    /// Reads `token` where a value is expected.
+    fn open(&mut self, token: &Token) {
+        match token {
+            Token::Punctuation('{') => self.frames.push(Frame::Object {
+                keys: Vec::new(),
+                key: None,
+                after_colon: false,
+                after_value: false,
+            }),
+            Token::Punctuation('[') => self.frames.push(Frame::Array {
+                len: 0,
+                after_value: false,
+            }),
+            Token::Punctuation(_) => {}
+            _ => self.end_value(),
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = self.frames.pop();
+        self.end_value();
+    }
+
+    fn end_value(&mut self) {
+        match self.frames.last_mut() {
+            Some(Frame::Object { after_value, .. } | Frame::Array { after_value, .. }) => {
+                *after_value = true
+            }
+            None => self.has_root = true,
+        }
+    }
+}
+
+/// What may be typed at the cursor `offset` in `src`. Tokens that are out of place are
+/// skipped, and a container closes even after a trailing comma, so completions are still
+/// offered in documents with mistakes. An offset past the end, or inside a
+/// character, is taken as the end of the character.
+pub fn completions_at(src: &str, offset: usize) -> Completions {
+    let offset = (offset.min(src.len())..=src.len())
+        .find(|&i| src.is_char_boundary(i))
+        .unwrap_or(src.len());
+    let mut structure = Structure {
+        frames: Vec::new(),
+        has_root: false,
+        closed: None,
+    };
+    let mut reader = Reader::new(src);
+    let mut replace = offset..offset;
+    let mut completions = None;
+
+    // How many containers the cursor is in, to go on collecting the keys of the innermost
+    // after the cursor until it closes.
+    let mut depth = 0;
+    loop {
+        let token = reader.next(1).pop();
+        let span = reader.last_span();
+        if completions.is_none() && token.as_ref().is_none_or(|_| span.start >= offset) {
+            completions = Some(snapshot(&structure, replace.clone()));
+            depth = structure.frames.len();
+        }
+        let Some(token) = token else {
+            break;
+        };
+
+        if completions.is_none() {
+            let is_partial = match &token {
+                Ok(Token::String(s)) => string_contents(s).is_none(),
+                Ok(_) => false,
+                Err(_) => true,
+            };
+            if span.end > offset || (span.end == offset && is_partial) {
+                // The cursor is in this token, which the completion replaces.
+                replace = span.start..span.end;
+                completions = Some(snapshot(&structure, replace.clone()));
+                depth = structure.frames.len();
+            }
+        }
+        if let Ok(token) = token {
+            structure.read(&token);
+        }
+        if completions.is_some() && structure.frames.len() < depth {
+            break;
+        }
+    }
+
+    let mut completions = completions.expect("a snapshot to be taken at the end");
+    let frame = match structure.frames.len() {
+        _ if depth == 0 => None,
+        len if len >= depth => structure.frames.get(depth - 1),
+        _ => structure.closed.as_ref(),
+    };
+    if let Some(Frame::Object { keys, .. }) = frame {
+        completions.keys = keys.clone();
+    }
+    completions
+}
+
+/// A container that spans more than one line, which an editor can fold.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FoldingRange {
+    start_line: usize,
+    end_line: usize,
+    range: Range<usize>,
+}
+
+impl FoldingRange {
+    /// The line of the opening bracket, counted from 1.
+    pub fn start_line(&self) -> usize {
+        self.start_line
+    }
+
+    /// The line of the closing bracket.
+    pub fn end_line(&self) -> usize {
+        self.end_line
+    }
+
+    /// The bytes of the container, brackets included.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+}
+
+/// The folding ranges of `src`, ordered by where they start. Brackets that do not pair up
+/// are ignored, so a document being edited still folds.
+pub fn folding_ranges(src: &str) -> Vec<FoldingRange> {
+    let mut ranges = Vec::new();
+    let mut open = Vec::<(char, Span)>::new();
+    let mut reader = Reader::new(src);
+    while let Some(token) = reader.next(1).pop() {
+        let span = reader.last_span();
+        match token {
+            Ok(Token::Punctuation(c @ ('{' | '['))) => open.push((c, span)),
+            Ok(Token::Punctuation(c @ ('}' | ']'))) => {
+                let opening = if c == '}' { '{' } else { '[' };
+                let Some(depth) = open.iter().rposition(|&(c, _)| c == opening) else {
+                    continue;
+                };
+                let (_, start) = open[depth];
+                open.truncate(depth);
+                if span.line > start.line {
+                    ranges.push(FoldingRange {
+                        start_line: start.line,
+                        end_line: span.line,
+                        range: start.start..span.end,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    ranges.sort_by_key(|range| range.range.start);
+    ranges
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum SymbolKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+/// A member of the top-level object, for an editor's outline.
+#[derive(Debug, PartialEq, Clone)]
+pub struct DocumentSymbol {
+    name: String,
+    kind: SymbolKind,
+    range: Range<usize>,
+    key_range: Range<usize>,
+    line: usize,
+}
+
+impl DocumentSymbol {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn kind(&self) -> SymbolKind {
+        self.kind
+    }
+
+    /// The bytes from the start of the key to the end of the value.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// The bytes of the key, quotes included.
+    pub fn key_range(&self) -> Range<usize> {
+        self.key_range.clone()
+    }
+
+    /// The line of the key, counted from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+/// The members of the top-level object of `src`, in document order, or none if it is not an
+/// object. A member whose value is missing or cut off ends at its last token.
+pub fn document_symbols(src: &str) -> Vec<DocumentSymbol> {
+    let mut structure = Structure {
+        frames: Vec::new(),
+        has_root: false,
+        closed: None,
+    };
+    let mut symbols = Vec::<DocumentSymbol>::new();
+    // The member being read, until its value ends.
+    let mut member = None::<DocumentSymbol>;
+    let mut reader = Reader::new(src);
+    while let Some(token) = reader.next(1).pop() {
+        let span = reader.last_span();
+        let Ok(token) = token else {
+            continue;
+        };
+        let expected = structure.expected();
+        let depth = structure.frames.len();
+        structure.read(&token);
+
+        match (depth, &mut member, &token) {
+            (0, _, Token::Punctuation('{')) if !structure.has_root => continue,
+            (0, _, _) => break,
+            (1, None, Token::String(key)) if expected.contains(&Expected::Key) => {
+                member = Some(DocumentSymbol {
+                    name: string_contents(key)
+                        .and_then(|contents| unescape(contents).ok())
+                        .unwrap_or_default(),
+                    kind: SymbolKind::Null,
+                    range: span.start..span.end,
+                    key_range: span.start..span.end,
+                    line: span.line,
+                });
+            }
+            (1, Some(symbol), token) if expected == [Expected::Value] => {
+                symbol.kind = match token {
+                    Token::Null => SymbolKind::Null,
+                    Token::Bool(_) => SymbolKind::Bool,
+                    Token::Number(_) => SymbolKind::Number,
+                    Token::Punctuation('{') => SymbolKind::Object,
+                    Token::Punctuation('[') => SymbolKind::Array,
+                    _ => SymbolKind::String,
+                };
+            }
+            _ => {}
+        }
+
+        if structure.frames.is_empty() {
+            // The root object closed, ending any member still missing its value.
+            break;
+        }
+        if let Some(symbol) = &mut member {
+            symbol.range.end = span.end;
+        }
+        // The root object is back to expecting a comma once the value has ended.
+        if structure.frames.len() == 1 && structure.expected()[0] == Expected::Comma {
+            symbols.extend(member.take());
+        }
+    }
+    symbols.extend(member);
+    symbols
+}
+
+fn snapshot(structure: &Structure, replace: Range<usize>) -> Completions {
+    let keys = match structure.frames.last() {
+        Some(Frame::Object { keys, .. }) => keys.clone(),
+        _ => Vec::new(),
+    };
+    Completions {
+        expected: structure.expected(),
+        pointer: structure.pointer(),
+        keys,
+        replace,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Completions at the `|` in `src`.
+    fn at(src: &str) -> Completions {
+        let offset = src.find('|').unwrap();
+        completions_at(&src.replacen('|', "", 1), offset)
+    }
+
+    #[test]
+    fn complete_at_cursor() {
+        let completions = at(r#"{"a": 1, |}"#);
+        assert_eq!(&[Expected::Key], completions.expected());
+        assert_eq!("", completions.pointer());
+
+        let completions = at(r#"{"a": {"b": [1, |]}, "c": 2}"#);
+        assert_eq!(&[Expected::Value], completions.expected());
+        assert_eq!("/a/b/1", completions.pointer());
+
+        let completions = at(r#"{"a": {"x": 1, "na|"#);
+        assert_eq!(&[Expected::Key], completions.expected());
+        assert_eq!("/a", completions.pointer());
+        assert_eq!(15..18, completions.replace());
+
+        let completions = at(r#"{"a": tr|"#);
+        assert_eq!(&[Expected::Value], completions.expected());
+        assert_eq!("/a", completions.pointer());
+        assert_eq!(6..8, completions.replace());
+
+        assert_eq!(&[Expected::Colon], at(r#"{"a"| "#).expected());
+        assert_eq!(
+            &[Expected::Comma, Expected::CloseArray],
+            at("[1 |, 2]").expected()
+        );
+        assert_eq!(&[Expected::Value], at("|").expected());
+        assert_eq!(&[Expected::End], at("[]|").expected());
+    }
+
+    #[test]
+    fn collect_keys_around_cursor() {
+        let completions = at(r#"{"a": 1, "q": {"z": 0}, | "b": [], "c": {"d": 1}}"#);
+        assert_eq!(vec!["a", "q", "b", "c"], completions.keys());
+
+        let completions = at(r#"[{"a": 1, |}, {"b": 2}]"#);
+        assert_eq!(vec!["a"], completions.keys());
+        assert_eq!("/0", completions.pointer());
+    }
+
+    #[test]
+    fn fold_containers() {
+        let src = "{\n  \"a\": [1, 2],\n  \"b\": [\n    {\"c\": {\n    }}\n  ]]\n}";
+        let ranges = folding_ranges(src)
+            .into_iter()
+            .map(|range| {
+                (
+                    range.start_line(),
+                    range.end_line(),
+                    &src[range.range()][..1],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![(1, 7, "{"), (3, 6, "["), (4, 5, "{"), (4, 5, "{")],
+            ranges
+        );
+    }
+
+    #[test]
+    fn outline_top_level_members() {
+        let src =
+            r#"{"id": 7, "tags": ["x", {"y": null}], "o\n": {"a": 1}, bad, "s": "v", "cut": [1,"#;
+        let symbols = document_symbols(src)
+            .into_iter()
+            .map(|symbol| {
+                (
+                    symbol.name().to_string(),
+                    symbol.kind(),
+                    &src[symbol.range()],
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                ("id".to_string(), SymbolKind::Number, r#""id": 7"#),
+                (
+                    "tags".to_string(),
+                    SymbolKind::Array,
+                    r#""tags": ["x", {"y": null}]"#
+                ),
+                ("o\n".to_string(), SymbolKind::Object, r#""o\n": {"a": 1}"#),
+                ("s".to_string(), SymbolKind::String, r#""s": "v""#),
+                ("cut".to_string(), SymbolKind::Array, r#""cut": [1,"#),
+            ],
+            symbols
+        );
+        assert!(document_symbols("[1]").is_empty());
+        let symbols = document_symbols(r#"{"a" }"#);
+        assert_eq!(1..4, symbols[0].range());
+    }
+
+    #[test]
+    fn recover_from_mistakes() {
+        let completions = at(r#"{"a": 1 "b": 2, "c": : |"#);
+        assert_eq!(&[Expected::Value], completions.expected());
+        assert_eq!("/c", completions.pointer());
+    }
+}
@@ -0,0 +1,350 @@
+use std::{cmp::Ordering, collections::HashSet, fmt, fmt::Display, fmt::Write, mem};
+
+use crate::{
+    parsing::{widen, Value},
+    serialize,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ArrayError {
+    NotFound(String),
+    NotAnArray(String),
+    NotANumber(String),
+    /// An array whose length or nesting differs from that of the first element at its level.
+    Ragged(String),
+}
+
+impl Display for ArrayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (message, pointer) = match self {
+            ArrayError::NotFound(pointer) => ("Path not found", pointer),
+            ArrayError::NotAnArray(pointer) => ("Expected an array", pointer),
+            ArrayError::NotANumber(pointer) => ("Expected a number", pointer),
+            ArrayError::Ragged(pointer) => {
+                ("Expected the same shape as the first element", pointer)
+            }
+        };
+        f.write_str(message)?;
+        if !pointer.is_empty() {
+            write!(f, " at {pointer}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ArrayError {}
+
+/// The numbers of nested arrays, flattened in row-major order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Matrix {
+    data: Vec<f64>,
+    shape: Vec<usize>,
+}
+
+impl Matrix {
+    pub fn data(&self) -> &[f64] {
+        &self.data
+    }
+
+    /// The length of the arrays at each level of nesting, outermost first, such as `[3, 2]`
+    /// for three pairs of coordinates.
+    pub fn shape(&self) -> &[usize] {
+        &self.shape
+    }
+
+    pub fn into_data(self) -> Vec<f64> {
+        self.data
+    }
+}
+
+impl Value {
+    /// Stably sorts the array at the `array` pointer by the value at the `key` pointer within
+    /// each element. Elements without the key come first.
+    pub fn sort_array_by_pointer(&mut self, array: &str, key: &str) -> Result<(), ArrayError> {
+        self.array_at(array)?
+            .sort_by(|a, b| compare_opt(a.pointer(key), b.pointer(key)));
+        Ok(())
+    }
+
+    /// Removes every element of the array at the `array` pointer whose value at the `key`
+    /// pointer equals that of an earlier element, keeping the rest in order. Elements without
+    /// the key are all kept.
+    pub fn dedup_array_by(&mut self, array: &str, key: &str) -> Result<(), ArrayError> {
+        let mut seen = HashSet::<String>::new();
+        self.array_at(array)?
+            .retain(|element| match element.pointer(key) {
+                Some(key) => seen.insert(serialize::to_canonical_string(key)),
+                None => true,
+            });
+        Ok(())
+    }
+
+    /// Flattens the nested arrays of numbers at `pointer`, such as the coordinates of a ring of
+    /// a GeoJSON polygon, for numeric libraries. Every array at a level must have the length
+    /// and nesting of the first.
+    pub fn to_f64_matrix(&self, pointer: &str) -> Result<Matrix, ArrayError> {
+        let value = match self.pointer(pointer) {
+            Some(value @ Value::Array(_)) => value,
+            Some(_) => return Err(ArrayError::NotAnArray(pointer.to_string())),
+            None => return Err(ArrayError::NotFound(pointer.to_string())),
+        };
+        let mut shape = Vec::new();
+        let mut first = value;
+        while let Value::Array(elements) = first {
+            shape.push(elements.len());
+            match elements.first() {
+                Some(element) => first = element,
+                None => break,
+            }
+        }
+        let mut data = Vec::with_capacity(shape.iter().product());
+        flatten(value, &shape, &mut pointer.to_string(), &mut data)?;
+        Ok(Matrix { data, shape })
+    }
+
+    pub(crate) fn array_at(&mut self, pointer: &str) -> Result<&mut Vec<Value>, ArrayError> {
+        match self.pointer_mut(pointer) {
+            Some(Value::Array(elements)) => Ok(elements),
+            Some(_) => Err(ArrayError::NotAnArray(pointer.to_string())),
+            None => Err(ArrayError::NotFound(pointer.to_string())),
+        }
+    }
+}
+
+/// Joins the arrays at one pointer in pages of an API response, such as the `statuses` of
+/// each page of a search, one page at a time so only the joined elements are kept. The
+/// joined document is the first page with the elements of every page in its array.
+pub struct Stitcher {
+    array: String,
+    id: Option<String>,
+    seen: HashSet<String>,
+    document: Option<Value>,
+}
+
+impl Stitcher {
+    pub fn new(array: &str) -> Self {
+        Stitcher {
+            array: array.to_string(),
+            id: None,
+            seen: HashSet::new(),
+            document: None,
+        }
+    }
+
+    /// Drops every element whose value at the `id` pointer equals that of an earlier
+    /// element, as pages often overlap when new items arrive between requests. Elements
+    /// without an id are all kept.
+    pub fn dedup_by(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn add(&mut self, mut page: Value) -> Result<(), ArrayError> {
+        let mut elements = mem::take(page.array_at(&self.array)?);
+        if let Some(id) = &self.id {
+            elements.retain(|element| match element.pointer(id) {
+                Some(id) => self.seen.insert(serialize::to_canonical_string(id)),
+                None => true,
+            });
+        }
+        match &mut self.document {
+            Some(document) => document.array_at(&self.array)?.append(&mut elements),
+            None => *page.array_at(&self.array)? = elements,
+        }
+        self.document.get_or_insert(page);
+        Ok(())
+    }
+
+    /// The joined document, or `None` if no page was added.
+    pub fn finish(self) -> Option<Value> {
+        self.document
+    }
+}
+
+fn flatten(
+    value: &Value,
+    shape: &[usize],
+    path: &mut String,
+    data: &mut Vec<f64>,
+) -> Result<(), ArrayError> {
+    match (value, shape) {
+        (Value::Number(n), []) => data.push(widen(*n)),
+        (Value::Array(elements), [len, shape @ ..]) if elements.len() == *len => {
+            let end = path.len();
+            for (i, element) in elements.iter().enumerate() {
+                write!(path, "/{i}").unwrap();
+                flatten(element, shape, path, data)?;
+                path.truncate(end);
+            }
+        }
+        (Value::Array(_) | Value::Number(_), _) => return Err(ArrayError::Ragged(path.clone())),
+        _ => return Err(ArrayError::NotANumber(path.clone())),
+    }
+    Ok(())
+}
+
+fn compare_opt(a: Option<&Value>, b: Option<&Value>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => compare(a, b),
+        (a, b) => a.is_some().cmp(&b.is_some()),
+    }
+}
+
+/// Orders values by type (null, bool, number, string, array, object), then by content.
+/// Objects are compared by their canonical serialization.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    fn rank(value: &Value) -> u8 {
+        match value {
+            Value::Null => 0,
+            Value::Bool(_) => 1,
+            Value::Number(_) => 2,
+            Value::String(_) => 3,
+            #[cfg(feature = "bytes")]
+            Value::Bytes(_) => 3,
+            Value::Array(_) => 4,
+            Value::Object(_) => 5,
+        }
+    }
+
+    match (a, b) {
+        (Value::Bool(a), Value::Bool(b)) => a.cmp(b),
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b).unwrap_or(Ordering::Equal),
+        (Value::String(a), Value::String(b)) => a.cmp(b),
+        #[cfg(feature = "bytes")]
+        (Value::Bytes(a), Value::Bytes(b)) => a.cmp(b),
+        (Value::Array(a), Value::Array(b)) => a
+            .iter()
+            .zip(b)
+            .map(|(a, b)| compare(a, b))
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(a.len().cmp(&b.len())),
+        (Value::Object(_), Value::Object(_)) => {
+            serialize::to_canonical_string(a).cmp(&serialize::to_canonical_string(b))
+        }
+        (a, b) => rank(a).cmp(&rank(b)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    const DOCUMENT: &str = r#"{"items": [
+        {"id": 3, "n": "a"},
+        {"id": "x", "n": "b"},
+        {"n": "c"},
+        {"id": 1, "n": "d"},
+        {"id": 3, "n": "e"},
+        {"id": [1, 2], "n": "f"},
+        {"id": null, "n": "g"}
+    ]}"#;
+
+    fn names(value: &Value) -> String {
+        match value.pointer("/items") {
+            Some(Value::Array(elements)) => elements
+                .iter()
+                .map(|e| e.pointer("/n").unwrap().to_string())
+                .collect(),
+            _ => panic!("no items"),
+        }
+    }
+
+    #[test]
+    fn sort_stably_by_key() {
+        let mut value = Parser::parse(DOCUMENT).unwrap();
+        value.sort_array_by_pointer("/items", "/id").unwrap();
+        assert_eq!(r#""c""g""d""a""e""b""f""#, names(&value));
+    }
+
+    #[test]
+    fn dedup_keeping_first() {
+        let mut value = Parser::parse(DOCUMENT).unwrap();
+        value.dedup_array_by("/items", "/id").unwrap();
+        assert_eq!(r#""a""b""c""d""f""g""#, names(&value));
+
+        let mut value =
+            Parser::parse(r#"[{"k": {"a": 1, "b": 2}}, {"k": {"b": 2, "a": 1}}]"#).unwrap();
+        value.dedup_array_by("", "/k").unwrap();
+        assert_eq!(r#"[{"k":{"a":1,"b":2}}]"#, value.to_string());
+    }
+
+    #[test]
+    fn flatten_numeric_arrays() {
+        let value =
+            Parser::parse(r#"{"ring": [[-65.6, 43.5], [-65.7, 43.4], [-65.8, 43.3]]}"#).unwrap();
+        let matrix = value.to_f64_matrix("/ring").unwrap();
+        assert_eq!(&[3, 2], matrix.shape());
+        assert_eq!(
+            vec![-65.6, 43.5, -65.7, 43.4, -65.8, 43.3],
+            matrix
+                .data()
+                .iter()
+                .map(|n| (n * 10.0).round() / 10.0)
+                .collect::<Vec<f64>>()
+        );
+        assert_eq!(
+            Err(ArrayError::NotAnArray("/ring/0/0".to_string())),
+            value.to_f64_matrix("/ring/0/0")
+        );
+
+        let empty = Parser::parse("[[], []]")
+            .unwrap()
+            .to_f64_matrix("")
+            .unwrap();
+        assert_eq!((&[2, 0][..], &[][..]), (empty.shape(), empty.data()));
+
+        let value = Parser::parse(r#"[[1, 2], [3], [4, [5]], [6, "7"]]"#).unwrap();
+        assert_eq!(
+            Err(ArrayError::Ragged("/1".to_string())),
+            value.to_f64_matrix("")
+        );
+        let value = Parser::parse(r#"[[1, 2], [4, [5]]]"#).unwrap();
+        assert_eq!(
+            Err(ArrayError::Ragged("/1/1".to_string())),
+            value.to_f64_matrix("")
+        );
+        let value = Parser::parse(r#"[[1, 2], [6, "7"]]"#).unwrap();
+        assert_eq!(
+            Err(ArrayError::NotANumber("/1/1".to_string())),
+            value.to_f64_matrix("")
+        );
+    }
+
+    #[test]
+    fn stitch_pages() {
+        let pages = [
+            r#"{"statuses": [{"id": 1}, {"id": 2}], "next": "a"}"#,
+            r#"{"statuses": [{"id": 2}, {"id": 3}, {}], "next": "b"}"#,
+            r#"{"statuses": [{}, {"id": 1}], "next": null}"#,
+        ];
+        let mut stitcher = Stitcher::new("/statuses").dedup_by("/id");
+        for page in pages {
+            stitcher.add(Parser::parse(page).unwrap()).unwrap();
+        }
+        assert_eq!(
+            Parser::parse(r#"{"statuses":[{"id":1},{"id":2},{"id":3},{},{}],"next":"a"}"#).ok(),
+            stitcher.finish()
+        );
+
+        let mut stitcher = Stitcher::new("/statuses");
+        assert_eq!(
+            Err(ArrayError::NotFound("/statuses".to_string())),
+            stitcher.add(Parser::parse("[]").unwrap())
+        );
+        assert_eq!(None, stitcher.finish());
+    }
+
+    #[test]
+    fn fail_without_array() {
+        let mut value = Parser::parse(DOCUMENT).unwrap();
+        assert_eq!(
+            Err(ArrayError::NotFound("/missing".to_string())),
+            value.sort_array_by_pointer("/missing", "/id")
+        );
+        assert_eq!(
+            Err(ArrayError::NotAnArray("/items/0".to_string())),
+            value.dedup_array_by("/items/0", "/id")
+        );
+    }
+}
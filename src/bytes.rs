@@ -0,0 +1,152 @@
+const STANDARD: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const URL_SAFE: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// How [`Value::Bytes`](crate::parsing::Value::Bytes) is written. Either way it is a JSON
+/// string.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub enum BytesFormat {
+    /// Base64 with `+` and `/`, padded with `=` (RFC 4648, section 4).
+    #[default]
+    Base64,
+    /// Base64 with `-` and `_` and no padding (RFC 4648, section 5), for URLs and file names.
+    Base64Url,
+}
+
+impl BytesFormat {
+    fn alphabet(self) -> &'static [u8; 64] {
+        match self {
+            BytesFormat::Base64 => STANDARD,
+            BytesFormat::Base64Url => URL_SAFE,
+        }
+    }
+
+    pub fn encode(self, bytes: &[u8]) -> String {
+        let alphabet = self.alphabet();
+        let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+        for chunk in bytes.chunks(3) {
+            let group = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |group, (i, b)| group | u32::from(*b) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                encoded.push(alphabet[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            }
+            if self == BytesFormat::Base64 {
+                for _ in chunk.len()..3 {
+                    encoded.push('=');
+                }
+            }
+        }
+        encoded
+    }
+
+    /// Decodes `s` if it is exactly what [`BytesFormat::encode`] would write for some bytes,
+    /// so a string that merely happens to use the alphabet, with the wrong padding or stray
+    /// low bits, is rejected.
+    pub fn decode(self, s: &str) -> Option<Vec<u8>> {
+        let digits = match self {
+            BytesFormat::Base64 if !s.len().is_multiple_of(4) => return None,
+            BytesFormat::Base64 => s.trim_end_matches('='),
+            BytesFormat::Base64Url => s,
+        };
+        if s.len() - digits.len() > 2 || digits.len() % 4 == 1 {
+            return None;
+        }
+
+        let alphabet = self.alphabet();
+        let mut bytes = Vec::with_capacity(digits.len() * 3 / 4);
+        for chunk in digits.as_bytes().chunks(4) {
+            let mut group = 0u32;
+            for (i, digit) in chunk.iter().enumerate() {
+                let value = alphabet.iter().position(|c| c == digit)? as u32;
+                group |= value << (18 - 6 * i);
+            }
+            let len = chunk.len() - 1;
+            bytes.extend((0..len).map(|i| (group >> (16 - 8 * i)) as u8));
+            if group & (0xff_ffff >> (8 * len)) != 0 {
+                return None;
+            }
+        }
+        Some(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        parsing::{ParseOptions, Parser, Value},
+        serialize::{self, FormatConfig},
+    };
+
+    #[test]
+    fn encode_and_decode_base64() {
+        // Test vectors from RFC 4648, section 10.
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (bytes, encoded) in vectors {
+            assert_eq!(encoded, BytesFormat::Base64.encode(bytes.as_bytes()));
+            assert_eq!(
+                Some(bytes.as_bytes().to_vec()),
+                BytesFormat::Base64.decode(encoded)
+            );
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(unpadded, BytesFormat::Base64Url.encode(bytes.as_bytes()));
+        }
+
+        assert_eq!("-_8", BytesFormat::Base64Url.encode(&[0xfb, 0xff]));
+        assert_eq!(Some(vec![0xfb, 0xff]), BytesFormat::Base64.decode("+/8="));
+        for invalid in ["Zg", "Zg=", "Zh==", "Z===", "Zm9v!A==", "-_8="] {
+            assert_eq!(None, BytesFormat::Base64.decode(invalid), "{invalid}");
+        }
+        assert_eq!(None, BytesFormat::Base64Url.decode("Zg=="));
+    }
+
+    #[test]
+    fn write_and_detect_bytes() {
+        let value = Value::Array(vec![
+            Value::Bytes(vec![0xfb, 0xff]),
+            Value::Bytes(Vec::new()),
+        ]);
+        assert_eq!(r#"["+/8=",""]"#, value.to_string());
+        let config = FormatConfig {
+            bytes: BytesFormat::Base64Url,
+            ..FormatConfig::compact()
+        };
+        assert_eq!(r#"["-_8",""]"#, serialize::to_string(&value, &config));
+        assert_eq!(
+            Ok(value.clone()),
+            Value::from_compact_bytes(&value.to_compact_bytes())
+        );
+
+        let json = r#"["+/8=", "word", "Zg"]"#;
+        assert_eq!(
+            Value::Array(vec![
+                Value::String("+/8=".to_string()),
+                Value::String("word".to_string()),
+                Value::String("Zg".to_string()),
+            ]),
+            Parser::parse(json).unwrap()
+        );
+        let options = ParseOptions {
+            detect_bytes: true,
+            ..ParseOptions::default()
+        };
+        let (detected, _) = Parser::parse_with_options(json, options).unwrap();
+        assert_eq!(
+            Value::Array(vec![
+                Value::Bytes(vec![0xfb, 0xff]),
+                Value::Bytes(vec![0xc2, 0x8a, 0xdd]),
+                Value::String("Zg".to_string()),
+            ]),
+            detected
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::{fmt, fmt::Display};
+
+use crate::{
+    map::Map,
+    parsing::{widen, Float, Value},
+};
+
+const MAGIC: &[u8; 3] = b"JPB";
+const VERSION: u8 = 1;
+
+const NULL: u8 = 0;
+const FALSE: u8 = 1;
+const TRUE: u8 = 2;
+const NUMBER: u8 = 3;
+const STRING: u8 = 4;
+const ARRAY: u8 = 5;
+const OBJECT: u8 = 6;
+#[cfg(feature = "bytes")]
+const BYTES: u8 = 7;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CompactError {
+    BadHeader,
+    UnsupportedVersion(u8),
+    UnexpectedEnd,
+    InvalidTag(u8),
+    InvalidUtf8,
+    TrailingBytes,
+}
+
+impl Display for CompactError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompactError::BadHeader => f.write_str("Not a compact encoded value"),
+            CompactError::UnsupportedVersion(v) => write!(f, "Unsupported version {v}"),
+            CompactError::UnexpectedEnd => f.write_str("Unexpected end of input"),
+            CompactError::InvalidTag(tag) => write!(f, "Invalid type tag {tag}"),
+            CompactError::InvalidUtf8 => f.write_str("String is not valid UTF-8"),
+            CompactError::TrailingBytes => f.write_str("Trailing bytes after value"),
+        }
+    }
+}
+
+impl std::error::Error for CompactError {}
+
+impl Value {
+    /// Encodes the value in a binary form that is much faster to load than JSON text.
+    ///
+    /// The encoding is a `JPB` magic and version byte followed by the value, where each value
+    /// is a type tag and, for strings, arrays, and objects, a LEB128 length. Numbers are
+    /// little-endian f64 bits and object members keep their order.
+    pub fn to_compact_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::<u8>::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        encode(&mut out, self);
+        out
+    }
+
+    pub fn from_compact_bytes(bytes: &[u8]) -> Result<Value, CompactError> {
+        let rest = match bytes.strip_prefix(MAGIC) {
+            Some([VERSION, rest @ ..]) => rest,
+            Some([version, ..]) => return Err(CompactError::UnsupportedVersion(*version)),
+            _ => return Err(CompactError::BadHeader),
+        };
+        let mut decoder = Decoder { bytes: rest };
+        let value = decoder.value()?;
+        if !decoder.bytes.is_empty() {
+            return Err(CompactError::TrailingBytes);
+        }
+        Ok(value)
+    }
+}
+
+fn encode(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(NULL),
+        Value::Bool(false) => out.push(FALSE),
+        Value::Bool(true) => out.push(TRUE),
+        Value::Number(n) => {
+            out.push(NUMBER);
+            out.extend_from_slice(&widen(*n).to_le_bytes());
+        }
+        Value::String(s) => {
+            out.push(STRING);
+            encode_str(out, s);
+        }
+        #[cfg(feature = "bytes")]
+        Value::Bytes(bytes) => {
+            out.push(BYTES);
+            encode_len(out, bytes.len());
+            out.extend_from_slice(bytes);
+        }
+        Value::Array(elements) => {
+            out.push(ARRAY);
+            encode_len(out, elements.len());
+            for element in elements {
+                encode(out, element);
+            }
+        }
+        Value::Object(members) => {
+            out.push(OBJECT);
+            encode_len(out, members.len());
+            for (key, member) in members {
+                encode_str(out, key);
+                encode(out, member);
+            }
+        }
+    }
+}
+
+fn encode_str(out: &mut Vec<u8>, s: &str) {
+    encode_len(out, s.len());
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn encode_len(out: &mut Vec<u8>, mut len: usize) {
+    while len >= 0x80 {
+        out.push(len as u8 | 0x80);
+        len >>= 7;
+    }
+    out.push(len as u8);
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Decoder<'a> {
+    fn value(&mut self) -> Result<Value, CompactError> {
+        match self.take(1)?[0] {
+            NULL => Ok(Value::Null),
+            FALSE => Ok(Value::Bool(false)),
+            TRUE => Ok(Value::Bool(true)),
+            NUMBER => {
+                let bytes = self.take(8)?.try_into().unwrap();
+                Ok(Value::Number(f64::from_le_bytes(bytes) as Float))
+            }
+            STRING => self.string().map(Value::String),
+            #[cfg(feature = "bytes")]
+            BYTES => {
+                let len = self.len()?;
+                Ok(Value::Bytes(self.take(len)?.to_vec()))
+            }
+            ARRAY => {
+                let len = self.len()?;
+                // Every element takes at least one byte, which bounds the allocation for
+                // corrupt lengths.
+                let mut elements = Vec::<Value>::with_capacity(len.min(self.bytes.len()));
+                for _ in 0..len {
+                    elements.push(self.value()?);
+                }
+                Ok(Value::Array(elements))
+            }
+            OBJECT => {
+                let mut members = Map::new();
+                for _ in 0..self.len()? {
+                    let key = self.string()?;
+                    members.insert(key, self.value()?);
+                }
+                Ok(Value::Object(members))
+            }
+            tag => Err(CompactError::InvalidTag(tag)),
+        }
+    }
+
+    fn string(&mut self) -> Result<String, CompactError> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| CompactError::InvalidUtf8)
+    }
+
+    fn len(&mut self) -> Result<usize, CompactError> {
+        let mut len = 0usize;
+        for shift in (0..usize::BITS).step_by(7) {
+            let byte = self.take(1)?[0];
+            len |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(len);
+            }
+        }
+        Err(CompactError::UnexpectedEnd)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CompactError> {
+        if self.bytes.len() < n {
+            return Err(CompactError::UnexpectedEnd);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn round_trip() {
+        let json = std::fs::read_to_string("tests/canada.json").unwrap();
+        let value = Parser::parse(&json).unwrap();
+        let bytes = value.to_compact_bytes();
+        assert_eq!(Ok(value), Value::from_compact_bytes(&bytes));
+
+        let value = Parser::parse(r#"{"b": [null, true, false, -1.5], "a": {"é": ""}}"#).unwrap();
+        let decoded = Value::from_compact_bytes(&value.to_compact_bytes()).unwrap();
+        assert_eq!(value.to_string(), decoded.to_string());
+
+        let long = Value::String("x".repeat(300));
+        assert_eq!(
+            Ok(long.clone()),
+            Value::from_compact_bytes(&long.to_compact_bytes())
+        );
+    }
+
+    #[test]
+    fn fail_on_corrupt_input() {
+        let bytes = Value::Array(vec![Value::Number(1.0)]).to_compact_bytes();
+        assert_eq!(
+            Err(CompactError::BadHeader),
+            Value::from_compact_bytes(b"{}")
+        );
+        assert_eq!(
+            Err(CompactError::UnsupportedVersion(9)),
+            Value::from_compact_bytes(b"JPB\x09\x00")
+        );
+        assert_eq!(
+            Err(CompactError::UnexpectedEnd),
+            Value::from_compact_bytes(&bytes[..bytes.len() - 1])
+        );
+        assert_eq!(
+            Err(CompactError::InvalidTag(0x7f)),
+            Value::from_compact_bytes(b"JPB\x01\x7f")
+        );
+        assert_eq!(
+            Err(CompactError::InvalidUtf8),
+            Value::from_compact_bytes(b"JPB\x01\x04\x01\xff")
+        );
+        assert_eq!(
+            Err(CompactError::TrailingBytes),
+            Value::from_compact_bytes(b"JPB\x01\x00\x00")
+        );
+        assert_eq!(
+            Err(CompactError::UnexpectedEnd),
+            Value::from_compact_bytes(b"JPB\x01\x05\xff\xff\xff\xff\x0f")
+        );
+    }
+}
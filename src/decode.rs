@@ -0,0 +1,229 @@
+use std::{collections::HashMap, fmt, fmt::Display};
+
+use crate::parsing::{escape_key, widen, Value};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct DecodeError {
+    path: String,
+    expected: &'static str,
+    found: &'static str,
+    suggestion: Option<String>,
+}
+
+impl DecodeError {
+    pub fn new(expected: &'static str, found: &Value) -> Self {
+        DecodeError {
+            path: String::new(),
+            expected,
+            found: kind(found),
+            suggestion: None,
+        }
+    }
+
+    /// Suggests the key that was probably meant where a member was missing, such as one
+    /// found with [`crate::map::closest_key`].
+    pub fn with_suggestion(mut self, key: Option<&str>) -> Self {
+        self.suggestion = key.map(str::to_string);
+        self
+    }
+
+    /// JSON pointer to the value that failed to decode.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn suggestion(&self) -> Option<&str> {
+        self.suggestion.as_deref()
+    }
+
+    /// Prefixes the path with one segment, e.g. the key of the member that failed to decode.
+    pub fn at(mut self, segment: &str) -> Self {
+        self.path = format!("/{}{}", escape_key(segment), self.path);
+        self
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Expected {}, found {}", self.expected, self.found)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        if let Some(key) = &self.suggestion {
+            write!(f, ", did you mean {}?", Value::String(key.clone()))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a bool",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        #[cfg(feature = "bytes")]
+        Value::Bytes(_) => "bytes",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+pub trait FromValue: Sized {
+    fn from_value(value: &Value) -> Result<Self, DecodeError>;
+}
+
+impl Value {
+    pub fn decode<T: FromValue>(&self) -> Result<T, DecodeError> {
+        T::from_value(self)
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            _ => Err(DecodeError::new("a bool", value)),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::String(s) => Ok(s.clone()),
+            _ => Err(DecodeError::new("a string", value)),
+        }
+    }
+}
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Number(n) => Ok(widen(*n)),
+            _ => Err(DecodeError::new("a number", value)),
+        }
+    }
+}
+
+impl FromValue for f32 {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        f64::from_value(value).map(|n| n as f32)
+    }
+}
+
+macro_rules! impl_from_value_for_integer {
+    ($($t:ty),*) => {
+        $(
+            impl FromValue for $t {
+                fn from_value(value: &Value) -> Result<Self, DecodeError> {
+                    match f64::from_value(value) {
+                        Ok(n)
+                            if n.fract() == 0.0
+                                && n >= <$t>::MIN as f64
+                                // `MAX as f64` rounds up for 64-bit types, so compare
+                                // against the next power of two exclusively.
+                                && n < <$t>::MAX as f64 + 1.0 =>
+                        {
+                            Ok(n as $t)
+                        }
+                        _ => Err(DecodeError::new(
+                            concat!("an integer in range of ", stringify!($t)),
+                            value,
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_for_integer!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Array(elements) => elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| T::from_value(element).map_err(|e| e.at(&i.to_string())))
+                .collect(),
+            _ => Err(DecodeError::new("an array", value)),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for HashMap<String, T> {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        match value {
+            Value::Object(members) => members
+                .iter()
+                .map(|(key, member)| {
+                    T::from_value(member)
+                        .map(|member| (key.to_string(), member))
+                        .map_err(|e| e.at(key))
+                })
+                .collect(),
+            _ => Err(DecodeError::new("an object", value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::{Float, Parser};
+
+    #[test]
+    fn decode_nested_values() {
+        let value = Parser::parse(r#"{"a": [1, 2, null], "b": []}"#).unwrap();
+        let decoded = value.decode::<HashMap<String, Vec<Option<u8>>>>();
+        assert_eq!(
+            Ok(vec![
+                ("a".to_string(), vec![Some(1), Some(2), None]),
+                ("b".to_string(), Vec::new()),
+            ]
+            .into_iter()
+            .collect()),
+            decoded
+        );
+        assert_eq!(Ok(true), Value::Bool(true).decode::<bool>());
+        assert_eq!(Ok(-1.5), Value::Number(-1.5).decode::<f64>());
+    }
+
+    #[test]
+    fn fail_with_path() {
+        let value = Parser::parse(r#"{"a/b": [1, "2"]}"#).unwrap();
+        let error = value.decode::<HashMap<String, Vec<i32>>>().unwrap_err();
+        assert_eq!("/a~1b/1", error.path());
+        assert_eq!(
+            "Expected an integer in range of i32, found a string at /a~1b/1",
+            error.to_string()
+        );
+
+        assert!(Value::Number(256.0).decode::<u8>().is_err());
+        assert!(Value::Number(Float::powi(2.0, 64)).decode::<u64>().is_err());
+        assert!(Value::Number(1.5).decode::<i64>().is_err());
+        assert_eq!(
+            "Expected a string, found null",
+            Value::Null.decode::<String>().unwrap_err().to_string()
+        );
+    }
+}
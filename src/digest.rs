@@ -0,0 +1,177 @@
+use std::{collections::HashMap, fmt, fmt::Display, fmt::Write};
+
+use sha2::{Digest as _, Sha256};
+
+use crate::{
+    parsing::{escape_key, Value},
+    serialize,
+};
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct Digest(pub [u8; 32]);
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Value {
+    /// SHA-256 of the canonical serialization of a scalar, or for a container of the digests
+    /// of its elements, or of its keys and the digests of its members in key order. Documents
+    /// that differ only in whitespace, key order, or escaping share a digest, and each value
+    /// is hashed once however deeply it is nested.
+    pub fn digest(&self) -> Digest {
+        digest_subtrees(self, &mut String::new(), &mut |_, _| {})
+    }
+}
+
+/// The digest of `value`, calling `visit` with the pointer and digest of it and of every
+/// value inside it, children before their parents.
+fn digest_subtrees(
+    value: &Value,
+    path: &mut String,
+    visit: &mut impl FnMut(&str, Digest),
+) -> Digest {
+    let end = path.len();
+    let digest = match value {
+        Value::Array(elements) => {
+            let mut hasher = Sha256::new();
+            hasher.update(b"[");
+            for (i, element) in elements.iter().enumerate() {
+                write!(path, "/{i}").unwrap();
+                hasher.update(digest_subtrees(element, path, visit).0);
+                path.truncate(end);
+            }
+            Digest(hasher.finalize().into())
+        }
+        Value::Object(members) => {
+            let mut digests = Vec::with_capacity(members.len());
+            for (key, member) in members {
+                write!(path, "/{}", escape_key(key)).unwrap();
+                digests.push((&**key, digest_subtrees(member, path, visit)));
+                path.truncate(end);
+            }
+            digests.sort_by_key(|&(key, _)| key);
+            let mut hasher = Sha256::new();
+            hasher.update(b"{");
+            for (key, digest) in digests {
+                hasher.update((key.len() as u64).to_le_bytes());
+                hasher.update(key.as_bytes());
+                hasher.update(digest.0);
+            }
+            Digest(hasher.finalize().into())
+        }
+        scalar => {
+            let canonical = serialize::to_canonical_string(scalar);
+            Digest(Sha256::digest(canonical.as_bytes()).into())
+        }
+    };
+    visit(path, digest);
+    digest
+}
+
+/// The JSON pointer of every subtree in a document, grouped by digest, to find all the places
+/// the same value appears.
+pub struct SubtreeIndex {
+    paths: HashMap<Digest, Vec<String>>,
+}
+
+impl SubtreeIndex {
+    pub fn new(value: &Value) -> Self {
+        let mut paths = HashMap::<Digest, Vec<String>>::new();
+        digest_subtrees(value, &mut String::new(), &mut |path, digest| {
+            paths.entry(digest).or_default().push(path.to_string());
+        });
+        // Equal subtrees are never nested, so visiting children first leaves the pointers of
+        // each digest in document order all the same.
+        SubtreeIndex { paths }
+    }
+
+    /// Pointers to the subtrees with `digest`, in document order.
+    pub fn paths(&self, digest: &Digest) -> &[String] {
+        self.paths.get(digest).map_or(&[], Vec::as_slice)
+    }
+
+    /// Pointers to the subtrees equal to `value`, in document order.
+    pub fn find(&self, value: &Value) -> &[String] {
+        self.paths(&value.digest())
+    }
+
+    /// Every subtree that appears more than once, with the pointers to its occurrences.
+    pub fn repeated(&self) -> impl Iterator<Item = (&Digest, &[String])> {
+        self.paths
+            .iter()
+            .filter(|(_, paths)| paths.len() > 1)
+            .map(|(digest, paths)| (digest, paths.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn digest_of_canonical_form() {
+        // sha256("null")
+        assert_eq!(
+            "74234e98afe7498fb5daf1f36ac2d78acc339464f950703b8c019892f982b90b",
+            Value::Null.digest().to_string()
+        );
+    }
+
+    #[test]
+    fn equal_documents_share_a_digest() {
+        let a = Parser::parse(r#"{"b": [1.0, -0], "a": "x"}"#).unwrap();
+        let b = Parser::parse("{\"a\":\"x\",\n  \"b\":[1,0]}").unwrap();
+        let c = Parser::parse(r#"{"a": "y", "b": [1, 0]}"#).unwrap();
+
+        assert_eq!(a.digest(), b.digest());
+        assert_ne!(a.digest(), c.digest());
+    }
+
+    #[test]
+    fn index_subtrees_by_digest() {
+        let value = Parser::parse(
+            r#"{"a": {"x": 1, "y": [2]}, "b/c": [{"y": [2], "x": 1}, [2]], "d": {"x": 2}}"#,
+        )
+        .unwrap();
+        let index = SubtreeIndex::new(&value);
+
+        let object = Parser::parse(r#"{"x": 1, "y": [2]}"#).unwrap();
+        assert_eq!(vec!["/a", "/b~1c/0"], index.find(&object));
+        assert_eq!(
+            vec!["/a/y", "/b~1c/0/y", "/b~1c/1"],
+            index.find(&Parser::parse("[2]").unwrap())
+        );
+        assert_eq!(
+            vec!["/a/y/0", "/b~1c/0/y/0", "/b~1c/1/0", "/d/x"],
+            index.find(&Value::Number(2.0))
+        );
+        assert_eq!(vec![""], index.paths(&value.digest()));
+
+        assert!(index.find(&Value::Null).is_empty());
+
+        let mut repeated = index
+            .repeated()
+            .map(|(_, paths)| paths[0].as_str())
+            .collect::<Vec<_>>();
+        repeated.sort();
+        assert_eq!(vec!["/a", "/a/x", "/a/y", "/a/y/0"], repeated);
+        let value = Parser::parse(r#"{"b": {"x": 1}, "a": {"x": 1}}"#).unwrap();
+        let index = SubtreeIndex::new(&value);
+        let Value::Object(members) = &value else {
+            panic!("expected an object")
+        };
+        // Document order, which is key order under `btree-map`.
+        let in_order = members
+            .keys()
+            .map(|key| format!("/{key}"))
+            .collect::<Vec<_>>();
+        assert_eq!(in_order, index.find(&members["b"]));
+    }
+}
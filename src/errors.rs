@@ -1,4 +1,6 @@
-use std::{fmt, fmt::Display};
+use std::{fmt, fmt::Display, ops::BitOr};
+
+use crate::lexical::Span;
 
 #[derive(Debug, PartialEq, Clone, Copy)]
 pub enum ErrorCode {
@@ -7,10 +9,40 @@ pub enum ErrorCode {
     ExpectedColon,
     ExpectedCommaOrEndWhileParsing(char),
     KeyMustBeAString,
-    InvalidNumber,
+    /// The text that is not a number, cut short like [`Literal`], and where the whole of it is.
+    InvalidNumber(Literal, Span),
     EndOfFileExpected,
     EndOfFileWhileParsing(char),
     EndOfFileWhileParsingValue,
+    InvalidEscape,
+    StringTooLong,
+    NumberTooLong,
+    UnterminatedString,
+    NestingTooDeep,
+    ContainerTooLarge,
+    ControlCharacter,
+    InexactInteger,
+    DuplicateKey,
+    SimilarKey,
+    TrailingComma,
+    SingleQuotedString,
+    UnquotedKey,
+    DangerousKey,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
 }
 
 impl Display for ErrorCode {
@@ -23,11 +55,11 @@ impl Display for ErrorCode {
             ErrorCode::ExpectedColon => f.write_str("Expected ':'"),
             ErrorCode::ExpectedCommaOrEndWhileParsing(end) => match end {
                 ']' => f.write_str("Expected ',' or ']' while parsing array"),
-                '}' => f.write_str("Expected ',' or '}' whiel parsing object"),
+                '}' => f.write_str("Expected ',' or '}' while parsing object"),
                 _ => panic!("Only arrays or objects are supported"),
             },
             ErrorCode::KeyMustBeAString => f.write_str("Key must be a string"),
-            ErrorCode::InvalidNumber => write!(f, "Invalid number"),
+            ErrorCode::InvalidNumber(number, _) => write!(f, "Invalid number: {number}"),
             ErrorCode::EndOfFileWhileParsing(c) => match c {
                 ']' => f.write_str("End of file while parsing a list"),
                 '}' => f.write_str("End of file while parsing an object"),
@@ -37,19 +69,927 @@ impl Display for ErrorCode {
             ErrorCode::EndOfFileWhileParsingValue => {
                 f.write_str("End of file while parsing a value")
             }
+            ErrorCode::InvalidEscape => f.write_str("Invalid escape sequence in string"),
+            ErrorCode::StringTooLong => f.write_str("String is longer than the limit"),
+            ErrorCode::NumberTooLong => f.write_str("Number is longer than the limit"),
+            ErrorCode::UnterminatedString => {
+                f.write_str("String is not closed before the line ends")
+            }
+            ErrorCode::NestingTooDeep => f.write_str("Nesting is deeper than the limit"),
+            ErrorCode::ContainerTooLarge => f.write_str("Container has more items than the limit"),
+            ErrorCode::ControlCharacter => {
+                f.write_str("String contains an unescaped control character")
+            }
+            ErrorCode::InexactInteger => {
+                f.write_str("Integer cannot be represented exactly as a 64-bit float")
+            }
+            ErrorCode::DuplicateKey => f.write_str("Duplicate key"),
+            ErrorCode::SimilarKey => f.write_str("Key differs from another only by case or spaces"),
+            ErrorCode::TrailingComma => f.write_str("Trailing comma"),
+            ErrorCode::SingleQuotedString => f.write_str("String in single quotes"),
+            ErrorCode::UnquotedKey => f.write_str("Key is not in quotes"),
+            ErrorCode::DangerousKey => f.write_str("Key can pollute JavaScript prototypes"),
         }
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+impl ErrorCode {
+    pub fn invalid_number(text: &str, span: Span) -> Self {
+        ErrorCode::InvalidNumber(Literal::new(text), span)
+    }
+
+    pub fn id(&self) -> &'static str {
+        match self {
+            ErrorCode::ExpectedToken => "E001",
+            ErrorCode::ExpectedDoubleQuote => "E002",
+            ErrorCode::ExpectedColon => "E003",
+            ErrorCode::ExpectedCommaOrEndWhileParsing(_) => "E004",
+            ErrorCode::KeyMustBeAString => "E005",
+            ErrorCode::InvalidNumber(..) => "E006",
+            ErrorCode::EndOfFileExpected => "E007",
+            ErrorCode::EndOfFileWhileParsing(_) => "E008",
+            ErrorCode::EndOfFileWhileParsingValue => "E009",
+            ErrorCode::InvalidEscape => "E010",
+            ErrorCode::StringTooLong => "E011",
+            ErrorCode::NumberTooLong => "E012",
+            ErrorCode::UnterminatedString => "E013",
+            ErrorCode::NestingTooDeep => "E014",
+            ErrorCode::ContainerTooLarge => "E015",
+            ErrorCode::ControlCharacter => "E016",
+            ErrorCode::InexactInteger => "W001",
+            ErrorCode::DuplicateKey => "W002",
+            ErrorCode::SimilarKey => "W003",
+            ErrorCode::TrailingComma => "W004",
+            ErrorCode::SingleQuotedString => "W005",
+            ErrorCode::UnquotedKey => "W006",
+            ErrorCode::DangerousKey => "W007",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ErrorCode::ExpectedToken => "ExpectedToken",
+            ErrorCode::ExpectedDoubleQuote => "ExpectedDoubleQuote",
+            ErrorCode::ExpectedColon => "ExpectedColon",
+            ErrorCode::ExpectedCommaOrEndWhileParsing(_) => "ExpectedCommaOrEndWhileParsing",
+            ErrorCode::KeyMustBeAString => "KeyMustBeAString",
+            ErrorCode::InvalidNumber(..) => "InvalidNumber",
+            ErrorCode::EndOfFileExpected => "EndOfFileExpected",
+            ErrorCode::EndOfFileWhileParsing(_) => "EndOfFileWhileParsing",
+            ErrorCode::EndOfFileWhileParsingValue => "EndOfFileWhileParsingValue",
+            ErrorCode::InvalidEscape => "InvalidEscape",
+            ErrorCode::StringTooLong => "StringTooLong",
+            ErrorCode::NumberTooLong => "NumberTooLong",
+            ErrorCode::UnterminatedString => "UnterminatedString",
+            ErrorCode::NestingTooDeep => "NestingTooDeep",
+            ErrorCode::ContainerTooLarge => "ContainerTooLarge",
+            ErrorCode::ControlCharacter => "ControlCharacter",
+            ErrorCode::InexactInteger => "InexactInteger",
+            ErrorCode::DuplicateKey => "DuplicateKey",
+            ErrorCode::SimilarKey => "SimilarKey",
+            ErrorCode::TrailingComma => "TrailingComma",
+            ErrorCode::SingleQuotedString => "SingleQuotedString",
+            ErrorCode::UnquotedKey => "UnquotedKey",
+            ErrorCode::DangerousKey => "DangerousKey",
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        match self {
+            ErrorCode::InexactInteger
+            | ErrorCode::DuplicateKey
+            | ErrorCode::SimilarKey
+            | ErrorCode::TrailingComma
+            | ErrorCode::SingleQuotedString
+            | ErrorCode::UnquotedKey
+            | ErrorCode::DangerousKey => Severity::Warning,
+            _ => Severity::Error,
+        }
+    }
+
+    /// What the code means, with an example and how to fix it.
+    pub fn explanation(&self) -> &'static str {
+        explain(self.id()).expect("every code to be explained")
+    }
+}
+
+/// The extended description of each code, by id, for `json-parser explain`: what was wrong,
+/// an example, and how to fix it.
+const EXPLANATIONS: [(&str, &str); 23] = [
+    (
+        "E001",
+        r#"A value was expected but something else was found, such as a bare word, a
+closing bracket, or a comma with nothing before it.
+
+    {"name": John, "tags": [1, , 3]}
+
+Values are objects, arrays, strings in double quotes, numbers, `true`, `false`, or
+`null`. Put text in double quotes, and remove stray commas:
+
+    {"name": "John", "tags": [1, 3]}"#,
+    ),
+    (
+        "E002",
+        r#"The document ended inside a string, before its closing double quote.
+
+    {"a": "abc
+
+Close the string, and check that the whole document was written:
+
+    {"a": "abc"}"#,
+    ),
+    (
+        "E003",
+        r#"An object key was not followed by a colon.
+
+    {"a" = 1}
+
+Separate each key from its value with `:`:
+
+    {"a": 1}"#,
+    ),
+    (
+        "E004",
+        r#"An element of an array or a member of an object was not followed by a comma or
+the closing bracket, usually because a comma is missing between two items.
+
+    {"a": 1 "b": [1 2]}
+
+Separate items with commas:
+
+    {"a": 1, "b": [1, 2]}"#,
+    ),
+    (
+        "E005",
+        r#"An object key was not a string. Keys must be strings in double quotes, even
+when they look like numbers or identifiers.
+
+    {1: "one", name: "x"}
+
+Quote the key:
+
+    {"1": "one", "name": "x"}
+
+Unquoted identifier keys are accepted with the `allow_unquoted_keys` parse option."#,
+    ),
+    (
+        "E006",
+        r#"A number does not follow the JSON grammar. Leading zeros, a leading `+` or `.`, a
+trailing `.`, hexadecimal, `NaN`, and `Infinity` are not allowed.
+
+    {"a": 01, "b": .5, "c": 1., "d": 0x1F}
+
+Write numbers in decimal with at least one digit on each side of the point, or put values
+that are not numbers in strings:
+
+    {"a": 1, "b": 0.5, "c": 1.0, "d": 31}"#,
+    ),
+    (
+        "E007",
+        r#"The document went on after its value ended, such as a second value or an extra
+closing bracket. A document holds exactly one value.
+
+    {"a": 1} {"b": 2}
+
+Wrap several values in an array, or write them as NDJSON, one per line:
+
+    [{"a": 1}, {"b": 2}]"#,
+    ),
+    (
+        "E008",
+        r#"The document ended before an object or array was closed, often because it was
+cut off or a closing bracket is missing.
+
+    {"a": [1, 2
+
+Close every container, innermost first:
+
+    {"a": [1, 2]}"#,
+    ),
+    (
+        "E009",
+        r#"The document ended where a value was expected, such as after a colon or a
+comma, or the document is empty.
+
+    {"a":
+
+Give the member a value, or check that the whole document was written:
+
+    {"a": null}"#,
+    ),
+    (
+        "E010",
+        r#"A string has a backslash that does not start a valid escape. The escapes are
+`\"`, `\\`, `\/`, `\b`, `\f`, `\n`, `\r`, `\t`, and `\u` with four hex digits.
+
+    {"path": "C:\Users\x"}
+
+Double the backslash to write one:
+
+    {"path": "C:\\Users\\x"}"#,
+    ),
+    (
+        "E011",
+        r#"A string is longer than the `max_string_len` parse option allows, which is 10
+bytes, quotes included, in this example. A string whose closing quote is missing can run
+on to the limit as well, with the rest of the document read as part of it.
+
+    {"a": "a much longer value"}
+
+Shorten the string, or raise the limit if such long strings are expected:
+
+    {"a": "short"}"#,
+    ),
+    (
+        "E012",
+        r#"A number has more characters than the `max_number_len` parse option allows,
+which is 20 in this example.
+
+    {"id": 123456789012345678901234567890}
+
+Write identifiers and other long digit strings as strings, or raise the limit:
+
+    {"id": "123456789012345678901234567890"}"#,
+    ),
+    (
+        "E013",
+        r#"A string is not closed before the end of its line. Strings cannot contain raw
+line breaks.
+
+    {"a": "first
+    second"}
+
+Close the string, or write the line break as `\n`:
+
+    {"a": "first\nsecond"}"#,
+    ),
+    (
+        "E014",
+        r#"Objects and arrays are nested deeper than the `max_depth` of the `limits` parse
+option, which is 3 in this example. The limit protects against documents built to exhaust
+the stack.
+
+    [[[[1]]]]
+
+Flatten the structure, or raise `max_depth` if such documents are expected:
+
+    [[1]]"#,
+    ),
+    (
+        "E015",
+        r#"An object or array has more members or elements than the `max_len` of the
+`limits` parse option, which is 3 in this example. The container is skipped without being
+built.
+
+    [1, 2, 3, 4]
+
+Split the data into smaller documents, such as NDJSON records, or raise `max_len`:
+
+    [1, 2, 3]"#,
+    ),
+    (
+        "E016",
+        r#"A string contains a control character, U+0000 to U+001F, that is not escaped,
+such as the raw tab between `one` and `two` here.
+
+    {"a": "one	two"}
+
+Escape it, as `\t`, `\n`, `\r`, `\b`, `\f`, or `\u` and four hex digits:
+
+    {"a": "one\ttwo"}"#,
+    ),
+    (
+        "W001",
+        r#"An integer has more digits than a 64-bit float holds exactly, so it will be
+read as a nearby but different number.
+
+    {"id": 9007199254740993}
+
+This is read as 9007199254740992. Write identifiers as strings to keep every digit:
+
+    {"id": "9007199254740993"}"#,
+    ),
+    (
+        "W002",
+        r#"An object has the same key more than once. Only the last value is kept, and
+other parsers may keep a different one.
+
+    {"a": 1, "a": 2}
+
+Remove or rename one of the members:
+
+    {"a": 2}"#,
+    ),
+    (
+        "W003",
+        r#"Two keys of an object differ only by case or surrounding spaces, which is
+usually a typo that leaves one of them unread.
+
+    {"userId": 1, "userid": 2}
+
+Use one spelling for each key:
+
+    {"userId": 1}"#,
+    ),
+    (
+        "W004",
+        r#"An array or object has a comma after its last item, which strict JSON does not
+allow. It is accepted with the `allow_trailing_commas` parse option.
+
+    [1, 2, ]
+
+Remove the comma:
+
+    [1, 2]"#,
+    ),
+    (
+        "W005",
+        r#"A string is in single quotes, which strict JSON does not allow. It is accepted
+with the `allow_single_quotes` parse option.
+
+    {'a': 'b'}
+
+Use double quotes:
+
+    {"a": "b"}"#,
+    ),
+    (
+        "W006",
+        r#"An object key is not in quotes, which strict JSON does not allow. It is
+accepted with the `allow_unquoted_keys` parse option.
+
+    {a: 1}
+
+Quote the key:
+
+    {"a": 1}"#,
+    ),
+    (
+        "W007",
+        r#"An object has a key named `__proto__`, `constructor`, or `prototype`. JavaScript
+code that merges or copies parsed objects member by member can set the prototype of every
+object through such a key, which is a common attack on services written in JavaScript.
+
+    {"__proto__": {"isAdmin": true}}
+
+Rename the key, or reject such documents before they reach JavaScript code. This is
+reported when the `dangerous_keys` parse option is `Warn`, as `validate` sets it, and is
+an error when it is `Reject`."#,
+    ),
+];
+
+/// The extended description of the code with `id`, such as `E007`, ignoring case.
+pub fn explain(id: &str) -> Option<&'static str> {
+    EXPLANATIONS
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(id))
+        .map(|(_, explanation)| *explanation)
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Expected(u8);
+
+impl Expected {
+    pub const NOTHING: Expected = Expected(0);
+    pub const VALUE: Expected = Expected(1 << 0);
+    pub const KEY: Expected = Expected(1 << 1);
+    pub const COLON: Expected = Expected(1 << 2);
+    pub const COMMA: Expected = Expected(1 << 3);
+    pub const END_OF_ARRAY: Expected = Expected(1 << 4);
+    pub const END_OF_OBJECT: Expected = Expected(1 << 5);
+    pub const END_OF_FILE: Expected = Expected(1 << 6);
+
+    const NAMES: [&'static str; 7] = [
+        "a value",
+        "a string key",
+        "':'",
+        "','",
+        "']'",
+        "'}'",
+        "end of file",
+    ];
+
+    pub fn end_of(end: char) -> Expected {
+        match end {
+            '}' => Expected::END_OF_OBJECT,
+            _ => Expected::END_OF_ARRAY,
+        }
+    }
+
+    pub fn element_of(end: char) -> Expected {
+        match end {
+            '}' => Expected::KEY,
+            _ => Expected::VALUE,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn contains(&self, other: Expected) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl BitOr for Expected {
+    type Output = Expected;
+
+    fn bitor(self, rhs: Expected) -> Expected {
+        Expected(self.0 | rhs.0)
+    }
+}
+
+impl Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = Expected::NAMES
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| self.0 & (1 << i) != 0)
+            .map(|(_, name)| *name)
+            .collect::<Vec<&str>>();
+        match names.as_slice() {
+            [] => Ok(()),
+            [name] => f.write_str(name),
+            [first, second] => write!(f, "{first} or {second}"),
+            [rest @ .., last] => write!(f, "{}, or {last}", rest.join(", ")),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Error {
     code: ErrorCode,
     line: usize,
     col: usize,
+    path: String,
+    expected: Expected,
+    found: String,
+    detail: String,
+    severity: Severity,
 }
 
 impl Error {
+    pub const MAX_LITERAL_CHARS: usize = 32;
+
     pub fn new(code: ErrorCode, line: usize, col: usize) -> Self {
-        Error { code, line, col }
+        Error {
+            code,
+            line,
+            col,
+            path: String::new(),
+            expected: Expected::NOTHING,
+            found: String::new(),
+            detail: String::new(),
+            severity: code.severity(),
+        }
+    }
+
+    /// Extra context appended to the message, such as the offending value.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+
+    /// The offending text of the input as the detail, cut short after
+    /// [`Error::MAX_LITERAL_CHARS`] characters so a huge token does not make a huge message.
+    pub fn with_literal(self, literal: &str) -> Self {
+        self.with_detail(truncate(literal))
+    }
+
+    /// The token that was read where [`Error::expected`] was wanted, such as `'}'`, cut
+    /// short like [`Error::with_literal`].
+    pub fn with_found(mut self, token: &str) -> Self {
+        self.found = truncate(token);
+        self
+    }
+
+    pub fn with_expected(mut self, expected: Expected) -> Self {
+        self.expected = expected;
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = path.into();
+        self
+    }
+
+    /// Overrides the severity of the error's code, such as to treat a warning as an error.
+    pub fn with_severity(mut self, severity: Severity) -> Self {
+        self.severity = severity;
+        self
+    }
+
+    /// Moves the error down by `lines`, for a document that starts partway through a larger
+    /// input such as NDJSON.
+    pub fn offset_lines(mut self, lines: usize) -> Self {
+        self.line += lines;
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn col(&self) -> usize {
+        self.col
+    }
+
+    /// JSON pointer to the value that was being parsed when the error occurred.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// JSON pointer to the object or array the error is in: its path for errors in the
+    /// container's own punctuation, such as a missing comma, or else the parent of its path.
+    pub fn container(&self) -> &str {
+        match self.code {
+            ErrorCode::ExpectedColon
+            | ErrorCode::ExpectedCommaOrEndWhileParsing(_)
+            | ErrorCode::KeyMustBeAString
+            | ErrorCode::EndOfFileWhileParsing(_)
+            | ErrorCode::ContainerTooLarge
+            | ErrorCode::TrailingComma => &self.path,
+            _ => self.path.rfind('/').map_or("", |end| &self.path[..end]),
+        }
+    }
+
+    /// The tokens that would have been valid where the error occurred.
+    pub fn expected(&self) -> Expected {
+        self.expected
+    }
+
+    pub fn explanation(&self) -> &'static str {
+        self.code.explanation()
+    }
+
+    /// The offending token, if one was read.
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    pub fn message(&self) -> String {
+        let message = match self.code {
+            _ if self.expected.is_empty() => self.code.to_string(),
+            ErrorCode::EndOfFileExpected => self.code.to_string(),
+            ErrorCode::EndOfFileWhileParsing(_) | ErrorCode::EndOfFileWhileParsingValue => {
+                format!("{}, expected {}", self.code, self.expected)
+            }
+            ErrorCode::ExpectedToken
+            | ErrorCode::ExpectedDoubleQuote
+            | ErrorCode::ExpectedColon
+            | ErrorCode::ExpectedCommaOrEndWhileParsing(_)
+            | ErrorCode::KeyMustBeAString => format!("Expected {}", self.expected),
+            // Errors in a token say what is wrong with it, which says more than what was
+            // expected in its place.
+            _ => self.code.to_string(),
+        };
+        let message = match self.found.as_str() {
+            "" => message,
+            found => format!("{message} but found {found}"),
+        };
+        if self.detail.is_empty() {
+            message
+        } else {
+            format!("{message}: {}", self.detail)
+        }
+    }
+}
+
+/// The start of an offending token, at most [`Error::MAX_LITERAL_CHARS`] bytes of it cut at a
+/// character boundary, held inline so that [`ErrorCode`] stays `Copy`. Displays with `…`
+/// after it if it was cut.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct Literal {
+    bytes: [u8; Error::MAX_LITERAL_CHARS],
+    len: u8,
+    truncated: bool,
+}
+
+impl Literal {
+    pub fn new(text: &str) -> Self {
+        let len = text
+            .char_indices()
+            .map(|(i, c)| i + c.len_utf8())
+            .take_while(|&end| end <= Error::MAX_LITERAL_CHARS)
+            .last()
+            .unwrap_or(0);
+        let mut bytes = [0; Error::MAX_LITERAL_CHARS];
+        bytes[..len].copy_from_slice(&text.as_bytes()[..len]);
+        Literal {
+            bytes,
+            len: len as u8,
+            truncated: len < text.len(),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        std::str::from_utf8(&self.bytes[..usize::from(self.len)])
+            .expect("literals to be cut at character boundaries")
+    }
+
+    /// Whether the token was longer than what is kept of it.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())?;
+        if self.truncated {
+            f.write_str("…")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Literal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.to_string(), f)
+    }
+}
+
+fn truncate(literal: &str) -> String {
+    match literal.char_indices().nth(Error::MAX_LITERAL_CHARS) {
+        Some((end, _)) => format!("{}…", &literal[..end]),
+        None => literal.to_string(),
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.col)?;
+        if self.severity() == Severity::Warning {
+            write!(f, "{}: ", self.severity())?;
+        }
+        write!(f, "{}", self.message())?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Groups `errors` by [`Error::container`], ordered by each container's first error, with
+/// the errors of a group in their original order.
+pub fn group_by_container(errors: &[Error]) -> Vec<(&str, Vec<&Error>)> {
+    let mut groups = Vec::<(&str, Vec<&Error>)>::new();
+    for error in errors {
+        match groups
+            .iter_mut()
+            .find(|(container, _)| *container == error.container())
+        {
+            Some((_, group)) => group.push(error),
+            None => groups.push((error.container(), vec![error])),
+        }
+    }
+    groups
+}
+
+/// Every error reported for one document, as a single value for APIs that expect one error
+/// type such as [`std::str::FromStr`]. Displays as the first error.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ParseError {
+    errors: Vec<Error>,
+}
+
+impl ParseError {
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+
+    pub fn into_errors(self) -> Vec<Error> {
+        self.errors
+    }
+}
+
+impl From<Vec<Error>> for ParseError {
+    fn from(errors: Vec<Error>) -> Self {
+        ParseError { errors }
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.errors.as_slice() {
+            [] => f.write_str("Invalid JSON"),
+            [error] => write!(f, "{error}"),
+            [error, rest @ ..] => write!(f, "{error} (and {} more errors)", rest.len()),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::{DangerousKeys, ParseOptions, Parser};
+
+    #[test]
+    fn explain_every_code() {
+        for (i, (id, explanation)) in EXPLANATIONS.iter().enumerate() {
+            assert!(!explanation.is_empty());
+            assert!(EXPLANATIONS[..i].iter().all(|(other, _)| other != id));
+        }
+        assert_eq!(
+            Some(ErrorCode::EndOfFileExpected.explanation()),
+            explain("e007")
+        );
+        assert!(Error::new(ErrorCode::UnquotedKey, 1, 1)
+            .explanation()
+            .contains("{a: 1}"));
+        assert_eq!(None, explain("E999"));
+    }
+
+    /// The options that the example in the explanation of `code` is parsed with.
+    fn example_options(code: ErrorCode) -> ParseOptions {
+        let mut options = ParseOptions::default();
+        match code {
+            ErrorCode::StringTooLong => options.max_string_len = Some(10),
+            ErrorCode::NumberTooLong => options.max_number_len = Some(20),
+            ErrorCode::NestingTooDeep => options.limits.max_depth = Some(3),
+            ErrorCode::ContainerTooLarge => options.limits.max_len = Some(3),
+            ErrorCode::DangerousKey => options.dangerous_keys = DangerousKeys::Warn,
+            ErrorCode::TrailingComma => options.allow_trailing_commas = true,
+            ErrorCode::SingleQuotedString => options.allow_single_quotes = true,
+            ErrorCode::UnquotedKey => options.allow_unquoted_keys = true,
+            ErrorCode::ExpectedToken
+            | ErrorCode::ExpectedDoubleQuote
+            | ErrorCode::ExpectedColon
+            | ErrorCode::ExpectedCommaOrEndWhileParsing(_)
+            | ErrorCode::KeyMustBeAString
+            | ErrorCode::InvalidNumber(..)
+            | ErrorCode::EndOfFileExpected
+            | ErrorCode::EndOfFileWhileParsing(_)
+            | ErrorCode::EndOfFileWhileParsingValue
+            | ErrorCode::InvalidEscape
+            | ErrorCode::UnterminatedString
+            | ErrorCode::ControlCharacter
+            | ErrorCode::InexactInteger
+            | ErrorCode::DuplicateKey
+            | ErrorCode::SimilarKey => {}
+        }
+        options
+    }
+
+    #[test]
+    fn explanation_examples_report_their_code() {
+        let codes = [
+            ErrorCode::ExpectedToken,
+            ErrorCode::ExpectedDoubleQuote,
+            ErrorCode::ExpectedColon,
+            ErrorCode::ExpectedCommaOrEndWhileParsing('}'),
+            ErrorCode::KeyMustBeAString,
+            ErrorCode::invalid_number("01", Span::new(6, 8, 1, 7)),
+            ErrorCode::EndOfFileExpected,
+            ErrorCode::EndOfFileWhileParsing(']'),
+            ErrorCode::EndOfFileWhileParsingValue,
+            ErrorCode::InvalidEscape,
+            ErrorCode::StringTooLong,
+            ErrorCode::NumberTooLong,
+            ErrorCode::UnterminatedString,
+            ErrorCode::NestingTooDeep,
+            ErrorCode::ContainerTooLarge,
+            ErrorCode::ControlCharacter,
+            ErrorCode::InexactInteger,
+            ErrorCode::DuplicateKey,
+            ErrorCode::SimilarKey,
+            ErrorCode::TrailingComma,
+            ErrorCode::SingleQuotedString,
+            ErrorCode::UnquotedKey,
+            ErrorCode::DangerousKey,
+        ];
+        assert_eq!(EXPLANATIONS.len(), codes.len());
+        let mut failures = Vec::<String>::new();
+        for code in codes {
+            // The first indented block is the mistake; the last, if any, is the fix.
+            let explanation = code.explanation();
+            let blocks = explanation
+                .split("\n\n")
+                .filter(|block| block.starts_with("    "))
+                .map(|block| {
+                    block
+                        .lines()
+                        .map(|line| line.strip_prefix("    ").unwrap_or(line))
+                        .collect::<Vec<&str>>()
+                        .join("\n")
+                })
+                .collect::<Vec<String>>();
+            let options = example_options(code);
+            let diagnostics = |json: &str| match Parser::parse_with_options(json, options) {
+                Ok((_, warnings)) => warnings,
+                Err(errors) => errors,
+            };
+
+            let example = blocks.first().expect("an example");
+            let found = diagnostics(example).first().map(Error::code);
+            if found != Some(code) {
+                failures.push(format!("{}: {example:?} reports {found:?}", code.id()));
+            }
+            if let [_, .., fix] = blocks.as_slice() {
+                if let Some(error) = diagnostics(fix).first() {
+                    failures.push(format!("{}: fix {fix:?} reports {error}", code.id()));
+                }
+            }
+        }
+        assert_eq!(Vec::<String>::new(), failures);
+    }
+
+    #[test]
+    fn group_errors_by_container() {
+        let errors = [
+            Error::new(ErrorCode::ExpectedToken, 1, 1).with_path("/servers/2/port"),
+            Error::new(ErrorCode::ExpectedColon, 2, 1).with_path("/servers/0"),
+            Error::new(ErrorCode::TrailingComma, 3, 1).with_path("/servers/2"),
+            Error::new(ErrorCode::ExpectedToken, 4, 1).with_path("/a"),
+            Error::new(ErrorCode::EndOfFileExpected, 5, 1),
+        ];
+        let groups = group_by_container(&errors)
+            .into_iter()
+            .map(|(container, group)| (container, group.iter().map(|e| e.line()).collect()))
+            .collect::<Vec<(&str, Vec<usize>)>>();
+        assert_eq!(
+            vec![
+                ("/servers/2", vec![1, 3]),
+                ("/servers/0", vec![2]),
+                ("", vec![4, 5])
+            ],
+            groups
+        );
+    }
+
+    #[test]
+    fn display_expected_sets() {
+        assert_eq!("", Expected::NOTHING.to_string());
+        assert_eq!("a value", Expected::VALUE.to_string());
+        assert_eq!(
+            "',' or ']'",
+            (Expected::COMMA | Expected::END_OF_ARRAY).to_string()
+        );
+        assert_eq!(
+            "a string key, ':', or end of file",
+            (Expected::END_OF_FILE | Expected::KEY | Expected::COLON).to_string()
+        );
+    }
+
+    #[test]
+    fn message_uses_expected_set() {
+        let error = Error::new(ErrorCode::ExpectedToken, 1, 2);
+        assert_eq!(
+            "1:2: Expected a JSON object, array, string, number, bool, or null.",
+            error.to_string()
+        );
+
+        let error = error.with_expected(Expected::COMMA | Expected::END_OF_OBJECT);
+        assert_eq!("1:2: Expected ',' or '}'", error.to_string());
+        let found = error.clone().with_found(&format!("\"{}\"", "a".repeat(40)));
+        assert_eq!(
+            format!("1:2: Expected ',' or '}}' but found \"{}…", "a".repeat(31)),
+            found.to_string()
+        );
+
+        let error = Error::new(ErrorCode::EndOfFileWhileParsing(']'), 3, 4)
+            .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+            .with_path("/a");
+        assert_eq!(
+            "3:4: End of file while parsing a list, expected ',' or ']' at /a",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn cut_literals_at_character_boundaries() {
+        let literal = Literal::new("1x");
+        assert_eq!(("1x", false), (literal.as_str(), literal.is_truncated()));
+        assert_eq!("1x", literal.to_string());
+
+        let literal = Literal::new(&format!("1{}", "é".repeat(20)));
+        assert_eq!(format!("1{}", "é".repeat(15)), literal.as_str());
+        assert_eq!(format!("1{}…", "é".repeat(15)), literal.to_string());
+    }
+
+    #[test]
+    fn message_keeps_text_of_token_errors() {
+        let errors = Parser::parse("[\"abc\n, 1]").unwrap_err();
+        assert_eq!(
+            "1:2: String is not closed before the line ends at /0",
+            errors[0].to_string()
+        );
+        assert_eq!(Expected::VALUE, errors[0].expected());
+
+        let errors = Parser::parse("[1, -]").unwrap_err();
+        assert_eq!("1:5: Invalid number: - at /1", errors[0].to_string());
     }
 }
@@ -0,0 +1,324 @@
+use std::{
+    collections::HashMap,
+    fmt,
+    fmt::{Display, Write},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    map::Map,
+    parsing::{escape_key, Parser, Value},
+};
+
+/// What [`Value::expand`] does. Both expansions are off by default.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ExpandOptions {
+    /// Replaces `${NAME}` in strings with the variable's value, such as from
+    /// `std::env::vars().collect()`. `$${` is a literal `${`.
+    pub vars: Option<HashMap<String, String>>,
+    /// Replaces each `{"$include": "file.json"}` object with the document in the file, itself
+    /// expanded. The file is relative to the including document, and must be inside this
+    /// directory, with the top-level document treated as being in it.
+    pub include_dir: Option<PathBuf>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ExpandErrorKind {
+    UndefinedVariable(String),
+    UnclosedPlaceholder,
+    /// An include that resolves to a file outside [`ExpandOptions::include_dir`].
+    IncludeOutsideDir(String),
+    /// An include of a file that is already being included.
+    IncludeCycle(String),
+    UnreadableInclude(String),
+    InvalidInclude(String),
+}
+
+impl Display for ExpandErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExpandErrorKind::UndefinedVariable(name) => write!(f, "Undefined variable {name}"),
+            ExpandErrorKind::UnclosedPlaceholder => f.write_str("Placeholder is missing its }"),
+            ExpandErrorKind::IncludeOutsideDir(file) => {
+                write!(f, "{file} is outside the include directory")
+            }
+            ExpandErrorKind::IncludeCycle(file) => write!(f, "{file} includes itself"),
+            ExpandErrorKind::UnreadableInclude(file) => write!(f, "Could not read {file}"),
+            ExpandErrorKind::InvalidInclude(file) => write!(f, "{file} is not valid JSON"),
+        }
+    }
+}
+
+/// The first placeholder or include that could not be expanded.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExpandError {
+    path: String,
+    kind: ExpandErrorKind,
+}
+
+impl ExpandError {
+    fn new(path: &str, kind: ExpandErrorKind) -> Self {
+        ExpandError {
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    /// JSON pointer to the string or include, counting from the top-level document through
+    /// any includes.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn kind(&self) -> &ExpandErrorKind {
+        &self.kind
+    }
+}
+
+impl Display for ExpandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ExpandError {}
+
+impl Value {
+    /// Expands variables and includes as `options` says. Either everything is expanded or, on
+    /// the first error, `self` is left unchanged.
+    pub fn expand(&mut self, options: &ExpandOptions) -> Result<(), ExpandError> {
+        let root = match &options.include_dir {
+            Some(dir) => Some(dir.canonicalize().map_err(|_| {
+                ExpandError::new(
+                    "",
+                    ExpandErrorKind::UnreadableInclude(dir.display().to_string()),
+                )
+            })?),
+            None => None,
+        };
+        let mut expander = Expander {
+            vars: options.vars.as_ref(),
+            root,
+            including: Vec::new(),
+        };
+        let dir = expander.root.clone().unwrap_or_default();
+
+        let mut expanded = self.clone();
+        expander.expand(&mut expanded, &dir, &mut String::new())?;
+        *self = expanded;
+        Ok(())
+    }
+}
+
+struct Expander<'a> {
+    vars: Option<&'a HashMap<String, String>>,
+    /// The canonical include directory, if includes are expanded.
+    root: Option<PathBuf>,
+    /// The files being included, outermost first.
+    including: Vec<PathBuf>,
+}
+
+impl Expander<'_> {
+    fn expand(
+        &mut self,
+        value: &mut Value,
+        dir: &Path,
+        path: &mut String,
+    ) -> Result<(), ExpandError> {
+        let end = path.len();
+        match value {
+            Value::String(s) => {
+                if let Some(vars) = self.vars {
+                    if s.contains('$') {
+                        *s = substitute(s, vars).map_err(|kind| ExpandError::new(path, kind))?;
+                    }
+                }
+            }
+            Value::Array(elements) => {
+                for (i, element) in elements.iter_mut().enumerate() {
+                    write!(path, "/{i}").unwrap();
+                    self.expand(element, dir, path)?;
+                    path.truncate(end);
+                }
+            }
+            Value::Object(members) => match include_target(members) {
+                Some(file) if self.root.is_some() => {
+                    *value = self.include(file, dir, path)?;
+                }
+                _ => {
+                    for (key, member) in members.iter_mut() {
+                        write!(path, "/{}", escape_key(key)).unwrap();
+                        self.expand(member, dir, path)?;
+                        path.truncate(end);
+                    }
+                }
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn include(&mut self, file: &str, dir: &Path, path: &mut String) -> Result<Value, ExpandError> {
+        let error =
+            |kind: fn(String) -> ExpandErrorKind| ExpandError::new(path, kind(file.to_string()));
+        let target = dir
+            .join(file)
+            .canonicalize()
+            .map_err(|_| error(ExpandErrorKind::UnreadableInclude))?;
+        if !self
+            .root
+            .as_ref()
+            .is_some_and(|root| target.starts_with(root))
+        {
+            return Err(error(ExpandErrorKind::IncludeOutsideDir));
+        }
+        if self.including.contains(&target) {
+            return Err(error(ExpandErrorKind::IncludeCycle));
+        }
+
+        let json =
+            fs::read_to_string(&target).map_err(|_| error(ExpandErrorKind::UnreadableInclude))?;
+        let mut included =
+            Parser::parse(&json).map_err(|_| error(ExpandErrorKind::InvalidInclude))?;
+        let dir = target.parent().unwrap_or(&target).to_path_buf();
+        self.including.push(target);
+        self.expand(&mut included, &dir, path)?;
+        self.including.pop();
+        Ok(included)
+    }
+}
+
+/// The file of an object that is only an include.
+fn include_target(members: &Map) -> Option<&str> {
+    match members.get("$include") {
+        Some(Value::String(file)) if members.len() == 1 => Some(file),
+        _ => None,
+    }
+}
+
+fn substitute(s: &str, vars: &HashMap<String, String>) -> Result<String, ExpandErrorKind> {
+    let mut expanded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        expanded.push_str(&rest[..start]);
+        let name_end = rest[start..]
+            .find('}')
+            .ok_or(ExpandErrorKind::UnclosedPlaceholder)?;
+        let name = &rest[start + 2..start + name_end];
+        let value = vars
+            .get(name)
+            .ok_or_else(|| ExpandErrorKind::UndefinedVariable(name.to_string()))?;
+        expanded.push_str(value);
+        rest = &rest[start + name_end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> HashMap<String, String> {
+        [("HOST", "db.local"), ("PORT", "5432")]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn expand_variables() {
+        let options = ExpandOptions {
+            vars: Some(vars()),
+            ..ExpandOptions::default()
+        };
+        let mut value =
+            Parser::parse(r#"{"raw": ["$${HOST}", "$5"], "url": "${HOST}:${PORT}"}"#).unwrap();
+        value.expand(&options).unwrap();
+        assert_eq!(
+            r#"{"raw":["${HOST}","$5"],"url":"db.local:5432"}"#,
+            value.to_string()
+        );
+
+        let mut value = Parser::parse(r#"{"a": ["${HOME}"], "b": "${PORT"}"#).unwrap();
+        let error = value.expand(&options).unwrap_err();
+        assert_eq!("Undefined variable HOME at /a/0", error.to_string());
+        assert_eq!(r#"{"a":["${HOME}"],"b":"${PORT"}"#, value.to_string());
+        let mut value = Value::String("${PORT".to_string());
+        assert_eq!(
+            &ExpandErrorKind::UnclosedPlaceholder,
+            value.expand(&options).unwrap_err().kind()
+        );
+    }
+
+    #[test]
+    fn resolve_includes() {
+        let dir = std::env::temp_dir().join(format!("expand-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(
+            dir.join("db.json"),
+            r#"{"host": "${HOST}", "more": {"$include": "sub/port.json"}}"#,
+        )
+        .unwrap();
+        fs::write(dir.join("sub/port.json"), r#""${PORT}""#).unwrap();
+        fs::write(
+            dir.join("sub/loop.json"),
+            r#"[{"$include": "../loop.json"}]"#,
+        )
+        .unwrap();
+        fs::write(dir.join("loop.json"), r#"{"$include": "sub/loop.json"}"#).unwrap();
+        let secret = format!("expand-{}-secret.json", std::process::id());
+        fs::write(dir.join("..").join(&secret), "{}").unwrap();
+        fs::write(
+            dir.join("sub/escape.json"),
+            format!(r#"{{"$include": "../../{secret}"}}"#),
+        )
+        .unwrap();
+
+        let options = ExpandOptions {
+            vars: Some(vars()),
+            include_dir: Some(dir.clone()),
+        };
+        let expanded = |json: &str| {
+            let mut value = Parser::parse(json).unwrap();
+            value.expand(&options).map(|_| value.to_string())
+        };
+        assert_eq!(
+            Ok(r#"{"db":{"host":"db.local","more":"5432"},"keep":{"$include":1}}"#.to_string()),
+            expanded(r#"{"db": {"$include": "db.json"}, "keep": {"$include": 1}}"#)
+        );
+
+        let error = expanded(r#"{"a": {"$include": "loop.json"}}"#).unwrap_err();
+        assert_eq!(
+            (
+                "/a/0",
+                &ExpandErrorKind::IncludeCycle("../loop.json".to_string())
+            ),
+            (error.path(), error.kind())
+        );
+        let error = expanded(r#"[{"$include": "sub/escape.json"}]"#).unwrap_err();
+        assert_eq!(
+            (
+                "/0",
+                &ExpandErrorKind::IncludeOutsideDir(format!("../../{secret}"))
+            ),
+            (error.path(), error.kind())
+        );
+        let error = expanded(r#"{"$include": "missing.json"}"#).unwrap_err();
+        assert_eq!("Could not read missing.json", error.to_string());
+
+        fs::remove_file(dir.join("..").join(&secret)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
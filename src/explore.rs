@@ -0,0 +1,248 @@
+use crate::{
+    parsing::{escape_key, Value},
+    serialize::{self, FormatConfig},
+};
+
+/// A value in the tree, in document order.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Node {
+    pointer: String,
+    depth: usize,
+    label: String,
+    summary: String,
+    parent: Option<usize>,
+    /// The index after this node's last descendant.
+    end: usize,
+    is_container: bool,
+}
+
+impl Node {
+    pub fn pointer(&self) -> &str {
+        &self.pointer
+    }
+
+    /// 0 for the whole document.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The member's key or the element's index, empty for the whole document.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Compact JSON for a scalar, or the size of a container, such as `{3}` or `[0]`.
+    pub fn summary(&self) -> &str {
+        &self.summary
+    }
+
+    pub fn is_container(&self) -> bool {
+        self.is_container
+    }
+}
+
+/// The state of a tree view of a document: which containers are expanded and which node is
+/// selected. Only the document as a whole starts expanded.
+pub struct Explorer {
+    nodes: Vec<Node>,
+    expanded: Vec<bool>,
+    /// Indices into `nodes` of the nodes whose ancestors are all expanded.
+    visible: Vec<usize>,
+    selected: usize,
+}
+
+impl Explorer {
+    pub fn new(value: &Value) -> Self {
+        let mut nodes = Vec::new();
+        add_nodes(value, String::new(), String::new(), None, 0, &mut nodes);
+        let mut expanded = vec![false; nodes.len()];
+        expanded[0] = true;
+        let mut explorer = Explorer {
+            nodes,
+            expanded,
+            visible: Vec::new(),
+            selected: 0,
+        };
+        explorer.update_visible();
+        explorer
+    }
+
+    /// The visible nodes, with whether each is expanded.
+    pub fn rows(&self) -> impl ExactSizeIterator<Item = (&Node, bool)> + '_ {
+        self.visible
+            .iter()
+            .map(|&i| (&self.nodes[i], self.expanded[i]))
+    }
+
+    /// The position of the selected node in [`Explorer::rows`].
+    pub fn selected_row(&self) -> usize {
+        self.visible
+            .iter()
+            .position(|&i| i == self.selected)
+            .expect("the selected node to be visible")
+    }
+
+    pub fn selected(&self) -> &Node {
+        &self.nodes[self.selected]
+    }
+
+    /// Moves the selection by `rows`, stopping at the first and last rows.
+    pub fn move_by(&mut self, rows: isize) {
+        let row = self.selected_row().saturating_add_signed(rows);
+        self.selected = self.visible[row.min(self.visible.len() - 1)];
+    }
+
+    /// Expands the selected container.
+    pub fn expand(&mut self) {
+        if self.nodes[self.selected].is_container {
+            self.expanded[self.selected] = true;
+            self.update_visible();
+        }
+    }
+
+    /// Collapses the selected container, or selects the parent if it is already collapsed
+    /// or not a container.
+    pub fn collapse(&mut self) {
+        if self.nodes[self.selected].is_container && self.expanded[self.selected] {
+            self.expanded[self.selected] = false;
+            self.update_visible();
+        } else if let Some(parent) = self.nodes[self.selected].parent {
+            self.selected = parent;
+        }
+    }
+
+    /// Selects the next node after the selected one, wrapping around, whose key or scalar
+    /// contains `text`, ignoring case, and expands the containers it is in. Returns whether
+    /// there was one.
+    pub fn search(&mut self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        let len = self.nodes.len();
+        let found = (1..=len).map(|n| (self.selected + n) % len).find(|&i| {
+            let node = &self.nodes[i];
+            node.label.to_lowercase().contains(&text)
+                || !node.is_container && node.summary.to_lowercase().contains(&text)
+        });
+        let Some(found) = found else {
+            return false;
+        };
+
+        let mut ancestor = self.nodes[found].parent;
+        while let Some(i) = ancestor {
+            self.expanded[i] = true;
+            ancestor = self.nodes[i].parent;
+        }
+        self.selected = found;
+        self.update_visible();
+        true
+    }
+
+    fn update_visible(&mut self) {
+        self.visible.clear();
+        let mut i = 0;
+        while i < self.nodes.len() {
+            self.visible.push(i);
+            i = match self.expanded[i] {
+                true => i + 1,
+                false => self.nodes[i].end,
+            };
+        }
+    }
+}
+
+fn add_nodes(
+    value: &Value,
+    pointer: String,
+    label: String,
+    parent: Option<usize>,
+    depth: usize,
+    nodes: &mut Vec<Node>,
+) {
+    let index = nodes.len();
+    let summary = match value {
+        Value::Array(elements) => format!("[{}]", elements.len()),
+        Value::Object(members) => format!("{{{}}}", members.len()),
+        scalar => serialize::to_string(scalar, &FormatConfig::compact()),
+    };
+    nodes.push(Node {
+        pointer: pointer.clone(),
+        depth,
+        label,
+        summary,
+        parent,
+        end: index + 1,
+        is_container: matches!(value, Value::Array(_) | Value::Object(_)),
+    });
+
+    match value {
+        Value::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                let pointer = format!("{pointer}/{i}");
+                add_nodes(
+                    element,
+                    pointer,
+                    i.to_string(),
+                    Some(index),
+                    depth + 1,
+                    nodes,
+                );
+            }
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                let pointer = format!("{pointer}/{}", escape_key(key));
+                add_nodes(
+                    member,
+                    pointer,
+                    key.to_string(),
+                    Some(index),
+                    depth + 1,
+                    nodes,
+                );
+            }
+        }
+        _ => {}
+    }
+    nodes[index].end = nodes.len();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn pointers(explorer: &Explorer) -> Vec<&str> {
+        explorer.rows().map(|(node, _)| node.pointer()).collect()
+    }
+
+    #[test]
+    fn expand_collapse_and_search() {
+        // Keys in sorted order, so that the rows are the same with either map.
+        let value =
+            Parser::parse(r#"{"user": {"name": "Ada", "tags": ["x", "Admin"]}, "v": 1}"#).unwrap();
+        let mut explorer = Explorer::new(&value);
+        assert_eq!(vec!["", "/user", "/v"], pointers(&explorer));
+
+        explorer.move_by(1);
+        explorer.expand();
+        assert_eq!(
+            vec!["", "/user", "/user/name", "/user/tags", "/v"],
+            pointers(&explorer)
+        );
+        explorer.move_by(10);
+        assert_eq!("/v", explorer.selected().pointer());
+        assert_eq!("1", explorer.selected().summary());
+
+        assert!(explorer.search("admin"));
+        assert_eq!("/user/tags/1", explorer.selected().pointer());
+        assert_eq!(5, explorer.selected_row());
+        assert!(explorer.search("ad"));
+        assert_eq!("/user/name", explorer.selected().pointer());
+        assert!(!explorer.search("missing"));
+
+        explorer.collapse();
+        assert_eq!("/user", explorer.selected().pointer());
+        explorer.collapse();
+        assert_eq!(vec!["", "/user", "/v"], pointers(&explorer));
+        assert_eq!("{2}", explorer.selected().summary());
+    }
+}
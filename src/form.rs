@@ -0,0 +1,221 @@
+use std::fmt::Write;
+
+use crate::{map::Map, parsing::Value};
+
+impl Value {
+    /// Writes an object as an `application/x-www-form-urlencoded` query string, or `None` for
+    /// any other value. Nested values use brackets: `{"a": {"b": [1, 2]}}` is written as
+    /// `a[b][]=1&a[b][]=2`, with an index instead of `[]` for containers in arrays. A `null`
+    /// is written as a bare key, and empty arrays and objects are left out, as a query string
+    /// cannot express them.
+    pub fn to_query_string(&self) -> Option<String> {
+        let Value::Object(members) = self else {
+            return None;
+        };
+        let mut query = String::new();
+        for (key, member) in members {
+            write_pair(&mut query, &encode(key), member);
+        }
+        Some(query)
+    }
+
+    /// Reads a query string, with or without its leading `?`, the way
+    /// [`Value::to_query_string`] writes one. The query string carries no types, so values
+    /// are strings, apart from bare keys which are `null`. A key given more than once, such
+    /// as `tag=a&tag=b`, collects its values into an array.
+    pub fn from_query_string(query: &str) -> Value {
+        let mut root = Value::Object(Map::new());
+        for pair in query.strip_prefix('?').unwrap_or(query).split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = match pair.split_once('=') {
+                Some((key, value)) => (key, Value::String(decode(value))),
+                None => (pair, Value::Null),
+            };
+            let (name, segments) = split_key(key);
+            insert(&mut root, &decode(name), &segments, value);
+        }
+        root
+    }
+}
+
+fn write_pair(query: &mut String, key: &str, value: &Value) {
+    let mut scalar = |text: Option<&str>| {
+        if !query.is_empty() {
+            query.push('&');
+        }
+        query.push_str(key);
+        if let Some(text) = text {
+            query.push('=');
+            query.push_str(&encode(text));
+        }
+    };
+    match value {
+        Value::Null => scalar(None),
+        Value::String(s) => scalar(Some(s)),
+        Value::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                let key = match element {
+                    Value::Array(_) | Value::Object(_) => format!("{key}[{i}]"),
+                    _ => format!("{key}[]"),
+                };
+                write_pair(query, &key, element);
+            }
+        }
+        Value::Object(members) => {
+            for (name, member) in members {
+                write_pair(query, &format!("{key}[{}]", encode(name)), member);
+            }
+        }
+        scalar_value => scalar(Some(&scalar_value.to_string())),
+    }
+}
+
+/// Splits `a[b][]` into `a` and the segments `b` and ``. A key whose brackets do not pair
+/// up is all name.
+fn split_key(key: &str) -> (&str, Vec<&str>) {
+    let Some(open) = key.find('[').filter(|&open| open > 0) else {
+        return (key, Vec::new());
+    };
+    let mut segments = Vec::new();
+    let mut rest = &key[open..];
+    while let Some(inner) = rest.strip_prefix('[') {
+        let Some(close) = inner.find(']') else {
+            return (key, Vec::new());
+        };
+        segments.push(&inner[..close]);
+        rest = &inner[close + 1..];
+    }
+    if !rest.is_empty() {
+        return (key, Vec::new());
+    }
+    (&key[..open], segments)
+}
+
+/// Puts `value` at `name` and then `segments` in `container`, making arrays for `[]` and
+/// indices and objects for other segments.
+fn insert(container: &mut Value, name: &str, segments: &[&str], value: Value) {
+    let (slot, is_new) = match container {
+        Value::Array(elements) => match name.parse::<usize>() {
+            Ok(i) if i < elements.len() => (&mut elements[i], false),
+            // Indices past the end append, so `a[99999]` cannot allocate a huge array.
+            _ => {
+                elements.push(Value::Null);
+                (elements.last_mut().unwrap(), true)
+            }
+        },
+        Value::Object(members) => {
+            let is_new = members.get(name).is_none();
+            if is_new {
+                members.insert(name, Value::Null);
+            }
+            (members.get_mut(name).unwrap(), is_new)
+        }
+        _ => unreachable!("only arrays and objects are inserted into"),
+    };
+
+    let Some((next, rest)) = segments.split_first() else {
+        match slot {
+            _ if is_new => *slot = value,
+            Value::Array(elements) => elements.push(value),
+            _ => *slot = Value::Array(vec![std::mem::take(slot), value]),
+        }
+        return;
+    };
+    let wants_array = next.is_empty() || next.parse::<usize>().is_ok();
+    match slot {
+        Value::Array(_) if wants_array => {}
+        Value::Object(_) if !wants_array => {}
+        _ if wants_array => *slot = Value::Array(Vec::new()),
+        _ => *slot = Value::Object(Map::new()),
+    }
+    insert(slot, &decode(next), rest, value);
+}
+
+fn encode(s: &str) -> String {
+    let mut encoded = String::with_capacity(s.len());
+    for b in s.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(b as char)
+            }
+            b' ' => encoded.push('+'),
+            b => write!(encoded, "%{b:02X}").unwrap(),
+        }
+    }
+    encoded
+}
+
+/// Decodes `+` and `%` escapes. An escape that is not two hex digits is kept as it is.
+fn decode(s: &str) -> String {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut rest = s.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        let hex = tail
+            .get(..2)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (b, hex) {
+            (b'+', _) => bytes.push(b' '),
+            (b'%', Some(byte)) => {
+                bytes.push(byte);
+                rest = &tail[2..];
+                continue;
+            }
+            (b, _) => bytes.push(b),
+        }
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    /// The parameters of `query`, sorted since their order follows the map's.
+    fn sorted_parameters(query: &str) -> Vec<&str> {
+        let mut parameters = query.split('&').collect::<Vec<&str>>();
+        parameters.sort();
+        parameters
+    }
+
+    #[test]
+    fn write_query_strings() {
+        let value = Parser::parse(
+            r#"{"q": "a b&c", "page": 2, "on": true, "none": null, "tags": ["x", "y"],
+                "filter": {"from": "2024-01-01", "ids": [[1], {"k/": "é"}]}, "empty": []}"#,
+        )
+        .unwrap();
+        let query = value.to_query_string().unwrap();
+        assert_eq!(
+            sorted_parameters(
+                "q=a+b%26c&page=2&on=true&none&tags[]=x&tags[]=y&filter[from]=2024-01-01\
+                 &filter[ids][0][]=1&filter[ids][1][k%2F]=%C3%A9"
+            ),
+            sorted_parameters(&query)
+        );
+        assert_eq!(None, Value::Array(Vec::new()).to_query_string());
+    }
+
+    #[test]
+    fn read_query_strings() {
+        let value = Value::from_query_string(
+            "?a=1&b[]=x&b[]=y%20z&c[d][e]=%C3%A9&c[d][f]&tag=1&tag=2&tag=3&odd[=1&i[5][k]=v&&",
+        );
+        assert_eq!(
+            Parser::parse(
+                r#"{"a": "1", "b": ["x", "y z"], "c": {"d": {"e": "é", "f": null}},
+                    "tag": ["1", "2", "3"], "odd[": "1", "i": [{"k": "v"}]}"#
+            )
+            .unwrap(),
+            value
+        );
+        assert_eq!(
+            Parser::parse(r#"{"%zz": "100%"}"#).unwrap(),
+            Value::from_query_string("%zz=100%")
+        );
+    }
+}
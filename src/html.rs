@@ -0,0 +1,134 @@
+use std::fmt::Write;
+
+use crate::{parsing::Value, serialize};
+
+const STYLE: &str = "body { font-family: monospace; }
+details > div, details > .end { margin-left: 1.5em; }
+summary { cursor: pointer; }
+.key { color: #881391; }
+.string { color: #c41a16; }
+.number { color: #1c00cf; }
+.bool, .null { color: #0d22aa; font-weight: bold; }
+.count { color: #808080; }";
+
+const OPEN_DEPTH: usize = 2;
+
+pub fn render_html(value: &Value) -> String {
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<style>\n");
+    out.push_str(STYLE);
+    out.push_str("\n</style>\n</head>\n<body>\n");
+    write_node(&mut out, None, value, 0);
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+fn write_node(out: &mut String, key: Option<&str>, value: &Value, depth: usize) {
+    let (open, close, len) = match value {
+        Value::Array(elements) if !elements.is_empty() => ('[', ']', elements.len()),
+        Value::Object(members) if !members.is_empty() => ('{', '}', members.len()),
+        _ => {
+            out.push_str("<div>");
+            write_key(out, key);
+            write_scalar(out, value);
+            out.push_str("</div>\n");
+            return;
+        }
+    };
+
+    out.push_str(if depth < OPEN_DEPTH {
+        "<details open>"
+    } else {
+        "<details>"
+    });
+    out.push_str("<summary>");
+    write_key(out, key);
+    let noun = if len == 1 { "item" } else { "items" };
+    writeln!(
+        out,
+        "{open} <span class=\"count\">{len} {noun}</span></summary>"
+    )
+    .unwrap();
+
+    match value {
+        Value::Array(elements) => {
+            for element in elements {
+                write_node(out, None, element, depth + 1);
+            }
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                write_node(out, Some(key), member, depth + 1);
+            }
+        }
+        _ => unreachable!(),
+    }
+    writeln!(out, "<span class=\"end\">{close}</span></details>").unwrap();
+}
+
+fn write_key(out: &mut String, key: Option<&str>) {
+    if let Some(key) = key {
+        let mut quoted = String::new();
+        serialize::write_string(&mut quoted, key);
+        out.push_str("<span class=\"key\">");
+        write_escaped(out, &quoted);
+        out.push_str("</span>: ");
+    }
+}
+
+fn write_scalar(out: &mut String, value: &Value) {
+    let class = match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        #[cfg(feature = "bytes")]
+        Value::Bytes(_) => "string",
+        Value::Array(_) | Value::Object(_) => "empty",
+    };
+    write!(out, "<span class=\"{class}\">").unwrap();
+    write_escaped(out, &value.to_string());
+    out.push_str("</span>");
+}
+
+fn write_escaped(out: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn render_nested_document() {
+        let value = Parser::parse(r#"{"a": [1, "<b>"], "c": {"d": {"e": null}}}"#).unwrap();
+        let html = render_html(&value);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(4, html.matches("<details").count());
+        assert_eq!(3, html.matches("<details open>").count());
+        assert!(html.contains("<span class=\"string\">&quot;&lt;b&gt;&quot;</span>"));
+        assert!(html.contains(
+            "<span class=\"key\">&quot;a&quot;</span>: [ <span class=\"count\">2 items</span>"
+        ));
+        assert!(html.contains("<span class=\"number\">1</span>"));
+        assert!(html.contains("<span class=\"null\">null</span>"));
+    }
+
+    #[test]
+    fn render_scalar_document() {
+        let html = render_html(&Value::Bool(true));
+        assert!(html.contains("<div><span class=\"bool\">true</span></div>"));
+        assert!(!html.contains("<details"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::{collections::HashMap, ops::Range};
+
+use crate::{
+    errors::Error,
+    parsing::ParseOptions,
+    pointer::{parse_index, parse_pointer},
+    stream::{Event, StreamParser},
+};
+
+enum Children {
+    None,
+    Object(HashMap<String, usize>),
+    Array(Vec<usize>),
+}
+
+struct Node {
+    range: Range<usize>,
+    lines: Range<(usize, usize)>,
+    children: Children,
+}
+
+/// The byte range and source position of every value in a document, found in one scan
+/// without building any values, so that a tool can seek straight to one record of a huge
+/// file or report where a value it rejected is.
+pub struct DocumentIndex {
+    nodes: Vec<Node>,
+}
+
+impl DocumentIndex {
+    pub fn new(json: &str) -> Result<DocumentIndex, Error> {
+        DocumentIndex::with_options(json, ParseOptions::default())
+    }
+
+    /// Indexes a document that may use the extensions to JSON that `options` allow, such as
+    /// one [`Parser::parse_with_options`](crate::parsing::Parser::parse_with_options) read.
+    pub fn with_options(json: &str, options: ParseOptions) -> Result<DocumentIndex, Error> {
+        let mut nodes = Vec::<Node>::new();
+        let mut open = Vec::<usize>::new();
+        let mut key = String::new();
+
+        for event in StreamParser::with_options(json, options) {
+            let (event, span) = event?;
+            let children = match event {
+                Event::Key(k) => {
+                    key = k;
+                    continue;
+                }
+                Event::EndObject | Event::EndArray => {
+                    let node = open.pop().expect("containers to be balanced");
+                    nodes[node].range.end = span.end;
+                    nodes[node].lines.end = (span.line, span.col + 1);
+                    continue;
+                }
+                Event::StartObject => Children::Object(HashMap::new()),
+                Event::StartArray => Children::Array(Vec::<usize>::new()),
+                _ => Children::None,
+            };
+
+            let index = nodes.len();
+            if let Some(&parent) = open.last() {
+                match &mut nodes[parent].children {
+                    Children::Object(members) => {
+                        members.insert(std::mem::take(&mut key), index);
+                    }
+                    Children::Array(elements) => elements.push(index),
+                    Children::None => unreachable!("only containers are open"),
+                }
+            }
+            if !matches!(children, Children::None) {
+                open.push(index);
+            }
+            let end_col = span.col + json[span.start..span.end].chars().count();
+            nodes.push(Node {
+                range: span.start..span.end,
+                lines: (span.line, span.col)..(span.line, end_col),
+                children,
+            });
+        }
+        Ok(DocumentIndex { nodes })
+    }
+
+    /// The byte range of the value that a JSON pointer such as `/a/b/3` refers to.
+    pub fn offset_of_pointer(&self, pointer: &str) -> Option<Range<usize>> {
+        Some(self.node(pointer)?.range.clone())
+    }
+
+    /// The line and column of the first character of the value at `pointer`, and of the
+    /// position just after its last, both counted from 1.
+    pub fn lines_of(&self, pointer: &str) -> Option<Range<(usize, usize)>> {
+        Some(self.node(pointer)?.lines.clone())
+    }
+
+    fn node(&self, pointer: &str) -> Option<&Node> {
+        let mut node = self.nodes.first()?;
+        for token in parse_pointer(pointer)? {
+            let index = match &node.children {
+                Children::Object(members) => *members.get(&token)?,
+                Children::Array(elements) => *elements.get(parse_index(&token)?)?,
+                Children::None => return None,
+            };
+            node = &self.nodes[index];
+        }
+        Some(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn find_value_ranges() {
+        let json =
+            r#" {"a": {"b": [0, 1, "two", {"x": null}]}, "é": true, "a/b": [], "d": 1, "d": 2} "#;
+        let index = DocumentIndex::new(json).unwrap();
+        let text = |pointer: &str| index.offset_of_pointer(pointer).map(|range| &json[range]);
+
+        assert_eq!(Some(json.trim()), text(""));
+        assert_eq!(Some(r#"[0, 1, "two", {"x": null}]"#), text("/a/b"));
+        assert_eq!(Some(r#""two""#), text("/a/b/2"));
+        assert_eq!(Some("null"), text("/a/b/3/x"));
+        assert_eq!(Some("true"), text("/é"));
+        assert_eq!(Some("[]"), text("/a~1b"));
+        assert_eq!(Some("2"), text("/d"));
+        assert_eq!(None, text("/a/b/4"));
+        assert_eq!(None, text("/a/b/01"));
+        assert_eq!(None, text("/a/b/0/x"));
+        assert_eq!(None, text("a"));
+    }
+
+    #[test]
+    fn find_value_positions() {
+        let json = "{\n  \"é\": \"ü\",\n  \"b\": [\n    1, {}\n  ]\n}";
+        let index = DocumentIndex::new(json).unwrap();
+
+        assert_eq!(Some((1, 1)..(6, 2)), index.lines_of(""));
+        assert_eq!(Some((2, 8)..(2, 11)), index.lines_of("/é"));
+        assert_eq!(Some((3, 8)..(5, 4)), index.lines_of("/b"));
+        assert_eq!(Some((4, 5)..(4, 6)), index.lines_of("/b/0"));
+        assert_eq!(Some((4, 8)..(4, 10)), index.lines_of("/b/1"));
+        assert_eq!(None, index.lines_of("/c"));
+    }
+
+    #[test]
+    fn find_positions_in_lenient_document() {
+        let json = "{a: 1,\n 'b': ['x', 2,],}";
+        let options = ParseOptions {
+            allow_trailing_commas: true,
+            allow_single_quotes: true,
+            allow_unquoted_keys: true,
+            ..ParseOptions::default()
+        };
+        let index = DocumentIndex::with_options(json, options).unwrap();
+
+        assert_eq!(Some((1, 5)..(1, 6)), index.lines_of("/a"));
+        assert_eq!(Some((2, 7)..(2, 16)), index.lines_of("/b"));
+        assert_eq!(Some((2, 13)..(2, 14)), index.lines_of("/b/1"));
+        assert!(DocumentIndex::new(json).is_err());
+    }
+
+    #[test]
+    fn fail_on_invalid_document() {
+        assert_eq!(
+            Some(ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            DocumentIndex::new("[1 2]").err().map(|e| e.code())
+        );
+        assert_eq!(
+            Some(ErrorCode::EndOfFileExpected),
+            DocumentIndex::new("{} {}").err().map(|e| e.code())
+        );
+    }
+}
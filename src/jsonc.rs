@@ -0,0 +1,329 @@
+use std::{collections::HashMap, iter::Peekable, ops::Range, vec};
+
+use crate::{
+    errors::Error,
+    parsing::escape_key,
+    stream::{Event, StreamParser},
+};
+
+#[derive(Default)]
+struct Attached {
+    leading: Vec<String>,
+    trailing: Option<String>,
+}
+
+/// Removes the comments and trailing commas from a JSONC document, leaving every other byte
+/// as it was.
+pub fn strip_jsonc(jsonc: &str) -> String {
+    let mut json = String::with_capacity(jsonc.len());
+    let mut start = 0;
+    for range in removed_ranges(jsonc) {
+        json.push_str(&jsonc[start..range.start]);
+        start = range.end;
+    }
+    json.push_str(&jsonc[start..]);
+    json
+}
+
+/// Like [`strip_jsonc`], but replaces what is removed with spaces, keeping line breaks, so
+/// that byte offsets and line numbers in the result are those of the original.
+pub fn blank_jsonc(jsonc: &str) -> String {
+    blank(jsonc, &removed_ranges(jsonc))
+}
+
+/// The `//` and `/* */` comments of a JSONC document, attached to the values they document.
+/// A comment belongs to the value after it, or, if it starts on the line where a value ends,
+/// to that value as its trailing comment. For object members the value is the whole member,
+/// so a comment above a key is found at the pointer of that key. Comments before a closing
+/// bracket that do not trail a value are not attached to anything.
+pub struct Comments {
+    nodes: HashMap<String, Attached>,
+}
+
+impl Comments {
+    pub fn new(jsonc: &str) -> Result<Comments, Error> {
+        let json = blank_jsonc(jsonc);
+        let mut attacher = Attacher {
+            jsonc,
+            comments: scan(jsonc).comments.into_iter().peekable(),
+            nodes: HashMap::new(),
+            last: None,
+        };
+
+        // The pointer of the value being read, and the length of the array each open
+        // container is, or `None` for objects.
+        let mut path = Vec::<String>::new();
+        let mut open = Vec::<Option<usize>>::new();
+        for event in StreamParser::new(&json) {
+            let (event, span) = event?;
+            if let Event::Key(key) = &event {
+                path.push(escape_key(key));
+                attacher.attach(span.start, Some(&pointer(&path)));
+                continue;
+            }
+            if matches!(event, Event::EndObject | Event::EndArray) {
+                attacher.attach(span.start, None);
+                open.pop();
+            } else {
+                if let Some(Some(len)) = open.last_mut() {
+                    path.push(len.to_string());
+                    *len += 1;
+                }
+                attacher.attach(span.start, Some(&pointer(&path)));
+                match event {
+                    Event::StartObject => open.push(None),
+                    Event::StartArray => open.push(Some(0)),
+                    _ => {}
+                }
+            }
+
+            if !matches!(event, Event::StartObject | Event::StartArray) {
+                attacher.last = Some((pointer(&path), span.end));
+                if !open.is_empty() {
+                    path.pop();
+                }
+            }
+        }
+        attacher.attach(json.len(), None);
+
+        Ok(Comments {
+            nodes: attacher.nodes,
+        })
+    }
+
+    /// The comments on the lines before the value at `pointer`, in order, including their
+    /// `//` or `/* */` markers.
+    pub fn leading_comments(&self, pointer: &str) -> &[String] {
+        self.nodes
+            .get(pointer)
+            .map_or(&[], |node| node.leading.as_slice())
+    }
+
+    /// The comment after the value at `pointer` on the line where it ends.
+    pub fn trailing_comment(&self, pointer: &str) -> Option<&str> {
+        self.nodes.get(pointer)?.trailing.as_deref()
+    }
+}
+
+struct Attacher<'a> {
+    jsonc: &'a str,
+    comments: Peekable<vec::IntoIter<Range<usize>>>,
+    nodes: HashMap<String, Attached>,
+    /// The pointer and end of the last value read, until another value starts.
+    last: Option<(String, usize)>,
+}
+
+impl Attacher<'_> {
+    /// Attaches the comments before `offset`, where the value at `pointer` starts if any.
+    fn attach(&mut self, offset: usize, pointer: Option<&str>) {
+        while let Some(range) = self.comments.next_if(|range| range.start < offset) {
+            let text = self.jsonc[range.clone()].to_string();
+            if let Some((last, end)) = &self.last {
+                let trailing = &mut self.nodes.entry(last.clone()).or_default().trailing;
+                if trailing.is_none() && !self.jsonc[*end..range.start].contains(['\n', '\r']) {
+                    *trailing = Some(text);
+                    continue;
+                }
+            }
+            if let Some(pointer) = pointer {
+                let node = self.nodes.entry(pointer.to_string()).or_default();
+                node.leading.push(text);
+            }
+        }
+        if pointer.is_some() {
+            self.last = None;
+        }
+    }
+}
+
+fn pointer(path: &[String]) -> String {
+    path.iter().map(|token| format!("/{token}")).collect()
+}
+
+struct Scan {
+    comments: Vec<Range<usize>>,
+    trailing_commas: Vec<usize>,
+}
+
+/// Finds the comments and the commas before a closing bracket, outside strings. A `//`
+/// comment ends before the line break and an unterminated `/*` comment at the end of the
+/// input.
+fn scan(jsonc: &str) -> Scan {
+    let bytes = jsonc.as_bytes();
+    let mut ranges = Vec::<Range<usize>>::new();
+    let mut trailing_commas = Vec::<usize>::new();
+    let mut comma = None;
+    // Whether the last token ended a value, so that a comma after it separates values.
+    let mut after_value = false;
+    let mut i = 0;
+    while i < bytes.len() {
+        let start = bytes[i];
+        match &bytes[i..] {
+            [b' ' | b'\t' | b'\n' | b'\r', ..] => {
+                i += 1;
+                continue;
+            }
+            [b',', ..] => {
+                comma = after_value.then_some(i);
+                after_value = false;
+                i += 1;
+                continue;
+            }
+            [b']' | b'}', ..] => {
+                trailing_commas.extend(comma);
+                i += 1;
+            }
+            [b'"', ..] => {
+                i += 1;
+                while let Some(&b) = bytes.get(i) {
+                    i += if b == b'\\' { 2 } else { 1 };
+                    if b == b'"' {
+                        break;
+                    }
+                }
+            }
+            [b'/', b'/', rest @ ..] => {
+                let len = rest.iter().position(|b| matches!(b, b'\n' | b'\r'));
+                let end = len.map_or(bytes.len(), |len| i + 2 + len);
+                ranges.push(i..end);
+                i = end;
+                continue;
+            }
+            [b'/', b'*', rest @ ..] => {
+                let len = rest.windows(2).position(|w| w == b"*/");
+                let end = len.map_or(bytes.len(), |len| i + 2 + len + 2);
+                ranges.push(i..end);
+                i = end;
+                continue;
+            }
+            _ => i += 1,
+        }
+        comma = None;
+        after_value = !matches!(start, b'[' | b'{' | b':');
+    }
+    Scan {
+        comments: ranges,
+        trailing_commas,
+    }
+}
+
+fn removed_ranges(jsonc: &str) -> Vec<Range<usize>> {
+    let Scan {
+        mut comments,
+        trailing_commas,
+    } = scan(jsonc);
+    comments.extend(trailing_commas.into_iter().map(|i| i..i + 1));
+    comments.sort_by_key(|range| range.start);
+    comments
+}
+
+/// Replaces every byte in `ranges` with a space, except line breaks, so that byte offsets and
+/// line numbers stay the same.
+fn blank(jsonc: &str, ranges: &[Range<usize>]) -> String {
+    let mut bytes = jsonc.as_bytes().to_vec();
+    for range in ranges {
+        for b in &mut bytes[range.clone()] {
+            if !matches!(b, b'\n' | b'\r') {
+                *b = b' ';
+            }
+        }
+    }
+    String::from_utf8(bytes).expect("comments to start and end on character boundaries")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::ErrorCode, parsing::Parser};
+
+    #[test]
+    fn find_comments_outside_strings() {
+        let jsonc = "[\"// no\", \"\\\"/*\" /* a\n b */, 1 // c\r\n] /* d";
+        let comments = scan(jsonc)
+            .comments
+            .into_iter()
+            .map(|range| &jsonc[range])
+            .collect::<Vec<_>>();
+        assert_eq!(vec!["/* a\n b */", "// c", "/* d"], comments);
+    }
+
+    #[test]
+    fn strip_comments_and_trailing_commas() {
+        let jsonc = "{\n  \"a\": [1, 2, /* two */], // é\r\n  \"b,]\": {\"c\": \"//\",\n  },\n}\n";
+        assert_eq!(
+            "{\n  \"a\": [1, 2 ], \r\n  \"b,]\": {\"c\": \"//\"\n  }\n}\n",
+            strip_jsonc(jsonc)
+        );
+
+        let blanked = blank_jsonc(jsonc);
+        assert_eq!(jsonc.len(), blanked.len());
+        assert_eq!(jsonc.lines().count(), blanked.lines().count());
+        for (original, blanked) in jsonc.bytes().zip(blanked.bytes()) {
+            assert!(original == blanked || blanked == b' ');
+        }
+        assert_eq!(Parser::parse(&strip_jsonc(jsonc)), Parser::parse(&blanked));
+        assert_eq!(
+            Ok(r#"{"a":[1,2],"b,]":{"c":"//"}}"#.to_string()),
+            Parser::parse(&blanked).map(|value| value.to_string())
+        );
+
+        for json in [
+            r#"{"a": [1, {"b": null}], "c": "/*,]"}"#,
+            "[]",
+            "\"x\"",
+            "[,]",
+            "[1,,]",
+        ] {
+            assert_eq!(json, strip_jsonc(json));
+            assert_eq!(json, blank_jsonc(json));
+        }
+        // Full of URLs in strings.
+        let twitter = std::fs::read_to_string("tests/twitter.json").unwrap();
+        assert!(strip_jsonc(&twitter) == twitter);
+    }
+
+    #[test]
+    fn attach_comments_to_values() {
+        let jsonc = r#"// The whole config
+{
+    // Where to listen.
+    /* Defaults to 80. */
+    "port": 80, // Overridden in tests.
+    "hosts": [
+        "a", /* first */ /* second */
+        // Backup
+        "b/c",
+        // Dangling
+    ], "x/y": {"z": /* inline */ null} // last
+}"#;
+        let comments = Comments::new(jsonc).unwrap();
+
+        assert_eq!(["// The whole config"], comments.leading_comments(""));
+        assert_eq!(
+            ["// Where to listen.", "/* Defaults to 80. */"],
+            comments.leading_comments("/port")
+        );
+        assert_eq!(
+            Some("// Overridden in tests."),
+            comments.trailing_comment("/port")
+        );
+        assert_eq!(Some("/* first */"), comments.trailing_comment("/hosts/0"));
+        assert_eq!(
+            ["/* second */", "// Backup"],
+            comments.leading_comments("/hosts/1")
+        );
+        assert_eq!(None, comments.trailing_comment("/hosts/1"));
+        assert_eq!(["/* inline */"], comments.leading_comments("/x~1y/z"));
+        assert_eq!(Some("// last"), comments.trailing_comment("/x~1y"));
+        assert!(comments.leading_comments("/hosts").is_empty());
+        assert!(comments.leading_comments("/missing").is_empty());
+    }
+
+    #[test]
+    fn fail_on_invalid_document() {
+        assert_eq!(
+            Some(ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            Comments::new("[1 /* , */ 2]").err().map(|e| e.code())
+        );
+    }
+}
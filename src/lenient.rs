@@ -0,0 +1,121 @@
+use std::borrow::Cow;
+
+use crate::parsing::{widen, Value};
+
+impl Value {
+    /// The value as a number, also reading strings such as `"42"` or `" 1e3 "` and treating
+    /// `true` and `false` as 1 and 0. Strings that are not finite numbers give `None`.
+    pub fn as_f64_lenient(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(widen(*n)),
+            Value::Bool(b) => Some(f64::from(u8::from(*b))),
+            Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+            _ => None,
+        }
+    }
+
+    /// Like [`Value::as_f64_lenient`], but only for whole numbers that fit in an `i64`.
+    /// Strings of digits are read exactly, even beyond the integers an `f64` can hold.
+    pub fn as_i64_lenient(&self) -> Option<i64> {
+        if let Value::String(s) = self {
+            if let Ok(n) = s.trim().parse::<i64>() {
+                return Some(n);
+            }
+        }
+        let n = self.as_f64_lenient()?;
+        let in_range = (i64::MIN as f64..i64::MAX as f64).contains(&n);
+        (n.fract() == 0.0 && in_range).then_some(n as i64)
+    }
+
+    /// The value as a bool, also reading the numbers 1 and 0 and the strings `"true"`,
+    /// `"false"`, `"yes"`, `"no"`, `"1"`, and `"0"`, ignoring case and surrounding spaces.
+    pub fn as_bool_lenient(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::Number(n) if *n == 1.0 => Some(true),
+            Value::Number(n) if *n == 0.0 => Some(false),
+            Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "yes" | "1" => Some(true),
+                "false" | "no" | "0" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The value as a string, writing numbers and bools as they appear in JSON.
+    pub fn as_str_lenient(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::String(s) => Some(Cow::Borrowed(s)),
+            Value::Number(_) | Value::Bool(_) => Some(Cow::Owned(self.to_string())),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn coerce_sloppy_values() {
+        let value = Parser::parse(
+            r#"[42, " 4.5 ", "1e3", "NaN", "x", true, null, "9007199254740993", 2.5, "YES", 0]"#,
+        )
+        .unwrap();
+        let Value::Array(values) = value else {
+            panic!("expected an array")
+        };
+
+        let numbers = values.iter().map(Value::as_f64_lenient).collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                Some(42.0),
+                Some(4.5),
+                Some(1000.0),
+                None,
+                None,
+                Some(1.0),
+                None,
+                Some(9007199254740992.0),
+                Some(2.5),
+                None,
+                Some(0.0)
+            ],
+            numbers
+        );
+
+        assert_eq!(Some(42), values[0].as_i64_lenient());
+        assert_eq!(Some(1000), values[2].as_i64_lenient());
+        assert_eq!(Some(9007199254740993), values[7].as_i64_lenient());
+        assert_eq!(None, values[8].as_i64_lenient());
+        assert_eq!(None, Value::Number(1e20).as_i64_lenient());
+
+        let bools = values
+            .iter()
+            .map(Value::as_bool_lenient)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            vec![
+                None,
+                None,
+                None,
+                None,
+                None,
+                Some(true),
+                None,
+                None,
+                None,
+                Some(true),
+                Some(false)
+            ],
+            bools
+        );
+
+        assert_eq!(Some("42".into()), values[0].as_str_lenient());
+        assert_eq!(Some(" 4.5 ".into()), values[1].as_str_lenient());
+        assert_eq!(Some("true".into()), values[5].as_str_lenient());
+        assert_eq!(None, values[6].as_str_lenient());
+    }
+}
@@ -1,124 +1,145 @@
-use std::{cmp::min, collections::VecDeque, iter::Peekable, str::Chars};
+use std::{borrow::Cow, cmp::min, iter::Peekable, str::CharIndices, sync::OnceLock};
 
 use crate::errors::{Error, ErrorCode};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
-    NewLine,
-    Whitespace(usize),
     Null,
-    Bool(String),
-    String(String),
-    Number(String),
+    Bool(bool),
+    /// The string as written, quotes and escapes included. The empty string is shared rather
+    /// than copied.
+    String(Cow<'static, str>),
+    /// The number as written. Integers of at most three digits are shared rather than copied.
+    Number(Cow<'static, str>),
     Punctuation(char),
+    /// A bare word such as `foo`, read only by [`Reader::with_identifiers`].
+    Identifier(String),
 }
 
-impl Token {
-    pub fn is_whitespace(&self) -> bool {
-        match self {
-            Self::NewLine => true,
-            Self::Whitespace(_) => true,
-            _ => false,
-        }
-    }
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
 
-    pub fn len(&self) -> usize {
-        match self {
-            Self::NewLine => 1,
-            Self::Whitespace(spaces) => *spaces,
-            Self::Null => 4,
-            Self::Bool(b) => b.len(),
-            Self::String(s) => s.len(),
-            Self::Number(s) => s.len(),
-            Self::Punctuation(_) => 1,
+impl Span {
+    pub fn new(start: usize, end: usize, line: usize, col: usize) -> Self {
+        Span {
+            start,
+            end,
+            line,
+            col,
         }
     }
+}
 
-    pub fn try_from_json(possible_json: &str) -> Result<Vec<Token>, Vec<Error>> {
-        let token_strings = tokenize_into_strings(&possible_json);
+pub struct Reader<'a> {
+    json: &'a str,
+    chars: Peekable<CharIndices<'a>>,
+    buffer: Vec<(Span, Result<Token, Error>)>,
+    /// Byte offset of `json` in the whole input, when resuming part way through it.
+    offset: usize,
+    line: usize,
+    col: usize,
+    msg_span: Span,
+    single_quotes: bool,
+    identifiers: bool,
+    max_string_len: Option<usize>,
+    max_number_len: Option<usize>,
+}
 
-        let mut tokens = Vec::<Token>::new();
-        let mut errors = Vec::<Error>::new();
-        let mut line_number = 1usize;
-        let mut col_number = 1usize;
-        for token in token_strings {
-            if token == "\n" {
-                line_number += 1;
-                col_number = 1;
-            }
-            match Token::try_from_token(&token) {
-                Some(t) => tokens.push(t),
-                None => errors.push(Error::new(
-                    ErrorCode::ExpectedToken,
-                    line_number,
-                    col_number,
-                )),
-            }
-            col_number += token.len();
-        }
+#[derive(Clone, Copy)]
+enum Kind {
+    String,
+    Number,
+    Literal,
+}
 
-        if !errors.is_empty() {
-            Err(errors)
-        } else {
-            Ok(tokens)
-        }
+#[derive(PartialEq, Clone, Copy)]
+enum State {
+    Outside,
+    InString,
+    Escaped,
+    Closed,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(possible_json: &'a str) -> Reader<'a> {
+        Reader::starting_at(possible_json, Span::new(0, 0, 1, 1))
     }
 
-    fn try_from_token(token: &str) -> Option<Token> {
-        assert!(!token.is_empty());
+    /// Continues reading `possible_json` from a `position` returned by [`Reader::position`],
+    /// without reading anything before it. Returns `None` if the position is not in the input.
+    pub fn resume(possible_json: &'a str, position: Span) -> Option<Reader<'a>> {
+        possible_json
+            .is_char_boundary(position.end)
+            .then(|| Reader::starting_at(possible_json, position))
+    }
 
-        let c = token.chars().next().unwrap();
-        if token.len() == 1 && Token::is_punctuation(&c) {
-            return Some(Token::Punctuation(c));
+    fn starting_at(possible_json: &'a str, position: Span) -> Reader<'a> {
+        let json = &possible_json[position.end..];
+        Reader {
+            json,
+            chars: json.char_indices().peekable(),
+            buffer: Vec::<(Span, Result<Token, Error>)>::new(),
+            offset: position.end,
+            line: position.line,
+            col: position.col,
+            msg_span: position,
+            single_quotes: false,
+            identifiers: false,
+            max_string_len: None,
+            max_number_len: None,
         }
+    }
 
-        match (c, token) {
-            (' ', _) => Some(Token::Whitespace(token.len())),
-            ('\n', _) => Some(Token::NewLine),
-            ('n', "null") => Some(Token::Null),
-            ('f', "false") => Some(Token::Bool("false".to_string())),
-            ('t', "true") => Some(Token::Bool("true".to_string())),
-            ('"', _) => Some(Token::String(token.to_string())),
-            ('-', _) => Some(Token::Number(token.to_string())),
-            ('0'..='9', _) => Some(Token::Number(token.to_string())),
-            _ => None,
-        }
+    /// Whether to also read strings in single quotes, as JSON5 does. Their tokens keep the
+    /// single quotes.
+    pub fn with_single_quotes(mut self, allow: bool) -> Self {
+        self.single_quotes = allow;
+        self
     }
 
-    fn is_punctuation(c: &char) -> bool {
-        const PUNCTUATIONS: &'static [char] = &[',', ':', '{', '}', '[', ']'];
-        PUNCTUATIONS.contains(&c)
+    /// Whether to read ECMAScript identifiers such as `foo` or `$id` as
+    /// [`Token::Identifier`] rather than as an error. Unicode escapes in them are not
+    /// supported.
+    pub fn with_identifiers(mut self, allow: bool) -> Self {
+        self.identifiers = allow;
+        self
     }
-}
 
-pub struct Reader<'a> {
-    chars: Peekable<Chars<'a>>,
-    buffer: Vec<(usize, usize, Result<Token, Error>)>,
-    line: usize,
-    col: usize,
-    msg_line: usize,
-    msg_col: usize,
-}
+    /// The most bytes a string token may have, counting its quotes. A longer string, such as
+    /// one whose closing quote is missing, is read as a [`ErrorCode::StringTooLong`] error
+    /// without copying it.
+    pub fn with_max_string_len(mut self, max: Option<usize>) -> Self {
+        self.max_string_len = max;
+        self
+    }
 
-impl<'a> Reader<'a> {
-    pub fn new(possible_json: &'a str) -> Reader<'a> {
-        Reader {
-            chars: possible_json.chars().peekable(),
-            buffer: Vec::<(usize, usize, Result<Token, Error>)>::new(),
-            line: 1,
-            col: 1,
-            msg_line: 1,
-            msg_col: 1,
-        }
+    /// The most bytes a number token may have, beyond which it is read as a
+    /// [`ErrorCode::NumberTooLong`] error.
+    pub fn with_max_number_len(mut self, max: Option<usize>) -> Self {
+        self.max_number_len = max;
+        self
+    }
+
+    /// The point just after the last token taken with [`Reader::next`]. Tokens that were only
+    /// peeked at are read again after resuming from here.
+    pub fn position(&self) -> Span {
+        let span = self.msg_span;
+        let token = &self.json[span.start - self.offset..span.end - self.offset];
+        let col = span.col + token.chars().count();
+        Span::new(span.end, span.end, span.line, col)
     }
 
     pub fn next(&mut self, num_tokens: usize) -> Vec<Result<Token, Error>> {
         self.read_in(num_tokens);
         self.buffer
             .drain(..min(self.buffer.len(), num_tokens))
-            .map(|(line, col, token)| {
-                self.msg_line = line;
-                self.msg_col = col;
+            .map(|(span, token)| {
+                self.msg_span = span;
                 token
             })
             .collect()
@@ -128,201 +149,381 @@ impl<'a> Reader<'a> {
         self.read_in(num_tokens);
         self.buffer[..min(self.buffer.len(), num_tokens)]
             .iter()
-            .map(|(_, _, token)| token.clone())
+            .map(|(_, token)| token.clone())
             .collect()
     }
 
-    fn peek_line_and_col(&mut self) -> Option<(usize, usize)> {
+    pub fn last_span(&self) -> Span {
+        self.msg_span
+    }
+
+    pub(crate) fn peek_span(&mut self) -> Option<Span> {
         self.read_in(1);
-        if self.buffer.is_empty() {
-            return None;
-        }
-        let (line, col, _) = self.buffer[0];
-        Some((line, col))
+        self.buffer.first().map(|(span, _)| *span)
     }
 
     fn read_in(&mut self, num_tokens: usize) {
-        if self.buffer.len() >= num_tokens {
-            return;
+        while self.buffer.len() < num_tokens {
+            match self.read_token() {
+                Some(token) => self.buffer.push(token),
+                None => break,
+            }
         }
+    }
 
-        let mut is_in_quotes = false;
-        let mut cur_token = String::new();
-
-        while let Some(c) = self.chars.next() {
-            match c {
-                '"' => {
-                    is_in_quotes = !is_in_quotes;
-                    cur_token.push('"');
-                }
-                '\\' if is_in_quotes => {
-                    cur_token.push('\\');
-                    if let Some(c) = self.chars.next() {
-                        cur_token.push(c);
-                    }
+    /// Reads one token, deciding its kind from the first character. Strings end at their
+    /// closing quote and other tokens at whitespace, punctuation, or the start of a string.
+    /// A string still open at a line break is read up to it as an
+    /// [`ErrorCode::UnterminatedString`] error, so that reading goes on from the next line
+    /// rather than taking the rest of the input as one string.
+    fn read_token(&mut self) -> Option<(Span, Result<Token, Error>)> {
+        self.read_whitespace();
+        let (start, c) = self.chars.next()?;
+        let (line, col) = (self.line, self.col);
+        self.col += 1;
+
+        let (kind, mut state) = match c {
+            ',' | ':' | '{' | '}' | '[' | ']' => {
+                let span = Span::new(self.offset + start, self.offset + start + 1, line, col);
+                return Some((span, Ok(Token::Punctuation(c))));
+            }
+            '"' => (Kind::String, State::InString),
+            '\'' if self.single_quotes => (Kind::String, State::InString),
+            '-' | '0'..='9' => (Kind::Number, State::Outside),
+            _ => (Kind::Literal, State::Outside),
+        };
+
+        let quote = c;
+        let mut end = start + c.len_utf8();
+        let mut unterminated = false;
+        let mut control = false;
+        while let Some(&(i, c)) = self.chars.peek() {
+            let escaped = state == State::Escaped;
+            state = match (state, c) {
+                (State::Outside, c) if c == '"' || is_whitespace(c) || is_punctuation(c) => break,
+                (State::InString, '\n' | '\r') => {
+                    unterminated = true;
+                    break;
                 }
-                c @ (',' | ':' | '{' | '}' | '[' | ']') if !is_in_quotes => {
-                    if cur_token.is_empty() {
-                        self.buffer
-                            .push(self.create_token(Ok(Token::Punctuation(c)), 0));
-                    } else {
-                        self.buffer.push(
-                            self.create_token(
-                                Token::try_from_token(&cur_token)
-                                    .ok_or(self.create_error(ErrorCode::ExpectedToken)),
-                                cur_token.len(),
-                            ),
-                        );
-                        cur_token.clear();
-                        self.buffer
-                            .push(self.create_token(Ok(Token::Punctuation(c)), 0));
-                    }
+                (State::InString, c) if c == quote => State::Closed,
+                (State::InString, '\0'..='\u{1f}') => {
+                    control = true;
+                    State::InString
                 }
-                c if !is_in_quotes && c.is_whitespace() => {
-                    if !cur_token.is_empty() {
-                        self.buffer.push(
-                            self.create_token(
-                                Token::try_from_token(&cur_token)
-                                    .ok_or(self.create_error(ErrorCode::ExpectedToken)),
-                                cur_token.len(),
-                            ),
-                        );
-                        cur_token.clear();
-                    }
-                    if c == '\n' || c == '\r' {
-                        self.line += 1;
-                        self.col = 0;
+                (State::InString, '\\') => State::Escaped,
+                (State::Escaped, _) => State::InString,
+                (state, _) => state,
+            };
+            self.chars.next();
+            end = i + c.len_utf8();
+            match c {
+                // An escaped line break continues the string on the next line, as in JSON5.
+                '\n' | '\r' if escaped => {
+                    if let Some(&(i, '\n')) = self.chars.peek().filter(|_| c == '\r') {
+                        self.chars.next();
+                        end = i + 1;
                     }
-                    self.read_whitespace();
-                }
-                c => {
-                    cur_token.push(c);
+                    self.line += 1;
+                    self.col = 1;
                 }
+                _ => self.col += 1,
             }
-            self.col += 1;
-
-            if self.buffer.len() >= num_tokens {
+            if state == State::Closed {
                 break;
             }
         }
 
-        assert!(self.buffer.is_empty() || self.buffer.len() - 1 <= num_tokens);
-        if !cur_token.is_empty() {
-            assert!(
-                self.buffer.len() < num_tokens,
-                "All required tokens must not have been parsed. Found {:?} {:?}",
-                self.buffer,
-                cur_token
-            );
-            self.buffer.push(
-                self.create_token(
-                    Token::try_from_token(&cur_token)
-                        .ok_or(self.create_error(ErrorCode::ExpectedToken)),
-                    cur_token.len(),
-                ),
-            );
-        }
-    }
-
-    fn create_token(
-        &self,
-        res: Result<Token, Error>,
-        token_len: usize,
-    ) -> (usize, usize, Result<Token, Error>) {
-        (self.line, self.col - token_len, res)
+        let span = Span::new(self.offset + start, self.offset + end, line, col);
+        let text = &self.json[start..end];
+        let too_long = |max: Option<usize>| max.is_some_and(|max| text.len() > max);
+        let token = match (kind, text) {
+            (Kind::String, _) if unterminated => {
+                Err(Error::new(ErrorCode::UnterminatedString, line, col))
+            }
+            (Kind::String, _) if too_long(self.max_string_len) => {
+                Err(Error::new(ErrorCode::StringTooLong, line, col))
+            }
+            (Kind::Number, _) if too_long(self.max_number_len) => {
+                Err(Error::new(ErrorCode::NumberTooLong, line, col))
+            }
+            (Kind::String, _) if control => Err(Error::new(ErrorCode::ControlCharacter, line, col)),
+            (Kind::String, "\"\"") => Ok(Token::String(Cow::Borrowed("\"\""))),
+            (Kind::String, _) => Ok(Token::String(Cow::Owned(text.to_string()))),
+            (Kind::Number, _) => Ok(Token::Number(
+                small_integer_text(text)
+                    .map_or_else(|| Cow::Owned(text.to_string()), Cow::Borrowed),
+            )),
+            (Kind::Literal, "null") => Ok(Token::Null),
+            (Kind::Literal, "true") => Ok(Token::Bool(true)),
+            (Kind::Literal, "false") => Ok(Token::Bool(false)),
+            (Kind::Literal, _) if self.identifiers && is_identifier(text) => {
+                Ok(Token::Identifier(text.to_string()))
+            }
+            (Kind::Literal, _) => Err(Error::new(ErrorCode::ExpectedToken, line, col)),
+        };
+        Some((span, token))
     }
 
     pub fn create_error(&self, code: ErrorCode) -> Error {
-        Error::new(code, self.msg_line, self.msg_col)
+        Error::new(code, self.msg_span.line, self.msg_span.col)
     }
 
     pub fn create_error_next(&mut self, code: ErrorCode) -> Error {
-        if let Some((line, col)) = self.peek_line_and_col() {
-            Error::new(code, line, col)
+        if let Some(span) = self.peek_span() {
+            Error::new(code, span.line, span.col)
         } else {
             self.create_error(code)
         }
     }
 
+    /// Skips whitespace, counting `\r\n` as a single line break.
     fn read_whitespace(&mut self) {
-        while let Some(c) = self.chars.peek() {
-            if !c.is_whitespace() {
+        while let Some(&(_, c)) = self.chars.peek() {
+            if !is_whitespace(c) {
                 break;
             }
+            self.chars.next();
 
             match c {
+                '\r' if matches!(self.chars.peek(), Some((_, '\n'))) => {}
                 '\n' | '\r' => {
                     self.line += 1;
                     self.col = 1;
                 }
-                ' ' | '\t' => {
-                    self.col += 1;
-                }
-                _ => {
-                    panic!("{c} is not a whitespace");
-                }
+                _ => self.col += 1,
             }
-            self.chars.next();
         }
     }
 }
 
-fn tokenize_into_strings(possible_json: &str) -> Vec<String> {
-    let mut is_in_quotes = false;
-    let mut tokens = Vec::<String>::new();
-    let mut cur_token = String::new();
-    let mut chars = possible_json.chars().peekable();
-
-    while let Some(c) = chars.next() {
-        match c {
-            '"' => {
-                is_in_quotes = !is_in_quotes;
-                cur_token.push(c);
-            }
-            '\\' if is_in_quotes => {
-                cur_token.push('\\');
-                if let Some(c) = chars.next() {
-                    cur_token.push(c);
-                }
-            }
-            c if !is_in_quotes && (Token::is_punctuation(&c) || c == '\n' || c == '\r') => {
-                if !cur_token.is_empty() {
-                    tokens.push(cur_token);
-                }
-                cur_token = String::new();
-                tokens.push(c.to_string());
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '$' || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || matches!(c, '$' | '_' | '\u{200c}' | '\u{200d}'))
+}
+
+fn is_punctuation(c: char) -> bool {
+    matches!(c, ',' | ':' | '{' | '}' | '[' | ']')
+}
+
+/// Whitespace as defined by RFC 8259. Other Unicode whitespace, such as form feed or a
+/// non-breaking space, is not allowed between tokens.
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// Strips the quotes from a string token, or returns `None` if it is unterminated.
+pub(crate) fn string_contents(token: &str) -> Option<&str> {
+    quoted_contents(token, '"')
+}
+
+/// Like [`string_contents`], for a token read by [`Reader::with_single_quotes`].
+pub(crate) fn single_quoted_contents(token: &str) -> Option<&str> {
+    quoted_contents(token, '\'')
+}
+
+fn quoted_contents(token: &str, quote: char) -> Option<&str> {
+    let contents = token.strip_prefix(quote)?.strip_suffix(quote)?;
+    let escapes = contents.len() - contents.trim_end_matches('\\').len();
+    (escapes % 2 == 0).then_some(contents)
+}
+
+/// The value of a number token that is an integer of at most three digits, without parsing
+/// it as a float. Such numbers, common in flags and sparse matrices, are always valid and
+/// exact if they have no leading zero.
+pub(crate) fn small_integer(number: &str) -> Option<f64> {
+    let (negative, digits) = match number.as_bytes() {
+        [b'-', digits @ ..] => (true, digits),
+        digits => (false, digits),
+    };
+    let n = match digits {
+        [b'0'] => 0,
+        [d @ b'1'..=b'9', rest @ ..] if rest.len() < 3 && rest.iter().all(u8::is_ascii_digit) => {
+            rest.iter()
+                .fold(u32::from(d - b'0'), |n, d| n * 10 + u32::from(d - b'0'))
+        }
+        _ => return None,
+    };
+    let n = f64::from(n);
+    Some(if negative { -n } else { n })
+}
+
+/// The shared text of a number token that [`small_integer`] reads, other than `-0`.
+fn small_integer_text(number: &str) -> Option<&'static str> {
+    static TEXTS: OnceLock<Vec<String>> = OnceLock::new();
+    let n = small_integer(number).filter(|n| !(*n == 0.0 && n.is_sign_negative()))?;
+    let texts = TEXTS.get_or_init(|| (-999..=999).map(|n: i32| n.to_string()).collect());
+    Some(&texts[(n as i32 + 999) as usize])
+}
+
+/// Checks `number` against the JSON number grammar: an optional minus sign, an integer part
+/// without leading zeros, then an optional fraction and exponent. The error's span covers
+/// `number` alone, starting at line 1, column 1.
+pub fn validate_number(number: &str) -> Result<(), ErrorCode> {
+    let invalid = || ErrorCode::invalid_number(number, Span::new(0, number.len(), 1, 1));
+    let bytes = number.as_bytes();
+    let digits = |i: usize| {
+        bytes[i.min(bytes.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+
+    let mut i = usize::from(bytes.first() == Some(&b'-'));
+    match (bytes.get(i), digits(i)) {
+        (Some(b'0'), _) => i += 1,
+        (_, 0) => return Err(invalid()),
+        (_, n) => i += n,
+    }
+    if bytes.get(i) == Some(&b'.') {
+        match digits(i + 1) {
+            0 => return Err(invalid()),
+            n => i += 1 + n,
+        }
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        match digits(i) {
+            0 => return Err(invalid()),
+            n => i += n,
+        }
+    }
+
+    if i == bytes.len() {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+/// Checks that `string` is a quoted JSON string with valid escapes and no unescaped control
+/// characters.
+pub fn validate_string(string: &str) -> Result<(), ErrorCode> {
+    let contents = string_contents(string).ok_or(ErrorCode::ExpectedDoubleQuote)?;
+    if contents.contains(|c: char| c < ' ') {
+        return Err(ErrorCode::ControlCharacter);
+    }
+    Unescaper::new()
+        .unescape(contents)
+        .map(|_| ())
+        .map_err(|_| ErrorCode::InvalidEscape)
+}
+
+/// Decodes the escapes in the contents of a JSON string, without its quotes.
+pub fn unescape(contents: &str) -> Result<String, ErrorCode> {
+    if contents.contains(|c: char| c < ' ') {
+        return Err(ErrorCode::ControlCharacter);
+    }
+    Unescaper::new()
+        .unescape(contents)
+        .map(str::to_string)
+        .map_err(|_| ErrorCode::InvalidEscape)
+}
+
+/// Decodes the escape sequences in string contents. The decoded text is built in a scratch
+/// buffer that is kept between calls, so reusing one `Unescaper` for a whole document avoids
+/// allocating a temporary string per escaped string.
+#[derive(Debug, Default)]
+pub struct Unescaper {
+    scratch: String,
+}
+
+impl Unescaper {
+    pub fn new() -> Self {
+        Unescaper::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Unescaper {
+            scratch: String::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the decoded contents, borrowing `escaped` unchanged when it has no escapes, or
+    /// the byte offset of the first invalid escape.
+    pub fn unescape<'s>(&'s mut self, escaped: &'s str) -> Result<&'s str, usize> {
+        self.decode(escaped, false)
+    }
+
+    /// Like [`Unescaper::unescape`], with the escapes JSON5 adds: `\'`, `\v`, `\0`, `\xHH`, a
+    /// backslash before a line break to continue the string on the next line, and a backslash
+    /// before any other character except a digit, which stands for that character.
+    pub fn unescape_json5<'s>(&'s mut self, escaped: &'s str) -> Result<&'s str, usize> {
+        self.decode(escaped, true)
+    }
+
+    fn decode<'s>(&'s mut self, escaped: &'s str, json5: bool) -> Result<&'s str, usize> {
+        if !escaped.contains('\\') {
+            return Ok(escaped);
+        }
+
+        self.scratch.clear();
+        let mut chars = escaped.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if c != '\\' {
+                self.scratch.push(c);
+                continue;
             }
-            c if !is_in_quotes && c.is_whitespace() => {
-                if !cur_token.is_empty() {
-                    tokens.push(cur_token);
-                    cur_token = String::new();
+            let decoded = match chars.next().map(|(_, c)| c) {
+                Some('"') => '"',
+                Some('\\') => '\\',
+                Some('/') => '/',
+                Some('b') => '\u{08}',
+                Some('f') => '\u{0c}',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                Some('t') => '\t',
+                Some('u') => match read_hex(&mut chars, 4) {
+                    Some(high @ 0xd800..=0xdbff) => {
+                        let low = match (chars.next(), chars.next()) {
+                            (Some((_, '\\')), Some((_, 'u'))) => read_hex(&mut chars, 4),
+                            _ => None,
+                        };
+                        match low {
+                            Some(low @ 0xdc00..=0xdfff) => {
+                                let c = 0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00);
+                                char::from_u32(c).ok_or(i)?
+                            }
+                            _ => return Err(i),
+                        }
+                    }
+                    Some(c) => char::from_u32(c).ok_or(i)?,
+                    None => return Err(i),
+                },
+                Some('v') if json5 => '\u{0b}',
+                Some('0') if json5 && !chars.as_str().starts_with(|c: char| c.is_ascii_digit()) => {
+                    '\0'
                 }
-                cur_token.push(' ');
-                while let Some(c) = chars.peek() {
-                    if *c == ' ' || *c == '\t' {
-                        cur_token.push(' ');
+                Some('x') if json5 => read_hex(&mut chars, 2).and_then(char::from_u32).ok_or(i)?,
+                Some('\r') if json5 => {
+                    if chars.as_str().starts_with('\n') {
                         chars.next();
-                    } else {
-                        break;
                     }
+                    continue;
                 }
-
-                if !cur_token.is_empty() {
-                    tokens.push(cur_token);
-                    cur_token = String::new();
-                }
-            }
-            _ => {
-                cur_token.push(c);
-            }
+                Some('\n' | '\u{2028}' | '\u{2029}') if json5 => continue,
+                Some(c) if json5 && !c.is_ascii_digit() => c,
+                _ => return Err(i),
+            };
+            self.scratch.push(decoded);
         }
+        Ok(&self.scratch)
     }
+}
 
-    if !cur_token.is_empty() {
-        tokens.push(cur_token);
+fn read_hex(chars: &mut std::str::CharIndices, len: usize) -> Option<u32> {
+    let digits = chars.as_str().get(..len)?;
+    let n = u32::from_str_radix(digits, 16).ok()?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
     }
-
-    tokens
+    chars.nth(len - 1);
+    Some(n)
 }
 
 #[cfg(test)]
@@ -340,7 +541,7 @@ mod tests {
             assert_eq!(
                 vec![
                     Ok(Token::Punctuation('{')),
-                    Ok(Token::String("\"age\"".to_string()))
+                    Ok(Token::String("\"age\"".into()))
                 ],
                 reader.peek(2)
             );
@@ -348,7 +549,7 @@ mod tests {
             assert_eq!(
                 vec![
                     Ok(Token::Punctuation('{')),
-                    Ok(Token::String("\"age\"".to_string()))
+                    Ok(Token::String("\"age\"".into()))
                 ],
                 reader.next(2)
             );
@@ -356,12 +557,12 @@ mod tests {
             assert_eq!(
                 vec![
                     Ok(Token::Punctuation(':')),
-                    Ok(Token::Number("30".to_string())),
+                    Ok(Token::Number("30".into())),
                     Ok(Token::Punctuation(',')),
-                    Ok(Token::String("\"is_student\"".to_string())),
+                    Ok(Token::String("\"is_student\"".into())),
                     Ok(Token::Punctuation(':')),
                     Ok(Token::Punctuation('[')),
-                    Ok(Token::Bool("false".to_string())),
+                    Ok(Token::Bool(false)),
                     Ok(Token::Punctuation(']')),
                     Ok(Token::Punctuation('}'))
                 ],
@@ -374,7 +575,7 @@ mod tests {
             let mut reader = Reader::new(r#""}, \n ""#);
 
             assert_eq!(
-                vec![Ok(Token::String(r#""}, \n ""#.to_string()))],
+                vec![Ok(Token::String(r#""}, \n ""#.into()))],
                 reader.next(1)
             );
         }
@@ -384,112 +585,304 @@ mod tests {
             let mut reader = Reader::new(r#"[,,]"#);
             assert_eq!(vec![Ok(Token::Punctuation('[')),], reader.peek(1));
             assert_eq!(vec![Ok(Token::Punctuation('[')),], reader.next(1));
-            assert_eq!(1, reader.msg_col);
+            assert_eq!(1, reader.last_span().col);
             assert_eq!(vec![Ok(Token::Punctuation(',')),], reader.peek(1));
             assert_eq!(vec![Ok(Token::Punctuation(',')),], reader.next(1));
-            assert_eq!(2, reader.msg_col);
+            assert_eq!(2, reader.last_span().col);
             assert_eq!(vec![Ok(Token::Punctuation(',')),], reader.peek(1));
             assert_eq!(vec![Ok(Token::Punctuation(',')),], reader.next(1));
-            assert_eq!(3, reader.msg_col);
+            assert_eq!(3, reader.last_span().col);
             assert_eq!(vec![Ok(Token::Punctuation(']')),], reader.next(1));
         }
-    }
 
-    mod tokenize_into_strings {
-        use super::*;
+        #[test]
+        fn read_single_quoted_strings() {
+            let json = r#"['a\'b"', "'"]"#;
+            let mut reader = Reader::new(json).with_single_quotes(true);
+            assert_eq!(
+                vec![
+                    Ok(Token::Punctuation('[')),
+                    Ok(Token::String(r#"'a\'b"'"#.into())),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::String(r#""'""#.into())),
+                    Ok(Token::Punctuation(']')),
+                ],
+                reader.next(5)
+            );
+            assert_eq!(Some(r#"a\'b""#), single_quoted_contents(r#"'a\'b"'"#));
+
+            let mut reader = Reader::new("'a'");
+            assert_eq!(
+                vec![Err(Error::new(ErrorCode::ExpectedToken, 1, 1))],
+                reader.next(1)
+            );
+        }
+
+        #[test]
+        fn read_identifiers() {
+            let mut reader = Reader::new("{$a_1: null, é: x-y, 1a}").with_identifiers(true);
+            assert_eq!(
+                vec![
+                    Ok(Token::Punctuation('{')),
+                    Ok(Token::Identifier("$a_1".to_string())),
+                    Ok(Token::Punctuation(':')),
+                    Ok(Token::Null),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::Identifier("é".to_string())),
+                    Ok(Token::Punctuation(':')),
+                    Err(Error::new(ErrorCode::ExpectedToken, 1, 17)),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::Number("1a".into())),
+                ],
+                reader.next(10)
+            );
+        }
+
+        #[test]
+        fn attach_spans_to_tokens() {
+            let mut reader = Reader::new("{\"a\\\"b\": [\"é\", 10]}");
+            let mut spans = Vec::<Span>::new();
+            while let Some(Ok(_)) = reader.next(1).pop() {
+                spans.push(reader.last_span());
+            }
+            assert_eq!(
+                vec![
+                    Span::new(0, 1, 1, 1),
+                    Span::new(1, 7, 1, 2),
+                    Span::new(7, 8, 1, 8),
+                    Span::new(9, 10, 1, 10),
+                    Span::new(10, 14, 1, 11),
+                    Span::new(14, 15, 1, 14),
+                    Span::new(16, 18, 1, 16),
+                    Span::new(18, 19, 1, 18),
+                    Span::new(19, 20, 1, 19),
+                ],
+                spans
+            );
+        }
+
         #[test]
         fn fail_space_separated_garbage() {
-            let json = "this is garbage";
+            let mut reader = Reader::new("this  \"d\"fds\"potato\"");
+            assert_eq!(
+                vec![
+                    Err(Error::new(ErrorCode::ExpectedToken, 1, 1)),
+                    Ok(Token::String("\"d\"".into())),
+                    Err(Error::new(ErrorCode::ExpectedToken, 1, 10)),
+                    Ok(Token::String("\"potato\"".into()))
+                ],
+                reader.next(4)
+            );
+        }
+
+        #[test]
+        fn pass_unterminated_string() {
+            assert_eq!(Some(r"a\\"), string_contents(r#""a\\""#));
+            assert_eq!(None, string_contents(r#""a\""#));
+            assert_eq!(None, string_contents("\""));
+
+            let mut reader = Reader::new(r#""a\", 1"#);
+            assert_eq!(vec![Ok(Token::String(r#""a\", 1"#.into()))], reader.next(2));
+        }
+
+        #[test]
+        fn fail_tokens_over_limits() {
+            let mut reader = Reader::new("[\"abc\", \"abcd\", 1.25, -1.25, \"abcdefgh")
+                .with_max_string_len(Some(5))
+                .with_max_number_len(Some(4));
+            assert_eq!(
+                vec![
+                    Ok(Token::Punctuation('[')),
+                    Ok(Token::String("\"abc\"".into())),
+                    Ok(Token::Punctuation(',')),
+                    Err(Error::new(ErrorCode::StringTooLong, 1, 9)),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::Number("1.25".into())),
+                    Ok(Token::Punctuation(',')),
+                    Err(Error::new(ErrorCode::NumberTooLong, 1, 23)),
+                    Ok(Token::Punctuation(',')),
+                    Err(Error::new(ErrorCode::StringTooLong, 1, 30)),
+                ],
+                reader.next(10)
+            );
+        }
+
+        #[test]
+        fn end_unterminated_string_at_line_break() {
+            let mut reader = Reader::new("[\"a\\\"b,\r\n \"c\"\n'd");
+            assert_eq!(
+                vec![
+                    Ok(Token::Punctuation('[')),
+                    Err(Error::new(ErrorCode::UnterminatedString, 1, 2)),
+                    Ok(Token::String("\"c\"".into())),
+                ],
+                reader.next(3)
+            );
+            assert_eq!(Span::new(10, 13, 2, 2), reader.last_span());
+            assert_eq!(
+                vec![Err(Error::new(ErrorCode::ExpectedToken, 3, 1))],
+                reader.next(1)
+            );
+        }
+
+        #[test]
+        fn fail_unescaped_control_characters() {
+            let mut reader = Reader::new("[\"a\u{0}\", \"\tb\", \"\\\t\", \"\u{7f}\"]");
             assert_eq!(
-                vec!["this", " ", "is", " ", "garbage"],
-                tokenize_into_strings(json)
+                vec![
+                    Ok(Token::Punctuation('[')),
+                    Err(Error::new(ErrorCode::ControlCharacter, 1, 2)),
+                    Ok(Token::Punctuation(',')),
+                    Err(Error::new(ErrorCode::ControlCharacter, 1, 8)),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::String("\"\\\t\"".into())),
+                    Ok(Token::Punctuation(',')),
+                    Ok(Token::String("\"\u{7f}\"".into())),
+                ],
+                reader.next(8)
             );
         }
 
         #[test]
-        fn fail_on_multiple_quotes_in_one_token() {
-            let json = r#"
-                "d"fds"potato"
-            "#;
-            let expected = vec![
-                "\n",
-                "                ",
-                "\"d\"fds\"potato\"",
-                "\n",
-                "            ",
-            ];
-            assert_eq!(expected, tokenize_into_strings(json));
+        fn count_lines_of_escaped_line_breaks() {
+            for line_break in ["\n", "\r\n", "\r"] {
+                let json = format!("{{'c': 'd\\{line_break}e',\n \"x\": tru}}");
+                let mut reader = Reader::new(&json).with_single_quotes(true);
+                let tokens = reader.next(8);
+                assert_eq!(
+                    Ok(Token::String(format!("'d\\{line_break}e'").into())),
+                    tokens[3]
+                );
+                assert_eq!(Err(Error::new(ErrorCode::ExpectedToken, 3, 7)), tokens[7]);
+            }
         }
 
         #[test]
-        fn pass_space_in_string() {
-            let json = "\" fjdsoif fds\" fd";
+        fn pass_rfc_whitespace() {
+            let mut reader = Reader::new("[\r\n\t1,\r\r 2,\n\n3]");
+            let mut positions = Vec::<(usize, usize)>::new();
+            while let Some(Ok(_)) = reader.next(1).pop() {
+                let span = reader.last_span();
+                positions.push((span.line, span.col));
+            }
             assert_eq!(
-                vec!["\" fjdsoif fds\"", " ", "fd"],
-                tokenize_into_strings(json)
+                vec![(1, 1), (2, 2), (2, 3), (4, 2), (4, 3), (6, 1), (6, 2)],
+                positions
             );
         }
 
         #[test]
-        fn should_separate_on_punctuation() {
-            let json = r#"{"age":30,"is_student":[false]}"#;
-            let expected = vec![
-                "{",
-                "\"age\"",
-                ":",
-                "30",
-                ",",
-                "\"is_student\"",
-                ":",
-                "[",
-                "false",
-                "]",
-                "}",
-            ];
-            assert_eq!(expected, tokenize_into_strings(json));
-        }
-    }
-
-    mod token {
+        fn fail_exotic_whitespace() {
+            let mut reader = Reader::new("[1,\u{0c}2]");
+            reader.next(3);
+            assert_eq!(
+                vec![Err(Error::new(ErrorCode::ExpectedToken, 1, 4))],
+                reader.next(1)
+            );
+        }
+    }
+
+    mod scalars {
         use super::*;
 
         #[test]
-        fn fail_space_separated_garbage() {
-            let expected = vec![
-                Error::new(ErrorCode::ExpectedToken, 1, 1),
-                Error::new(ErrorCode::ExpectedToken, 1, 6),
-            ];
-            let json = "this garbage";
-            assert_eq!(Err(expected), Token::try_from_json(json));
+        fn pass_valid_numbers() {
+            for number in ["0", "-0", "12", "-1.5", "0.25e10", "1E+2", "3e-04"] {
+                assert_eq!(Ok(()), validate_number(number), "{number}");
+            }
+        }
+
+        #[test]
+        fn read_small_integers() {
+            for n in -1200i32..=1200 {
+                let number = n.to_string();
+                let expected = (n.abs() < 1000).then_some(f64::from(n));
+                assert_eq!(expected, small_integer(&number), "{number}");
+            }
+            assert!(small_integer("-0").is_some_and(|n| n == 0.0 && n.is_sign_negative()));
+            for number in ["", "-", "00", "01", "-012", "1.0", "1e2", "+1", "1a", "0x1"] {
+                assert_eq!(None, small_integer(number), "{number}");
+            }
         }
 
         #[test]
-        fn pass_space_in_string() {
-            let json = "\"fjdsoif fds\"";
+        fn share_common_tokens() {
+            let shared = |token: &Result<Token, Error>| match token {
+                Ok(Token::String(text) | Token::Number(text)) => {
+                    matches!(text, Cow::Borrowed(_))
+                }
+                _ => panic!("{token:?} is not a string or number"),
+            };
+            let tokens = Reader::new(r#"0 -5 999 "" -0 1000 1.5 "a""#).next(8);
             assert_eq!(
-                Ok(vec![Token::String("\"fjdsoif fds\"".to_string())]),
-                Token::try_from_json(json)
+                vec![true, true, true, true, false, false, false, false],
+                tokens.iter().map(shared).collect::<Vec<bool>>()
             );
+            assert_eq!(Ok(Token::Number("-5".into())), tokens[1]);
         }
 
         #[test]
-        fn should_tokenize_on_punctuation() {
-            let json = r#" {"age":30,"is_student":[false]}"#;
-            let expected = vec![
-                Token::Whitespace(1),
-                Token::Punctuation('{'),
-                Token::String("\"age\"".into()),
-                Token::Punctuation(':'),
-                Token::Number("30".into()),
-                Token::Punctuation(','),
-                Token::String("\"is_student\"".into()),
-                Token::Punctuation(':'),
-                Token::Punctuation('['),
-                Token::Bool("false".into()),
-                Token::Punctuation(']'),
-                Token::Punctuation('}'),
-            ];
-            assert_eq!(Ok(expected), Token::try_from_json(json));
+        fn fail_invalid_numbers() {
+            for number in [
+                "", "-", "01", "+1", "1.", ".5", "1e", "1e+", "0x10", "-inf", "1.5.2",
+            ] {
+                let code = ErrorCode::invalid_number(number, Span::new(0, number.len(), 1, 1));
+                assert_eq!(Err(code), validate_number(number), "{number}");
+            }
+        }
+
+        #[test]
+        fn validate_and_unescape_strings() {
+            assert_eq!(Ok(()), validate_string(r#""a\u00e9""#));
+            assert_eq!(Err(ErrorCode::ExpectedDoubleQuote), validate_string("\"a"));
+            assert_eq!(Err(ErrorCode::InvalidEscape), validate_string(r#""\q""#));
+            assert_eq!(Ok("a\tb".to_string()), unescape(r"a\tb"));
+            assert_eq!(Err(ErrorCode::InvalidEscape), unescape(r"\ud800"));
+            assert_eq!(
+                Err(ErrorCode::ControlCharacter),
+                validate_string("\"a\u{1}b\"")
+            );
+            assert_eq!(Err(ErrorCode::ControlCharacter), unescape("a\u{1f}"));
+        }
+    }
+
+    mod unescaper {
+        use super::*;
+
+        #[test]
+        fn pass_escapes() {
+            let mut unescaper = Unescaper::new();
+            assert_eq!(Ok("plain"), unescaper.unescape("plain"));
+            assert_eq!(
+                Ok("a\"b\\c/\u{08}\u{0c}\n\r\t"),
+                unescaper.unescape(r#"a\"b\\c\/\b\f\n\r\t"#)
+            );
+            assert_eq!(Ok("é€😀"), unescaper.unescape(r"\u00e9\u20AC\ud83d\ude00"));
+        }
+
+        #[test]
+        fn fail_invalid_escapes() {
+            let mut unescaper = Unescaper::new();
+            assert_eq!(Err(2), unescaper.unescape(r"ab\x"));
+            assert_eq!(Err(0), unescaper.unescape(r"\u12"));
+            assert_eq!(Err(0), unescaper.unescape(r"\u+123"));
+            assert_eq!(Err(1), unescaper.unescape(r"a\ud83d"));
+            assert_eq!(Err(0), unescaper.unescape(r"\ude00"));
+            assert_eq!(Err(0), unescaper.unescape("\\"));
+        }
+
+        #[test]
+        fn json5_escapes() {
+            let mut unescaper = Unescaper::new();
+            assert_eq!(
+                Ok("it's \u{0b}\0A\u{e9}qab"),
+                unescaper.unescape_json5("it\\'s \\v\\0\\x41\\xe9\\qa\\\r\nb")
+            );
+            assert_eq!(Ok("\"\n"), unescaper.unescape_json5(r#"\"\n"#));
+            assert_eq!(Err(0), unescaper.unescape_json5(r"\01"));
+            assert_eq!(Err(0), unescaper.unescape_json5(r"\1"));
+            assert_eq!(Err(1), unescaper.unescape_json5(r"a\x4"));
+            assert_eq!(Err(0), unescaper.unescape(r"\'"));
         }
     }
 }
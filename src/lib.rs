@@ -0,0 +1,53 @@
+// `ErrorCode` holds the start of an invalid token inline so that it stays `Copy`, which makes
+// `Error` larger than this lint allows. That is cheaper than boxing the token, which would
+// allocate for every invalid number.
+#![allow(clippy::result_large_err)]
+
+pub mod analysis;
+pub mod array;
+#[cfg(feature = "bytes")]
+pub mod bytes;
+pub mod compact;
+pub mod decode;
+pub mod digest;
+pub mod encode;
+pub mod errors;
+pub mod expand;
+pub mod explore;
+pub mod form;
+pub mod html;
+pub mod index;
+pub mod jsonc;
+pub mod lenient;
+pub mod lexical;
+pub mod limits;
+pub mod map;
+pub mod merge;
+pub mod mutate;
+pub mod ndjson;
+pub mod parsing;
+pub mod patch;
+pub mod pointer;
+pub mod profile;
+pub mod query;
+pub mod sample;
+pub mod sarif;
+pub mod schema;
+pub mod serialize;
+pub mod shared;
+pub mod size;
+#[cfg(not(feature = "btree-map"))]
+pub mod small_map;
+pub mod split;
+pub mod stream;
+pub mod transcode;
+pub mod transform;
+pub mod tuning;
+pub mod typed;
+pub mod validate;
+pub mod visit;
+pub mod watch;
+pub mod writer;
+
+#[cfg(feature = "derive")]
+pub use json_parser_derive::{FromValue, ToValue};
@@ -0,0 +1,149 @@
+use std::{fmt, fmt::Display, fmt::Write};
+
+use crate::{
+    errors::ErrorCode,
+    parsing::{escape_key, Value},
+};
+
+/// Bounds on the shape of a document. The parser enforces them through
+/// [`ParseOptions::limits`](crate::parsing::ParseOptions::limits), and [`Value::check_limits`]
+/// and the `_with_limits` variants of the mutation APIs hold documents built or changed from
+/// untrusted input to the same bounds.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub struct Limits {
+    /// The most arrays and objects that may be nested inside one another. `[[1]]` has a depth
+    /// of 2 and a lone scalar 0.
+    pub max_depth: Option<usize>,
+    /// The most elements an array, or members an object, may have.
+    pub max_len: Option<usize>,
+}
+
+impl Limits {
+    pub(crate) fn allows_depth(&self, depth: usize) -> bool {
+        self.max_depth.is_none_or(|max| depth <= max)
+    }
+
+    pub(crate) fn allows_len(&self, len: usize) -> bool {
+        self.max_len.is_none_or(|max| len <= max)
+    }
+
+    /// Checks a container at `depth`, counting itself, with `len` items.
+    pub(crate) fn check_container(
+        &self,
+        depth: usize,
+        len: usize,
+        path: &str,
+    ) -> Result<(), LimitError> {
+        if !self.allows_depth(depth) {
+            Err(LimitError::new(ErrorCode::NestingTooDeep, path))
+        } else if !self.allows_len(len) {
+            Err(LimitError::new(ErrorCode::ContainerTooLarge, path))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// The first container of a document that goes beyond its [`Limits`].
+#[derive(Debug, PartialEq, Clone)]
+pub struct LimitError {
+    code: ErrorCode,
+    path: String,
+}
+
+impl LimitError {
+    fn new(code: ErrorCode, path: &str) -> Self {
+        LimitError {
+            code,
+            path: path.to_string(),
+        }
+    }
+
+    /// [`ErrorCode::NestingTooDeep`] or [`ErrorCode::ContainerTooLarge`].
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    /// JSON pointer to the container.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for LimitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for LimitError {}
+
+impl Value {
+    /// Checks the whole document against `limits`, such as after building it with
+    /// [`ToValue`](crate::encode::ToValue) from untrusted input.
+    pub fn check_limits(&self, limits: &Limits) -> Result<(), LimitError> {
+        check(self, 0, &mut String::new(), limits)
+    }
+}
+
+/// Checks `value`, which is at `path` inside `depth` containers.
+pub(crate) fn check(
+    value: &Value,
+    depth: usize,
+    path: &mut String,
+    limits: &Limits,
+) -> Result<(), LimitError> {
+    let end = path.len();
+    match value {
+        Value::Array(elements) => {
+            limits.check_container(depth + 1, elements.len(), path)?;
+            for (i, element) in elements.iter().enumerate() {
+                write!(path, "/{i}").unwrap();
+                check(element, depth + 1, path, limits)?;
+                path.truncate(end);
+            }
+        }
+        Value::Object(members) => {
+            limits.check_container(depth + 1, members.len(), path)?;
+            for (key, member) in members {
+                write!(path, "/{}", escape_key(key)).unwrap();
+                check(member, depth + 1, path, limits)?;
+                path.truncate(end);
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn check_built_documents() {
+        let value = Parser::parse(r#"{"a": [1, [2, 3]], "b/c": {"d": [[]]}}"#).unwrap();
+        let limits = |max_depth, max_len| Limits { max_depth, max_len };
+
+        assert_eq!(Ok(()), value.check_limits(&Limits::default()));
+        assert_eq!(Ok(()), value.check_limits(&limits(Some(4), Some(2))));
+        assert_eq!(Ok(()), Value::Null.check_limits(&limits(Some(0), Some(0))));
+
+        let error = value.check_limits(&limits(Some(3), None)).unwrap_err();
+        assert_eq!(ErrorCode::NestingTooDeep, error.code());
+        assert_eq!("/b~1c/d/0", error.path());
+        assert_eq!(
+            "Nesting is deeper than the limit at /b~1c/d/0",
+            error.to_string()
+        );
+
+        let error = value.check_limits(&limits(None, Some(1))).unwrap_err();
+        assert_eq!(ErrorCode::ContainerTooLarge, error.code());
+        assert_eq!("", error.path());
+    }
+}
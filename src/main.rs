@@ -1,49 +1,1510 @@
-mod errors;
-mod lexical;
-mod parsing;
-use std::fs;
+use json_parser::{
+    array::Stitcher,
+    digest::Digest,
+    encode::ToValue,
+    errors::{self, Error, Severity},
+    html,
+    index::DocumentIndex,
+    parsing,
+    parsing::Value,
+    profile::Profile,
+    query, sample, sarif,
+    schema::{DirectoryResolver, Keywords, Schema, Violation},
+    serialize,
+    split::{self, ArrayJoiner, Split, SplitError},
+    stream::StreamParser,
+    transcode::{self, TranscodeError},
+    validate,
+    writer::JsonWriter,
+};
+use sha2::{Digest as _, Sha256};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    env,
+    fmt::Display,
+    fs,
+    hint::black_box,
+    io::{self, BufRead, BufWriter, Write},
+    path::Path,
+    process::{self, Command},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        OnceLock,
+    },
+    time::{Duration, Instant},
+};
 
-fn time_test(test: String, file_size_bytes: usize, process: impl Fn()) {
+const USAGE: &str = "usage: json-parser [--quiet | --porcelain] <command>
+
+commands:
+    bench [--format text|json|csv]
+    fetch-corpus
+    validate [--format text|sarif] [--jobs <n>] [--group] <file>...
+    check --schema <schema> [--assert-formats] [--ndjson] <file|->
+    fmt [--format json|html] [--indent <n>] [--max-width <n>] [--sort-keys] <file>
+    convert --from json|compact --to json|compact [--source-map <file>] <input> <output>
+    merge [--merge-patch] [--indent <n>] <base> <override>...
+    patch [--indent <n>] <document> <patch>
+    sort [--at <pointer>] [--dedup] [--indent <n>] <file> <key>
+    stitch --at <pointer> [--id <pointer>] [--indent <n>] <file>...
+    sample [--at <query>] [--seed <n>] <file> <n>
+    split --size <n> | --files <n> <file> <prefix>
+    join <output> <file>...
+    get <file|-> <query>
+    paths [--types] [--values] <file>
+    keys [--at <pointer>] <file>
+    explore <file>
+    profile <file|->
+    explain <code>";
+
+/// The input was not valid JSON, or did not pass validation.
+const EXIT_INVALID: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+/// A file or stream could not be read or written.
+const EXIT_IO: i32 = 3;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Output {
+    Text,
+    Quiet,
+    /// One tab-separated line per diagnostic: file, line, column, code, message.
+    Porcelain,
+}
+
+static OUTPUT: OnceLock<Output> = OnceLock::new();
+
+const CONFIG_FILE: &str = ".jsonparser.json";
+
+/// Project defaults from the nearest `.jsonparser.json` in the current directory or one of
+/// its parents. Command line flags take precedence.
+struct Config {
+    format: serialize::FormatConfig,
+    /// Extensions to strict JSON accepted in documents that are parsed whole.
+    parse: parsing::ParseOptions,
+    /// Levels for warning codes, where `None` turns the warning off.
+    rules: Vec<(String, Option<Severity>)>,
+}
+
+fn config() -> &'static Config {
+    static CONFIG: OnceLock<Config> = OnceLock::new();
+    CONFIG.get_or_init(load_config)
+}
+
+fn load_config() -> Config {
+    let mut config = Config {
+        format: serialize::FormatConfig::pretty(),
+        parse: parsing::ParseOptions::default(),
+        rules: Vec::<(String, Option<Severity>)>::new(),
+    };
+    let Some(path) = env::current_dir().ok().and_then(|dir| {
+        dir.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }) else {
+        return config;
+    };
+
+    let file = path.display().to_string();
+    let setting_error = |key: &str| -> ! { invalid(format!("{file}: invalid setting {key}")) };
+    let count = |key: &str, value: Value| match value {
+        Value::Number(n) if n >= 0.0 && n.fract() == 0.0 => n as usize,
+        _ => setting_error(key),
+    };
+
+    let Value::Object(settings) = parse_file_with(&file, parsing::ParseOptions::default()) else {
+        invalid(format!("{file}: expected an object of settings"))
+    };
+    for (key, value) in settings {
+        match (&*key, value) {
+            ("format", Value::Object(format)) => {
+                for (key, value) in format {
+                    match (&*key, value) {
+                        ("indent", Value::Null) => config.format.indent = None,
+                        ("indent", value) => {
+                            config.format.indent = Some(count("format.indent", value))
+                        }
+                        ("max-width", value) => {
+                            config.format.max_width = Some(count("format.max-width", value))
+                        }
+                        ("sort-keys", Value::Bool(b)) => config.format.sort_keys = b,
+                        (key, _) => setting_error(&format!("format.{key}")),
+                    }
+                }
+            }
+            ("lenient", Value::Object(lenient)) => {
+                for (key, value) in lenient {
+                    match (&*key, value) {
+                        ("trailing-commas", Value::Bool(b)) => {
+                            config.parse.allow_trailing_commas = b
+                        }
+                        ("single-quotes", Value::Bool(b)) => config.parse.allow_single_quotes = b,
+                        ("unquoted-keys", Value::Bool(b)) => config.parse.allow_unquoted_keys = b,
+                        (key, _) => setting_error(&format!("lenient.{key}")),
+                    }
+                }
+            }
+            ("rules", Value::Object(rules)) => {
+                for (code, level) in rules {
+                    let level = match level {
+                        Value::String(level) if level == "off" => None,
+                        Value::String(level) if level == "warning" => Some(Severity::Warning),
+                        Value::String(level) if level == "error" => Some(Severity::Error),
+                        _ => setting_error(&format!("rules.{code}")),
+                    };
+                    config.rules.push((code.to_string(), level));
+                }
+            }
+            (key, _) => setting_error(key),
+        }
+    }
+    config
+}
+
+/// Applies the configured rule levels. Only warnings can be reconfigured; a document that
+/// fails to parse is always an error.
+fn apply_rules(errors: Vec<Error>) -> Vec<Error> {
+    errors
+        .into_iter()
+        .filter_map(|error| {
+            if error.code().severity() != Severity::Warning {
+                return Some(error);
+            }
+            match config()
+                .rules
+                .iter()
+                .find(|(code, _)| code == error.code().id())
+            {
+                Some((_, None)) => None,
+                Some((_, Some(severity))) => Some(error.with_severity(*severity)),
+                None => Some(error),
+            }
+        })
+        .collect()
+}
+
+fn output() -> Output {
+    *OUTPUT.get().unwrap_or(&Output::Text)
+}
+
+fn diagnostic(file: &str, error: &Error) -> Option<String> {
+    match output() {
+        Output::Text => Some(format!("{file}:{error}")),
+        Output::Quiet => None,
+        Output::Porcelain => Some(format!(
+            "{file}\t{}\t{}\t{}\t{}",
+            error.line(),
+            error.col(),
+            error.code().id(),
+            error.message()
+        )),
+    }
+}
+
+/// Counts heap allocations while [`COUNTING`] is set, which only `bench` does, so that other
+/// commands pay for no more than a load of the flag.
+struct CountingAllocator;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+impl CountingAllocator {
+    fn count(&self) {
+        if COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        self.count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        self.count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+struct BenchResult {
+    file: String,
+    bytes: usize,
+    runs: u32,
+    /// Runs left out of the statistics for being far slower or faster than the rest.
+    outliers: usize,
+    /// Statistics of the throughput in MB/s of each remaining run. The p95 is the throughput
+    /// of the run that 95% of them were at least as fast as.
+    mean: f64,
+    median: f64,
+    p95: f64,
+    stddev: f64,
+    /// Allocations per run.
+    allocations: u64,
+}
+
+fn time_test<T>(file: String, file_size_bytes: usize, process: impl Fn() -> T) -> BenchResult {
+    const NUM_WARM_UP_RUNS: u32 = 10;
     const NUM_RUNS: u32 = 100;
 
-    let start_time = std::time::Instant::now();
+    // Fills caches and lets the allocator reach a steady state before anything is measured.
+    for _ in 0..NUM_WARM_UP_RUNS {
+        black_box(process());
+    }
+
+    let mut times = Vec::<Duration>::with_capacity(NUM_RUNS as usize);
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    COUNTING.store(true, Ordering::Relaxed);
     for _ in 0..NUM_RUNS {
-        process();
+        let start_time = Instant::now();
+        black_box(process());
+        times.push(start_time.elapsed());
     }
-    let end_time = std::time::Instant::now();
+    COUNTING.store(false, Ordering::Relaxed);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) / NUM_RUNS as u64;
+
+    // Tukey's fences: runs more than 1.5 interquartile ranges outside the middle half are
+    // usually the scheduler or another process, not the parser.
+    times.sort();
+    let (q1, q3) = (times[times.len() / 4], times[times.len() * 3 / 4]);
+    let fence = (q3 - q1) * 3 / 2;
+    let low = q1.saturating_sub(fence);
+    times.retain(|time| (low..=q3 + fence).contains(time));
+    let outliers = NUM_RUNS as usize - times.len();
 
+    // Fastest first, as the times are sorted.
     let mbs = file_size_bytes as f64 / 1_000_000.0;
-    let mbps = mbs * NUM_RUNS as f64 / ((end_time - start_time).as_secs_f64());
+    let throughputs = times
+        .iter()
+        .map(|time| mbs / time.as_secs_f64())
+        .collect::<Vec<f64>>();
+    let mean = throughputs.iter().sum::<f64>() / throughputs.len() as f64;
+    let variance = throughputs
+        .iter()
+        .map(|throughput| (throughput - mean).powi(2))
+        .sum::<f64>()
+        / (throughputs.len() - 1).max(1) as f64;
+    BenchResult {
+        file,
+        bytes: file_size_bytes,
+        runs: NUM_RUNS,
+        outliers,
+        mean,
+        median: throughputs[throughputs.len() / 2],
+        p95: throughputs[(throughputs.len() * 95).div_ceil(100) - 1],
+        stddev: variance.sqrt(),
+        allocations,
+    }
+}
 
-    println!("[{test}] Parsing speed: {mbps:.2} MB/s");
+fn read_json(file_name: &str) -> BenchResult {
+    let contents = fs::read_to_string(file_name).expect("Should have been able to read the file");
+    parse_json(file_name.to_string(), &contents)
 }
 
-fn get_file_contents(file_name: &str) -> (String, usize) {
-    let file = fs::File::open(file_name).unwrap();
-    let file_size_bytes = file.metadata().unwrap().len().try_into().unwrap();
+fn parse_json(file: String, contents: &str) -> BenchResult {
+    time_test(file, contents.len(), || {
+        match parsing::Parser::parse(black_box(contents)) {
+            Ok(value) => value,
+            Err(error) => panic!("error: {:?}", error[0]),
+        }
+    })
+}
 
-    let contents = fs::read_to_string(file_name).expect("Should have been able to read the file");
+fn bench(args: &[String]) {
+    let format = match args {
+        [] => "text",
+        [flag, format] if flag == "--format" => match format.as_str() {
+            f @ ("text" | "json" | "csv") => f,
+            _ => usage_error(),
+        },
+        _ => usage_error(),
+    };
+
+    // Mostly small integers and literals, as in sparse matrices and maps of flags.
+    let row = "[0, 0, 1, 0, -1, 0, 0, 255, 0, 0, true, false, null, \"\"]";
+    let results = [
+        read_json("tests/canada.json"),
+        read_json("tests/twitter.json"),
+        parse_json(
+            "sparse matrix".to_string(),
+            &format!("[{}]", vec![row; 20_000].join(",\n")),
+        ),
+    ];
+
+    match format {
+        "json" => {
+            let out = io::stdout().lock();
+            if let Err(error) = write_bench_json(out, &results) {
+                io_error("could not write standard output".to_string(), error);
+            }
+            println!();
+        }
+        "csv" => {
+            println!(
+                "file,bytes,runs,outliers,mean_mbps,median_mbps,p95_mbps,stddev_mbps,allocations"
+            );
+            for r in &results {
+                let file = if r.file.contains([',', '"', '\n']) {
+                    format!("\"{}\"", r.file.replace('"', "\"\""))
+                } else {
+                    r.file.clone()
+                };
+                println!(
+                    "{file},{},{},{},{:.2},{:.2},{:.2},{:.2},{}",
+                    r.bytes, r.runs, r.outliers, r.mean, r.median, r.p95, r.stddev, r.allocations
+                );
+            }
+        }
+        _ => {
+            for r in &results {
+                println!(
+                    "[{}] Parsing speed: {:.2} ± {:.2} MB/s (median {:.2}, p95 {:.2}), \
+                     {} allocations, {} of {} runs rejected as outliers",
+                    r.file, r.mean, r.stddev, r.median, r.p95, r.allocations, r.outliers, r.runs
+                );
+            }
+        }
+    }
+}
 
-    (contents, file_size_bytes)
+fn write_bench_json(out: impl Write, results: &[BenchResult]) -> io::Result<()> {
+    let mut writer = JsonWriter::new(out, serialize::FormatConfig::pretty());
+    writer.start_array()?;
+    for r in results {
+        writer.start_object()?;
+        writer.key("file")?;
+        writer.string(&r.file)?;
+        for (key, n) in [
+            ("bytes", r.bytes as f64),
+            ("runs", r.runs as f64),
+            ("outliers", r.outliers as f64),
+            ("mean_mbps", r.mean),
+            ("median_mbps", r.median),
+            ("p95_mbps", r.p95),
+            ("stddev_mbps", r.stddev),
+            ("allocations", r.allocations as f64),
+        ] {
+            writer.key(key)?;
+            writer.number(n)?;
+        }
+        writer.end_object()?;
+    }
+    writer.end_array()?;
+    writer.finish()?.flush()
 }
 
-fn read_json(file_name: &str) {
-    // let (contents, file_size_bytes) = get_file_contents(file_name);
-    let contents = fs::read_to_string(file_name).expect("Should have been able to read the file");
-    let file_size_bytes = contents.len();
-    time_test(
-        format!("read {file_name}"),
-        file_size_bytes,
-        || match parsing::Parser::parse(&contents) {
-            Ok(json) => {
-                // println!("{json:#?}");
+const CORPUS_DIR: &str = "tests";
+
+/// Files `fetch-corpus` downloads into [`CORPUS_DIR`], with the SHA-256 each must match. A
+/// file without one is skipped with a warning that prints its checksum, so that it can be
+/// pinned.
+const CORPUS: &[(&str, &str, Option<&str>)] = &[
+    (
+        "canada.json",
+        "https://raw.githubusercontent.com/serde-rs/json-benchmark/master/data/canada.json",
+        Some("f83b3b354030d5dd58740c68ac4fecef64cb730a0d12a90362a7f23077f50d78"),
+    ),
+    (
+        "twitter.json",
+        "https://raw.githubusercontent.com/serde-rs/json-benchmark/master/data/twitter.json",
+        Some("7306ed9b6f34b375ed2a087ac5f5d2b2993d37f46924817e1cf6a9e060d477e4"),
+    ),
+    (
+        "citm_catalog.json",
+        "https://raw.githubusercontent.com/serde-rs/json-benchmark/master/data/citm_catalog.json",
+        None,
+    ),
+];
+
+const JSON_TEST_SUITE: &str = "https://github.com/nst/JSONTestSuite";
+
+/// The commit of [`JSON_TEST_SUITE`] that `fetch-corpus` checks out. Without one, the suite is
+/// skipped with a warning that prints the commit at its `HEAD`, so that it can be pinned.
+const JSON_TEST_SUITE_COMMIT: Option<&str> = None;
+
+fn fetch_corpus(args: &[String]) {
+    if !args.is_empty() {
+        usage_error();
+    }
+    let checksum = |contents: &[u8]| Digest(Sha256::digest(contents).into()).to_string();
+
+    for (name, url, pinned) in CORPUS {
+        let file = format!("{CORPUS_DIR}/{name}");
+        if let Ok(contents) = fs::read(&file) {
+            if pinned.is_some_and(|pinned| checksum(&contents) == pinned) {
+                println!("{file} is up to date");
+                continue;
             }
-            Err(error) => panic!("error: {:?}", error[0]),
-        },
-    );
+        }
+
+        let contents = download(url);
+        let actual = checksum(&contents);
+        match pinned {
+            Some(pinned) if actual != *pinned => {
+                invalid(format!("{url}: checksum {actual} does not match {pinned}"))
+            }
+            Some(_) => println!("fetched {file}"),
+            None => {
+                eprintln!("warning: {url}: no pinned checksum, skipped; pin {actual} to use it");
+                continue;
+            }
+        }
+        if let Err(error) = fs::write(&file, contents) {
+            io_error(format!("could not write {file}"), error);
+        }
+    }
+
+    // The suite is hundreds of small files, so it is pinned by commit rather than checksummed.
+    let suite = format!("{CORPUS_DIR}/JSONTestSuite");
+    let Some(commit) = JSON_TEST_SUITE_COMMIT else {
+        let head = run(Command::new("git").args(["ls-remote", JSON_TEST_SUITE, "HEAD"]));
+        let head = String::from_utf8_lossy(&head);
+        let head = head.split_whitespace().next().unwrap_or_default();
+        eprintln!("warning: {JSON_TEST_SUITE}: no pinned commit, skipped; pin {head} to use it");
+        return;
+    };
+    let git = |args: &[&str]| run(Command::new("git").arg("-C").arg(&suite).args(args));
+    if Path::new(&suite).exists() {
+        let checked_out = git(&["rev-parse", "HEAD"]);
+        if String::from_utf8_lossy(&checked_out).trim() == commit {
+            println!("{suite} is up to date");
+            return;
+        }
+    } else if let Err(error) = fs::create_dir_all(&suite) {
+        io_error(format!("could not create {suite}"), error);
+    }
+    git(&["init", "--quiet"]);
+    git(&["fetch", "--quiet", "--depth", "1", JSON_TEST_SUITE, commit]);
+    git(&["checkout", "--quiet", "--detach", "FETCH_HEAD"]);
+    println!("fetched {suite} at {commit}");
+}
+
+fn download(url: &str) -> Vec<u8> {
+    run(Command::new("curl").args(["--fail", "--silent", "--show-error", "--location", url]))
+}
+
+/// Runs a command and returns its standard output, exiting if it fails.
+fn run(command: &mut Command) -> Vec<u8> {
+    let program = command.get_program().to_string_lossy().into_owned();
+    match command.output() {
+        Ok(output) if output.status.success() => output.stdout,
+        Ok(output) => {
+            eprintln!(
+                "error: {program} failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            process::exit(EXIT_IO);
+        }
+        Err(error) => io_error(format!("could not run {program}"), error),
+    }
+}
+
+fn validate(args: &[String]) {
+    let mut format = "text";
+    let mut jobs = 0;
+    let mut group = false;
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().map(String::as_str) {
+                Some(f @ ("text" | "sarif")) => format = f,
+                _ => usage_error(),
+            },
+            "--jobs" => jobs = parse_number_arg(args.next()),
+            "--group" => group = true,
+            file => files.push(file),
+        }
+    }
+    if files.is_empty() {
+        usage_error();
+    }
+
+    let options = parsing::ParseOptions {
+        dangerous_keys: parsing::DangerousKeys::Warn,
+        ..config().parse
+    };
+    let mut reports = Vec::<(&str, Vec<Error>)>::new();
+    for (file, report) in files
+        .iter()
+        .zip(validate::validate_files_with_options(&files, jobs, options))
+    {
+        match report.into_diagnostics() {
+            Ok(errors) => reports.push((file, apply_rules(errors))),
+            Err(error) => io_error(format!("could not read {file}"), error),
+        }
+    }
+
+    match format {
+        "sarif" => {
+            let files = reports
+                .iter()
+                .map(|(file, errors)| (*file, errors.as_slice()))
+                .collect::<Vec<(&str, &[Error])>>();
+            let log = sarif::report(&files);
+            println!(
+                "{}",
+                serialize::to_string(&log, &serialize::FormatConfig::pretty())
+            );
+        }
+        _ if group && matches!(output(), Output::Text) => {
+            for (file, errors) in &reports {
+                for (container, errors) in errors::group_by_container(errors) {
+                    println!(
+                        "{file}: {} in {}",
+                        count_diagnostics(&errors),
+                        match container {
+                            "" => "the document",
+                            container => container,
+                        }
+                    );
+                    for error in errors {
+                        println!("  {file}:{error}");
+                    }
+                }
+            }
+        }
+        _ => {
+            for (file, errors) in &reports {
+                for line in errors.iter().filter_map(|error| diagnostic(file, error)) {
+                    println!("{line}");
+                }
+            }
+        }
+    }
+
+    if reports
+        .iter()
+        .flat_map(|(_, errors)| errors)
+        .any(|e| e.severity() == Severity::Error)
+    {
+        process::exit(EXIT_INVALID);
+    }
+}
+
+fn explain(args: &[String]) {
+    let [id] = args else { usage_error() };
+    match errors::explain(id) {
+        Some(explanation) => println!("{explanation}"),
+        None => {
+            eprintln!("error: no error code {id}");
+            process::exit(EXIT_USAGE);
+        }
+    }
+}
+
+/// Such as "2 errors and 1 warning".
+fn count_diagnostics(diagnostics: &[&Error]) -> String {
+    let plural = |n: usize, noun: &str| match n {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    };
+    let errors = diagnostics
+        .iter()
+        .filter(|error| error.severity() == Severity::Error)
+        .count();
+    let warnings = diagnostics.len() - errors;
+    match (errors, warnings) {
+        (_, 0) => plural(errors, "error"),
+        (0, _) => plural(warnings, "warning"),
+        _ => format!(
+            "{} and {}",
+            plural(errors, "error"),
+            plural(warnings, "warning")
+        ),
+    }
+}
+
+fn check(args: &[String]) {
+    let mut schema_file = None;
+    let mut assert_formats = false;
+    let mut ndjson = false;
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--assert-formats" => assert_formats = true,
+            "--ndjson" => ndjson = true,
+            "--schema" => {
+                schema_file = Some(args.next().map_or_else(|| usage_error(), String::as_str))
+            }
+            file => files.push(file),
+        }
+    }
+    let (Some(schema_file), [file]) = (schema_file, &files[..]) else {
+        usage_error()
+    };
+
+    // `$ref`s to other files are relative to the schema's directory.
+    let dir = Path::new(schema_file).parent().unwrap_or(Path::new(""));
+    let resolver = DirectoryResolver::new(dir);
+    let schema = Schema::with_resolver(parse_file(schema_file), Keywords::new(), &resolver)
+        .unwrap_or_else(|error| invalid(format!("{schema_file}: {error}")))
+        .with_format_assertion(assert_formats);
+    if ndjson {
+        return check_ndjson(&schema, file);
+    }
+
+    let contents = if *file == "-" {
+        io::read_to_string(io::stdin())
+            .unwrap_or_else(|error| io_error("could not read standard input".to_string(), error))
+    } else {
+        read_file(file)
+    };
+    let value = match parsing::Parser::parse_with_options(&contents, config().parse) {
+        Ok((value, _)) => value,
+        Err(errors) => parse_error(file, &errors),
+    };
+    let violations = schema.validate(&value);
+    if !violations.is_empty() {
+        print_violations(file, &contents, 0, &violations);
+        process::exit(EXIT_INVALID);
+    }
+}
+
+/// Validates each line of an NDJSON file, or of standard input given `-`, without holding
+/// more than one record in memory, and ends with a count of the failed records.
+fn check_ndjson(schema: &Schema, file: &str) {
+    let on_failure =
+        |line: usize, record: &str, result: Result<&[Violation], &[Error]>| match result {
+            Ok(violations) => print_violations(file, record, line - 1, violations),
+            Err(errors) => {
+                for line in errors.iter().filter_map(|error| diagnostic(file, error)) {
+                    eprintln!("{line}");
+                }
+            }
+        };
+    let report = if file == "-" {
+        schema.validate_ndjson_with_options(io::stdin().lock(), config().parse, on_failure)
+    } else {
+        let reader = fs::File::open(file)
+            .map(io::BufReader::new)
+            .unwrap_or_else(|error| io_error(format!("could not read {file}"), error));
+        schema.validate_ndjson_with_options(reader, config().parse, on_failure)
+    }
+    .unwrap_or_else(|error| io_error(format!("could not read {file}"), error));
+
+    if output() == Output::Text {
+        println!(
+            "{} records, {} failed, {} violations, {} not valid JSON",
+            report.records(),
+            report.failed().len(),
+            report.violations(),
+            report.unparsable().len()
+        );
+    }
+    if !report.is_valid() {
+        process::exit(EXIT_INVALID);
+    }
+}
+
+/// Prints each violation at the position of the rejected value in `json`, a document that
+/// starts `lines` lines into `file`. A violation whose value cannot be located is printed
+/// without a position.
+fn print_violations(file: &str, json: &str, lines: usize, violations: &[Violation]) {
+    let index = DocumentIndex::with_options(json, config().parse).ok();
+    for violation in violations {
+        let position = index
+            .as_ref()
+            .and_then(|index| index.lines_of(violation.instance_path()))
+            .map(|lines_of| lines_of.start);
+        match (output(), position) {
+            (Output::Text, Some((line, col))) => {
+                println!("{file}:{}:{col}: {violation}", line + lines)
+            }
+            (Output::Text, None) => println!("{file}: {violation}"),
+            (Output::Quiet, _) => {}
+            (Output::Porcelain, position) => {
+                let (line, col) = position
+                    .map(|(line, col)| ((line + lines).to_string(), col.to_string()))
+                    .unwrap_or_default();
+                println!(
+                    "{file}\t{line}\t{col}\t{}\t{}\t{}",
+                    violation.keyword(),
+                    violation.message(),
+                    violation.keyword_path()
+                )
+            }
+        }
+    }
+}
+
+fn fmt(args: &[String]) {
+    let mut format = "json";
+    let mut config = config().format.clone();
+    let mut file = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => match args.next().map(String::as_str) {
+                Some(f @ ("json" | "html")) => format = f,
+                _ => usage_error(),
+            },
+            "--indent" => config.indent = Some(parse_number_arg(args.next())),
+            "--max-width" => config.max_width = Some(parse_number_arg(args.next())),
+            "--sort-keys" => config.sort_keys = true,
+            f if file.is_none() => file = Some(f),
+            _ => usage_error(),
+        }
+    }
+    let Some(file) = file else { usage_error() };
+
+    let value = parse_file(file);
+    match format {
+        "html" => print!("{}", html::render_html(&value)),
+        _ => println!("{}", serialize::to_string(&value, &config)),
+    }
+}
+
+fn convert(args: &[String]) {
+    let mut from = None;
+    let mut to = None;
+    let mut source_map = None;
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--from" => from = args.next().map(String::as_str),
+            "--to" => to = args.next().map(String::as_str),
+            "--source-map" => source_map = Some(args.next().unwrap_or_else(|| usage_error())),
+            file => files.push(file),
+        }
+    }
+    let [input, output] = files[..] else {
+        usage_error()
+    };
+    // Formats that `convert` is meant to take once the crate can read and write them.
+    for format in [from, to].into_iter().flatten() {
+        if let "msgpack" | "cbor" | "yaml" | "csv" = format {
+            eprintln!("error: convert does not support {format} yet, only json and compact");
+            process::exit(EXIT_USAGE);
+        }
+    }
+
+    match (from, to) {
+        (Some("json"), Some("json")) => {
+            // Streams so that large documents are never held in memory as a `Value`.
+            let contents = read_file(input);
+            let out = BufWriter::new(create_file(output));
+            let writer = JsonWriter::new(out, serialize::FormatConfig::compact());
+            let reader = StreamParser::new(&contents);
+            let result = match source_map {
+                Some(_) => transcode::transcode_with_source_map(reader, writer).map(Some),
+                None => transcode::transcode(reader, writer).map(|_| None),
+            };
+            match result {
+                Ok(None) => {}
+                Ok(Some((_, map))) => {
+                    let file = source_map.expect("a source map was requested");
+                    let json =
+                        serialize::to_string(&map.to_value(), &serialize::FormatConfig::compact());
+                    if let Err(error) = fs::write(file, json) {
+                        io_error(format!("could not write {file}"), error);
+                    }
+                }
+                Err(TranscodeError::Parse(error)) => parse_error(input, &[error]),
+                Err(TranscodeError::Io(error)) => {
+                    io_error(format!("could not write {output}"), error)
+                }
+            }
+        }
+        (Some(from @ ("json" | "compact")), Some(to @ ("json" | "compact")))
+            if source_map.is_none() =>
+        {
+            let value = match from {
+                "compact" => {
+                    let bytes = fs::read(input)
+                        .unwrap_or_else(|error| io_error(format!("could not read {input}"), error));
+                    Value::from_compact_bytes(&bytes)
+                        .unwrap_or_else(|error| invalid(format!("{input}: {error}")))
+                }
+                _ => parse_file(input),
+            };
+            let bytes = match to {
+                "compact" => value.to_compact_bytes(),
+                _ => serialize::to_string(&value, &serialize::FormatConfig::compact()).into_bytes(),
+            };
+            if let Err(error) = fs::write(output, bytes) {
+                io_error(format!("could not write {output}"), error);
+            }
+        }
+        _ => usage_error(),
+    }
+}
+
+fn merge(args: &[String]) {
+    let mut merge_patch = false;
+    let mut config = config().format.clone();
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--merge-patch" => merge_patch = true,
+            "--indent" => config.indent = Some(parse_number_arg(args.next())),
+            file => files.push(file),
+        }
+    }
+    let Some((base, overrides)) = files.split_first() else {
+        usage_error()
+    };
+
+    let mut value = parse_file(base);
+    for file in overrides {
+        let other = parse_file(file);
+        if merge_patch {
+            value.merge_patch(other);
+        } else {
+            value.merge(other);
+        }
+    }
+    println!("{}", serialize::to_string(&value, &config));
+}
+
+fn patch(args: &[String]) {
+    let mut config = config().format.clone();
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--indent" => config.indent = Some(parse_number_arg(args.next())),
+            file => files.push(file),
+        }
+    }
+    let [document, patch] = files[..] else {
+        usage_error()
+    };
+
+    let mut value = parse_file(document);
+    if let Err(error) = value.apply_patch(&parse_file(patch)) {
+        invalid(format!("{patch}: {error}"));
+    }
+    println!("{}", serialize::to_string(&value, &config));
+}
+
+fn sort(args: &[String]) {
+    let mut config = config().format.clone();
+    let mut array = "";
+    let mut dedup = false;
+    let mut operands = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--at" => array = args.next().map_or_else(|| usage_error(), String::as_str),
+            "--dedup" => dedup = true,
+            "--indent" => config.indent = Some(parse_number_arg(args.next())),
+            operand => operands.push(operand),
+        }
+    }
+    let [file, key] = operands[..] else {
+        usage_error()
+    };
+
+    let mut value = parse_file(file);
+    let result = if dedup {
+        value.dedup_array_by(array, key)
+    } else {
+        Ok(())
+    };
+    if let Err(error) = result.and_then(|_| value.sort_array_by_pointer(array, key)) {
+        invalid(format!("{file}: {error}"));
+    }
+    println!("{}", serialize::to_string(&value, &config));
+}
+
+fn stitch(args: &[String]) {
+    let mut config = config().format.clone();
+    let mut array = None;
+    let mut id = None;
+    let mut files = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--at" => array = Some(args.next().map_or_else(|| usage_error(), String::as_str)),
+            "--id" => id = Some(args.next().map_or_else(|| usage_error(), String::as_str)),
+            "--indent" => config.indent = Some(parse_number_arg(args.next())),
+            file => files.push(file),
+        }
+    }
+    let (Some(array), false) = (array, files.is_empty()) else {
+        usage_error()
+    };
+
+    let mut stitcher = Stitcher::new(array);
+    if let Some(id) = id {
+        stitcher = stitcher.dedup_by(id);
+    }
+    for file in files {
+        if let Err(error) = stitcher.add(parse_file(file)) {
+            invalid(format!("{file}: {error}"));
+        }
+    }
+    let value = stitcher.finish().expect("a page for each file");
+    println!("{}", serialize::to_string(&value, &config));
+}
+
+fn sample(args: &[String]) {
+    let mut at = "$";
+    let mut seed = 0;
+    let mut operands = Vec::<&String>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--at" => at = args.next().map_or_else(|| usage_error(), String::as_str),
+            "--seed" => seed = parse_number_arg(args.next()) as u64,
+            _ => operands.push(arg),
+        }
+    }
+    let [file, n] = operands[..] else {
+        usage_error()
+    };
+    let n = parse_number_arg(Some(n));
+    let Some(query) = query::parse_query(at) else {
+        eprintln!("error: invalid query {at}");
+        process::exit(EXIT_USAGE);
+    };
+
+    match sample::sample_array(StreamParser::new(&read_file(file)), &query, n, seed) {
+        Ok(Some(value)) => println!("{}", serialize::to_string(&value, &config().format)),
+        Ok(None) => invalid(format!("{file}: Expected an array at {at}")),
+        Err(error) => parse_error(file, &[error]),
+    }
+}
+
+fn split(args: &[String]) {
+    let mut split = None;
+    let mut operands = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => split = Some(Split::Size(parse_number_arg(args.next()))),
+            "--files" => split = Some(Split::Parts(parse_number_arg(args.next()))),
+            operand => operands.push(operand),
+        }
+    }
+    let (Some(split), [file, prefix]) = (split, &operands[..]) else {
+        usage_error()
+    };
+    if matches!(split, Split::Size(0) | Split::Parts(0)) {
+        usage_error();
+    }
+
+    let mut part = 0;
+    let result = split::split_array(&read_file(file), split, |chunk| {
+        let output = format!("{prefix}-{part:05}.json");
+        part += 1;
+        fs::write(&output, format!("{chunk}\n"))
+            .unwrap_or_else(|error| io_error(format!("could not write {output}"), error));
+        Ok(())
+    });
+    if let Err(error) = result {
+        split_error(file, error);
+    }
+}
+
+fn join(args: &[String]) {
+    let [output, files @ ..] = args else {
+        usage_error()
+    };
+    if files.is_empty() {
+        usage_error();
+    }
+
+    let mut joiner = ArrayJoiner::new(BufWriter::new(create_file(output)));
+    for file in files {
+        if let Err(error) = joiner.append(&read_file(file)) {
+            split_error(file, error);
+        }
+    }
+    let written = joiner
+        .finish()
+        .and_then(|mut out| writeln!(out).and_then(|_| out.flush()));
+    if let Err(error) = written {
+        io_error(format!("could not write {output}"), error);
+    }
+}
+
+fn split_error(file: &str, error: SplitError) -> ! {
+    match error {
+        SplitError::Parse(error) => parse_error(file, &[error]),
+        SplitError::NotAnArray => invalid(format!("{file}: {error}")),
+        SplitError::Io(error) => io_error(format!("could not write parts of {file}"), error),
+    }
+}
+
+fn get(args: &[String]) {
+    let [file, query] = args else { usage_error() };
+    let Some(query) = query::parse_query(query) else {
+        eprintln!("error: invalid query {query}");
+        process::exit(EXIT_USAGE);
+    };
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut failed = false;
+    let mut select =
+        |json: &str, first_line: usize| match query::select(StreamParser::new(json), &query) {
+            Ok(value) => {
+                let value = value.unwrap_or_default();
+                let line = serialize::to_string(&value, &serialize::FormatConfig::compact());
+                if let Err(error) = writeln!(out, "{line}") {
+                    io_error("could not write standard output".to_string(), error);
+                }
+            }
+            Err(error) => {
+                if let Some(line) = diagnostic(file, &error.offset_lines(first_line - 1)) {
+                    eprintln!("{line}");
+                }
+                failed = true;
+            }
+        };
+
+    // Standard input is read as NDJSON, one document per line, so memory use does not grow
+    // with the input.
+    if file == "-" {
+        for (i, line) in io::stdin().lock().lines().enumerate() {
+            let line = line.unwrap_or_else(|error| {
+                io_error("could not read standard input".to_string(), error)
+            });
+            if !line.trim().is_empty() {
+                select(&line, i + 1);
+            }
+        }
+    } else {
+        select(&read_file(file), 1);
+    }
+
+    if let Err(error) = out.flush() {
+        io_error("could not write standard output".to_string(), error);
+    }
+    drop(out);
+    if failed {
+        process::exit(EXIT_INVALID);
+    }
+}
+
+/// Values longer than this are cut short in `paths --values`.
+const PREVIEW_LEN: usize = 40;
+
+fn paths(args: &[String]) {
+    let mut types = false;
+    let mut values = false;
+    let mut operands = Vec::<&str>::new();
+    for arg in args {
+        match arg.as_str() {
+            "--types" => types = true,
+            "--values" => values = true,
+            operand => operands.push(operand),
+        }
+    }
+    let [file] = operands[..] else { usage_error() };
+
+    let value = parse_file(file);
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    for (pointer, leaf) in value.leaves() {
+        let mut line = pointer;
+        if types {
+            line.push('\t');
+            line.push_str(type_name(leaf));
+        }
+        if values {
+            let json = serialize::to_string(leaf, &serialize::FormatConfig::compact());
+            line.push('\t');
+            match json.char_indices().nth(PREVIEW_LEN) {
+                Some((end, _)) => {
+                    line.push_str(&json[..end]);
+                    line.push('…');
+                }
+                None => line.push_str(&json),
+            }
+        }
+        if let Err(error) = writeln!(out, "{line}") {
+            io_error("could not write standard output".to_string(), error);
+        }
+    }
+    if let Err(error) = out.flush() {
+        io_error("could not write standard output".to_string(), error);
+    }
+}
+
+fn keys(args: &[String]) {
+    let mut at = "";
+    let mut operands = Vec::<&str>::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--at" => at = args.next().map_or_else(|| usage_error(), String::as_str),
+            operand => operands.push(operand),
+        }
+    }
+    let [file] = operands[..] else { usage_error() };
+
+    match parse_file(file).pointer(at) {
+        Some(Value::Object(members)) => {
+            for key in members.keys() {
+                println!("{key}");
+            }
+        }
+        Some(Value::Array(elements)) => {
+            for i in 0..elements.len() {
+                println!("{i}");
+            }
+        }
+        Some(_) => invalid(format!("{file}: Expected an object or array at {at}")),
+        None => invalid(format!("{file}: Nothing at {at}")),
+    }
+}
+
+#[cfg(not(feature = "explore"))]
+fn explore(_: &[String]) {
+    eprintln!("error: explore needs json-parser built with --features explore");
+    process::exit(EXIT_USAGE);
+}
+
+#[cfg(feature = "explore")]
+fn explore(args: &[String]) {
+    use crossterm::{
+        cursor,
+        event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+        execute, queue,
+        style::{Attribute, Print, SetAttribute},
+        terminal::{self, ClearType},
+    };
+    use json_parser::explore::Explorer;
+
+    /// Puts the terminal back however the explorer exits.
+    struct Screen;
+
+    impl Drop for Screen {
+        fn drop(&mut self) {
+            let _ = execute!(io::stdout(), cursor::Show, terminal::LeaveAlternateScreen);
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+
+    let [file] = args else { usage_error() };
+    let json = read_file(file);
+    let mut explorer = match parsing::Parser::parse(&json) {
+        Ok(value) => Explorer::new(&value),
+        Err(errors) => parse_error(file, &errors),
+    };
+    let index = DocumentIndex::new(&json).unwrap_or_else(|error| parse_error(file, &[error]));
+
+    let mut run = || -> io::Result<()> {
+        terminal::enable_raw_mode()?;
+        let _screen = Screen;
+        let mut out = BufWriter::new(io::stdout());
+        execute!(out, terminal::EnterAlternateScreen, cursor::Hide)?;
+
+        let mut top = 0;
+        // The search being typed, and the last one made, which `n` repeats.
+        let mut search = None::<String>;
+        let mut last_search = String::new();
+        let mut status = String::new();
+        loop {
+            let (width, height) = terminal::size()?;
+            let (width, height) = (usize::from(width), usize::from(height.max(2)) - 1);
+            let selected = explorer.selected_row();
+            top = top.min(selected).max((selected + 1).saturating_sub(height));
+
+            queue!(out, terminal::Clear(ClearType::All))?;
+            for (row, (node, expanded)) in explorer.rows().enumerate().skip(top).take(height) {
+                let marker = match (node.is_container(), expanded) {
+                    (false, _) => "  ",
+                    (true, true) => "▾ ",
+                    (true, false) => "▸ ",
+                };
+                let label = match node.label() {
+                    "" => String::new(),
+                    label => format!("{label}: "),
+                };
+                let line = format!(
+                    "{}{marker}{label}{}",
+                    "  ".repeat(node.depth()),
+                    node.summary()
+                );
+                let line = line.chars().take(width).collect::<String>();
+                queue!(out, cursor::MoveTo(0, (row - top) as u16))?;
+                if row == selected {
+                    queue!(
+                        out,
+                        SetAttribute(Attribute::Reverse),
+                        Print(line),
+                        SetAttribute(Attribute::Reset)
+                    )?;
+                } else {
+                    queue!(out, Print(line))?;
+                }
+            }
+            let pointer = explorer.selected().pointer();
+            let footer = match &search {
+                Some(text) => format!("/{text}"),
+                None if !status.is_empty() => status.clone(),
+                None => {
+                    let line = index.lines_of(pointer).map_or(0, |lines| lines.start.0);
+                    format!("{pointer} (line {line})  ↑↓ move  →← expand  / search  n next  c copy  q quit")
+                }
+            };
+            let footer = footer.chars().take(width).collect::<String>();
+            queue!(out, cursor::MoveTo(0, height as u16), Print(footer))?;
+            out.flush()?;
+
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+                return Ok(());
+            }
+            if let Some(text) = &mut search {
+                match key.code {
+                    KeyCode::Enter => {
+                        if !explorer.search(text) {
+                            status = format!("{text} not found");
+                        }
+                        last_search = search.take().unwrap_or_default();
+                    }
+                    KeyCode::Esc => search = None,
+                    KeyCode::Backspace => {
+                        text.pop();
+                    }
+                    KeyCode::Char(c) => text.push(c),
+                    _ => {}
+                }
+                continue;
+            }
+
+            status.clear();
+            match key.code {
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                KeyCode::Up | KeyCode::Char('k') => explorer.move_by(-1),
+                KeyCode::Down | KeyCode::Char('j') => explorer.move_by(1),
+                KeyCode::PageUp => explorer.move_by(-(height as isize)),
+                KeyCode::PageDown => explorer.move_by(height as isize),
+                KeyCode::Home => explorer.move_by(isize::MIN),
+                KeyCode::End => explorer.move_by(isize::MAX),
+                KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => explorer.expand(),
+                KeyCode::Left | KeyCode::Char('h') => explorer.collapse(),
+                KeyCode::Char('/') => search = Some(String::new()),
+                KeyCode::Char('n') if !last_search.is_empty() && !explorer.search(&last_search) => {
+                    status = format!("{last_search} not found");
+                }
+                KeyCode::Char('c') => {
+                    // OSC 52 asks the terminal to put the text on the clipboard, which also
+                    // works over SSH.
+                    let pointer = explorer.selected().pointer();
+                    queue!(
+                        out,
+                        Print(format!("\x1b]52;c;{}\x07", base64(pointer.as_bytes())))
+                    )?;
+                    status = format!("Copied {pointer}");
+                }
+                _ => {}
+            }
+        }
+    };
+    if let Err(error) = run() {
+        io_error("could not use the terminal".to_string(), error);
+    }
+}
+
+#[cfg(feature = "explore")]
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |group, (i, b)| group | u32::from(*b) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => encoded.push('='),
+            }
+        }
+    }
+    encoded
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        #[cfg(feature = "bytes")]
+        Value::Bytes(_) => "bytes",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn profile(args: &[String]) {
+    let [file] = args else { usage_error() };
+
+    let mut profile = Profile::new();
+    let mut failed = false;
+    let mut add =
+        |line: &str, number: usize| match parsing::Parser::parse_with_options(line, config().parse)
+        {
+            Ok((record, _)) => {
+                for drift in profile.add(&record) {
+                    println!("{file}:{number}: {drift}");
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    if let Some(line) = diagnostic(file, &error.offset_lines(number - 1)) {
+                        eprintln!("{line}");
+                    }
+                }
+                failed = true;
+            }
+        };
+    if file == "-" {
+        for (i, line) in io::stdin().lock().lines().enumerate() {
+            let line = line.unwrap_or_else(|error| {
+                io_error("could not read standard input".to_string(), error)
+            });
+            if !line.trim().is_empty() {
+                add(&line, i + 1);
+            }
+        }
+    } else {
+        for (i, line) in read_file(file).lines().enumerate() {
+            if !line.trim().is_empty() {
+                add(line, i + 1);
+            }
+        }
+    }
+
+    let records = profile.records();
+    let percent = |count: usize| 100.0 * count as f64 / records.max(1) as f64;
+    let width = profile
+        .fields()
+        .map(|(path, _)| path.len())
+        .max()
+        .unwrap_or(0);
+    println!("{records} records");
+    println!("{:width$}  {:>7}  {:>7}  types", "path", "present", "null");
+    for (path, stats) in profile.fields() {
+        let kinds: Vec<String> = stats
+            .kinds()
+            .map(|(kind, count)| format!("{kind} {count}"))
+            .collect();
+        println!(
+            "{path:width$}  {:>6.1}%  {:>6.1}%  {}",
+            percent(stats.present()),
+            100.0 * stats.nulls() as f64 / stats.present() as f64,
+            kinds.join(", ")
+        );
+    }
+    if failed {
+        process::exit(EXIT_INVALID);
+    }
+}
+
+fn create_file(file: &str) -> fs::File {
+    match fs::File::create(file) {
+        Ok(file) => file,
+        Err(error) => io_error(format!("could not write {file}"), error),
+    }
+}
+
+fn read_file(file: &str) -> String {
+    match fs::read_to_string(file) {
+        Ok(contents) => contents,
+        Err(error) => io_error(format!("could not read {file}"), error),
+    }
+}
+
+fn parse_file(file: &str) -> Value {
+    parse_file_with(file, config().parse)
+}
+
+fn parse_file_with(file: &str, options: parsing::ParseOptions) -> Value {
+    match parsing::Parser::parse_with_options(&read_file(file), options) {
+        Ok((value, _)) => value,
+        Err(errors) => parse_error(file, &errors),
+    }
+}
+
+fn parse_error(file: &str, errors: &[Error]) -> ! {
+    for line in errors.iter().filter_map(|error| diagnostic(file, error)) {
+        eprintln!("{line}");
+    }
+    process::exit(EXIT_INVALID);
+}
+
+/// Reports invalid input that has no position, such as a failed patch operation.
+fn invalid(message: impl Display) -> ! {
+    if output() != Output::Quiet {
+        eprintln!("{message}");
+    }
+    process::exit(EXIT_INVALID);
+}
+
+fn io_error(context: String, error: io::Error) -> ! {
+    eprintln!("error: {context}: {error}");
+    process::exit(EXIT_IO);
+}
+
+fn parse_number_arg(arg: Option<&String>) -> usize {
+    match arg.map(|a| a.parse::<usize>()) {
+        Some(Ok(n)) => n,
+        _ => usage_error(),
+    }
+}
+
+fn usage_error() -> ! {
+    eprintln!("{USAGE}");
+    process::exit(EXIT_USAGE);
 }
 
 fn main() {
-    read_json("tests/canada.json");
-    read_json("tests/twitter.json");
+    let mut args = env::args().skip(1).collect::<Vec<String>>();
+    while let Some(flag @ ("--quiet" | "--porcelain")) = args.first().map(String::as_str) {
+        let output = match flag {
+            "--quiet" => Output::Quiet,
+            _ => Output::Porcelain,
+        };
+        if OUTPUT.set(output).is_err() {
+            usage_error();
+        }
+        args.remove(0);
+    }
+    match args.first().map(String::as_str) {
+        None => bench(&[]),
+        Some("bench") => bench(&args[1..]),
+        Some("fetch-corpus") => fetch_corpus(&args[1..]),
+        Some("validate") => validate(&args[1..]),
+        Some("check") => check(&args[1..]),
+        Some("fmt") => fmt(&args[1..]),
+        Some("convert") => convert(&args[1..]),
+        Some("merge") => merge(&args[1..]),
+        Some("patch") => patch(&args[1..]),
+        Some("sort") => sort(&args[1..]),
+        Some("stitch") => stitch(&args[1..]),
+        Some("sample") => sample(&args[1..]),
+        Some("split") => split(&args[1..]),
+        Some("join") => join(&args[1..]),
+        Some("get") => get(&args[1..]),
+        Some("paths") => paths(&args[1..]),
+        Some("keys") => keys(&args[1..]),
+        Some("explain") => explain(&args[1..]),
+        Some("explore") => explore(&args[1..]),
+        Some("profile") => profile(&args[1..]),
+        Some(_) => usage_error(),
+    }
 }
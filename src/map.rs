@@ -0,0 +1,321 @@
+use std::ops::Index;
+
+use crate::parsing::Value;
+
+/// The type of object keys: `String` by default. The `box-keys` feature stores them as
+/// `Box<str>`, a word smaller per key, and `arc-keys` as `Arc<str>`, which is cheap to clone
+/// and share across threads.
+#[cfg(not(any(feature = "box-keys", feature = "arc-keys")))]
+pub type Key = String;
+#[cfg(all(feature = "box-keys", not(feature = "arc-keys")))]
+pub type Key = Box<str>;
+#[cfg(feature = "arc-keys")]
+pub type Key = std::sync::Arc<str>;
+
+/// The table behind [`Map`]: a [`SmallMap`], which keeps small objects in a vector and larger
+/// ones in an `IndexMap`, by default. The `btree-map` feature uses a `BTreeMap` instead, which
+/// stores no hashes or index table but keeps members sorted by key rather than in the order
+/// they were inserted.
+///
+/// [`SmallMap`]: crate::small_map::SmallMap
+#[cfg(not(feature = "btree-map"))]
+pub type Members = crate::small_map::SmallMap<Key, Value>;
+#[cfg(feature = "btree-map")]
+pub type Members = std::collections::BTreeMap<Key, Value>;
+
+#[cfg(not(feature = "btree-map"))]
+pub type Iter<'a> = crate::small_map::Iter<'a, Key, Value>;
+#[cfg(not(feature = "btree-map"))]
+pub type IntoIter = crate::small_map::IntoIter<Key, Value>;
+#[cfg(feature = "btree-map")]
+pub type Iter<'a> = std::collections::btree_map::Iter<'a, Key, Value>;
+#[cfg(feature = "btree-map")]
+pub type IntoIter = std::collections::btree_map::IntoIter<Key, Value>;
+
+/// The members of a JSON object.
+///
+/// Iteration follows insertion order, which for parsed documents is the order the keys appear
+/// in the source. Inserting an existing key replaces its value but keeps its position, and a
+/// duplicate key in a document keeps the position of its first occurrence with the value of
+/// its last. With the `btree-map` feature, iteration is by key instead. Equality ignores
+/// order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Map {
+    members: Members,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Map::default()
+    }
+
+    #[cfg(not(feature = "btree-map"))]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Map {
+            members: Members::with_capacity(capacity),
+        }
+    }
+
+    /// A `BTreeMap` allocates its nodes as it grows, so there is nothing to reserve.
+    #[cfg(feature = "btree-map")]
+    pub fn with_capacity(_capacity: usize) -> Self {
+        Map::new()
+    }
+
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    #[cfg(not(feature = "btree-map"))]
+    pub fn capacity(&self) -> usize {
+        self.members.capacity()
+    }
+
+    #[cfg(feature = "btree-map")]
+    pub fn capacity(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Whether the members are in a hash table rather than a vector.
+    #[cfg(not(feature = "btree-map"))]
+    pub(crate) fn is_indexed(&self) -> bool {
+        self.members.is_indexed()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.members.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.members.get_mut(key)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.members.contains_key(key)
+    }
+
+    pub fn insert(&mut self, key: impl Into<Key>, value: Value) -> Option<Value> {
+        self.members.insert(key.into(), value)
+    }
+
+    /// Removes `key`, shifting the members after it so the order of the rest is kept.
+    #[cfg(not(feature = "btree-map"))]
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.members.shift_remove(key)
+    }
+
+    #[cfg(feature = "btree-map")]
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        self.members.remove(key)
+    }
+
+    /// Keeps only the members for which `keep` returns true, in their order.
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &mut Value) -> bool) {
+        self.members.retain(|key, value| keep(key, value));
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &str> + ExactSizeIterator {
+        self.members.keys().map(|key| &**key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &Value> + ExactSizeIterator {
+        self.members.values()
+    }
+
+    pub fn values_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut Value> + ExactSizeIterator {
+        self.members.values_mut()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        self.members.iter()
+    }
+
+    pub fn iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (&str, &mut Value)> + ExactSizeIterator {
+        self.members.iter_mut().map(|(key, value)| (&**key, value))
+    }
+
+    /// Reorders the members by key, comparing code points.
+    #[cfg(not(feature = "btree-map"))]
+    pub fn sort_keys(&mut self) {
+        self.members.sort_keys();
+    }
+
+    /// The members are always sorted by key.
+    #[cfg(feature = "btree-map")]
+    pub fn sort_keys(&mut self) {}
+}
+
+impl Index<&str> for Map {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        &self.members[key]
+    }
+}
+
+impl<K: Into<Key>> FromIterator<(K, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let mut map = Map::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<Key>> Extend<(K, Value)> for Map {
+    fn extend<I: IntoIterator<Item = (K, Value)>>(&mut self, iter: I) {
+        self.members
+            .extend(iter.into_iter().map(|(key, value)| (key.into(), value)));
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Key, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        self.members.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a Key, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.members.iter()
+    }
+}
+
+/// The candidate closest to `key` by edit distance, for suggesting what a misspelled key
+/// was meant to be. A candidate further than a third of the key's length, or equal to it, is
+/// not close.
+pub fn closest_key<'a>(
+    key: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let max = (key.chars().count() / 3).max(1);
+    candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|&(distance, _)| distance > 0 && distance <= max)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The Levenshtein distance between `a` and `b`, counting transposed characters as one edit
+/// since they are the most common typo in keys.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<char>>();
+    let b = b.chars().collect::<Vec<char>>();
+    let mut rows = vec![(0..=b.len()).collect::<Vec<usize>>()];
+    for i in 1..=a.len() {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            row[j] = (rows[i - 1][j] + 1)
+                .min(row[j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = row[j].min(rows[i - 2][j - 2] + 1);
+            }
+        }
+        rows.push(row);
+    }
+    rows[a.len()][b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn members(json: &str) -> Map {
+        match Parser::parse(json).unwrap() {
+            Value::Object(members) => members,
+            value => panic!("{value:?} is not an object"),
+        }
+    }
+
+    fn sorted_keys(members: &Map) -> Vec<&str> {
+        let mut keys = members.keys().collect::<Vec<&str>>();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn insert_and_remove_members() {
+        let mut members = members(r#"{"b": 1, "a": 2, "c": 3, "a": 4}"#);
+        assert_eq!(3, members.len());
+        assert_eq!(vec!["a", "b", "c"], sorted_keys(&members));
+        assert_eq!(Value::Number(4.0), members["a"]);
+
+        assert_eq!(Some(Value::Number(1.0)), members.remove("b"));
+        assert_eq!(None, members.remove("b"));
+        members.insert("d".to_string(), Value::Null);
+        assert_eq!(vec!["a", "c", "d"], sorted_keys(&members));
+
+        let reversed = members.clone().into_iter().rev().collect::<Map>();
+        assert_eq!(members, reversed);
+
+        members.insert("0".to_string(), Value::Null);
+        members.sort_keys();
+        assert_eq!(
+            vec!["0", "a", "c", "d"],
+            members.keys().collect::<Vec<&str>>()
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "btree-map"))]
+    fn iterate_in_document_order() {
+        let mut members = members(r#"{"b": 1, "a": 2, "c": 3, "a": 4}"#);
+        assert_eq!(vec!["b", "a", "c"], members.keys().collect::<Vec<&str>>());
+
+        members.remove("b");
+        members.insert("d".to_string(), Value::Null);
+        members.insert("a".to_string(), Value::Null);
+        assert_eq!(vec!["a", "c", "d"], members.keys().collect::<Vec<&str>>());
+    }
+
+    #[test]
+    #[cfg(feature = "btree-map")]
+    fn iterate_sorted_by_key() {
+        let mut members = members(r#"{"b": 1, "a": 2, "c": 3, "a": 4}"#);
+        assert_eq!(vec!["a", "b", "c"], members.keys().collect::<Vec<&str>>());
+
+        assert_eq!(Some(Value::Number(4.0)), members.remove("a"));
+        members.insert("0".to_string(), Value::Null);
+        members.insert("bb".to_string(), Value::Null);
+        assert_eq!(
+            vec!["0", "b", "bb", "c"],
+            members.keys().collect::<Vec<&str>>()
+        );
+        assert_eq!(
+            vec![
+                &Value::Null,
+                &Value::Number(1.0),
+                &Value::Null,
+                &Value::Number(3.0)
+            ],
+            members.values().collect::<Vec<&Value>>()
+        );
+    }
+
+    #[test]
+    fn suggest_closest_key() {
+        let keys = ["user", "users", "id", "location"];
+        assert_eq!(Some("user"), closest_key("usre", keys));
+        assert_eq!(Some("location"), closest_key("loaction", keys));
+        assert_eq!(Some("id"), closest_key("Id", keys));
+        assert_eq!(None, closest_key("name", keys));
+        assert_eq!(None, closest_key("user", ["user"]));
+    }
+}
@@ -0,0 +1,234 @@
+use std::fmt::Write;
+
+use crate::{
+    limits::{self, LimitError, Limits},
+    map::Map,
+    parsing::{escape_key, Value},
+};
+
+impl Value {
+    /// Deep-merges `other` into `self`. Members of two objects are merged recursively and any
+    /// other value in `other`, including `null`, replaces the one in `self`.
+    pub fn merge(&mut self, other: Value) {
+        match (self, other) {
+            (Value::Object(members), Value::Object(others)) => {
+                for (key, other) in others {
+                    match members.get_mut(&key) {
+                        Some(member) => member.merge(other),
+                        None => {
+                            members.insert(key, other);
+                        }
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    /// Like [`Value::merge`], but fails without changing `self` if what `other` adds or
+    /// replaces would go beyond `limits`.
+    pub fn merge_with_limits(&mut self, other: Value, limits: &Limits) -> Result<(), LimitError> {
+        check_merge(self, &other, 0, &mut String::new(), limits)?;
+        self.merge(other);
+        Ok(())
+    }
+
+    /// Like [`Value::merge_patch`], but fails without changing `self` if what `patch` adds or
+    /// replaces would go beyond `limits`.
+    pub fn merge_patch_with_limits(
+        &mut self,
+        patch: Value,
+        limits: &Limits,
+    ) -> Result<(), LimitError> {
+        check_merge_patch(Some(self), &patch, 0, &mut String::new(), limits)?;
+        self.merge_patch(patch);
+        Ok(())
+    }
+
+    /// Applies an RFC 7386 JSON Merge Patch. Unlike [`Value::merge`], a `null` member in the
+    /// patch removes the member from `self`.
+    pub fn merge_patch(&mut self, patch: Value) {
+        let Value::Object(patches) = patch else {
+            *self = patch;
+            return;
+        };
+        if !matches!(self, Value::Object(_)) {
+            *self = Value::Object(Default::default());
+        }
+        let Value::Object(members) = self else {
+            unreachable!()
+        };
+
+        for (key, patch) in patches {
+            match (members.get_mut(&key), patch) {
+                (_, Value::Null) => {
+                    members.remove(&key);
+                }
+                (Some(member), patch) => member.merge_patch(patch),
+                (None, patch) => {
+                    let mut member = Value::Null;
+                    member.merge_patch(patch);
+                    members.insert(key, member);
+                }
+            }
+        }
+    }
+}
+
+/// Checks what merging `other` into `this`, at `path` inside `depth` containers, would give.
+fn check_merge(
+    this: &Value,
+    other: &Value,
+    depth: usize,
+    path: &mut String,
+    limits: &Limits,
+) -> Result<(), LimitError> {
+    let (Value::Object(members), Value::Object(others)) = (this, other) else {
+        return limits::check(other, depth, path, limits);
+    };
+    let added = others
+        .keys()
+        .filter(|key| !members.contains_key(key))
+        .count();
+    limits.check_container(depth + 1, members.len() + added, path)?;
+
+    let end = path.len();
+    for (key, other) in others {
+        write!(path, "/{}", escape_key(key)).unwrap();
+        match members.get(key) {
+            Some(member) => check_merge(member, other, depth + 1, path, limits)?,
+            None => limits::check(other, depth + 1, path, limits)?,
+        }
+        path.truncate(end);
+    }
+    Ok(())
+}
+
+/// Like [`check_merge`] for a merge patch, where `this` is `None` for a member the patch adds.
+fn check_merge_patch(
+    this: Option<&Value>,
+    patch: &Value,
+    depth: usize,
+    path: &mut String,
+    limits: &Limits,
+) -> Result<(), LimitError> {
+    let Value::Object(patches) = patch else {
+        return limits::check(patch, depth, path, limits);
+    };
+    let members = match this {
+        Some(Value::Object(members)) => Some(members),
+        _ => None,
+    };
+    let existing = |key: &str| members.and_then(|members| members.get(key));
+    let mut len = members.map_or(0, Map::len);
+    for (key, patch) in patches {
+        match (existing(key), patch) {
+            (Some(_), Value::Null) => len -= 1,
+            (None, Value::Null) | (Some(_), _) => {}
+            (None, _) => len += 1,
+        }
+    }
+    limits.check_container(depth + 1, len, path)?;
+
+    let end = path.len();
+    for (key, patch) in patches {
+        if !matches!(patch, Value::Null) {
+            write!(path, "/{}", escape_key(key)).unwrap();
+            check_merge_patch(existing(key), patch, depth + 1, path, limits)?;
+            path.truncate(end);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{errors::ErrorCode, parsing::Parser};
+
+    fn merged(base: &str, other: &str, patch: bool) -> String {
+        let mut value = Parser::parse(base).unwrap();
+        let other = Parser::parse(other).unwrap();
+        if patch {
+            value.merge_patch(other);
+        } else {
+            value.merge(other);
+        }
+        value.to_string()
+    }
+
+    #[test]
+    fn deep_merge_objects() {
+        assert_eq!(
+            r#"{"a":{"b":1,"c":3,"e":[4]},"d":null,"f":true}"#,
+            merged(
+                r#"{"a": {"b": 1, "c": 2, "e": [1, 2]}, "d": 0}"#,
+                r#"{"a": {"c": 3, "e": [4]}, "d": null, "f": true}"#,
+                false
+            )
+        );
+        assert_eq!("[1]", merged(r#"{"a": 1}"#, "[1]", false));
+    }
+
+    #[test]
+    fn apply_merge_patch() {
+        // Examples from RFC 7386, appendix A.
+        let cases = [
+            (r#"{"a":"b"}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (r#"{"a":"b"}"#, r#"{"a":null}"#, "{}"),
+            (r#"{"a":"b","b":"c"}"#, r#"{"a":null}"#, r#"{"b":"c"}"#),
+            (r#"{"a":["b"]}"#, r#"{"a":"c"}"#, r#"{"a":"c"}"#),
+            (
+                r#"{"a":{"b":"c"}}"#,
+                r#"{"a":{"b":"d","c":null}}"#,
+                r#"{"a":{"b":"d"}}"#,
+            ),
+            (r#"["a","b"]"#, r#"["c","d"]"#, r#"["c","d"]"#),
+            (r#"{"a":"foo"}"#, "null", "null"),
+            (r#"{"e":null}"#, r#"{"a":1}"#, r#"{"e":null,"a":1}"#),
+            (r#"["a","b"]"#, r#"{"a":"b"}"#, r#"{"a":"b"}"#),
+            ("{}", r#"{"a":{"bb":{"ccc":null}}}"#, r#"{"a":{"bb":{}}}"#),
+        ];
+        for (base, patch, expected) in cases {
+            let expected = Parser::parse(expected).unwrap();
+            let actual = Parser::parse(&merged(base, patch, true)).unwrap();
+            assert_eq!(expected, actual, "{base} + {patch}");
+        }
+    }
+
+    #[test]
+    fn merge_within_limits() {
+        let limits = Limits {
+            max_depth: Some(2),
+            max_len: Some(2),
+        };
+        let parse = |json: &str| Parser::parse(json).unwrap();
+        let mut value = parse(r#"{"a": {"b": 1}, "c": 2}"#);
+
+        value
+            .merge_with_limits(parse(r#"{"a": {"d": 3}}"#), &limits)
+            .unwrap();
+        let error = value
+            .merge_with_limits(parse(r#"{"a": {"e": 4}}"#), &limits)
+            .unwrap_err();
+        assert_eq!(
+            (ErrorCode::ContainerTooLarge, "/a"),
+            (error.code(), error.path())
+        );
+        let error = value
+            .merge_with_limits(parse(r#"{"c": {"f": [1]}}"#), &limits)
+            .unwrap_err();
+        assert_eq!(
+            (ErrorCode::NestingTooDeep, "/c/f"),
+            (error.code(), error.path())
+        );
+
+        value
+            .merge_patch_with_limits(parse(r#"{"a": {"b": null, "e": 4}}"#), &limits)
+            .unwrap();
+        assert!(value
+            .merge_patch_with_limits(parse(r#"{"g": 5}"#), &limits)
+            .is_err());
+        assert_eq!(r#"{"a":{"d":3,"e":4},"c":2}"#, value.to_string());
+    }
+}
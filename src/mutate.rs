@@ -0,0 +1,137 @@
+use crate::errors::ErrorCode;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MutationKind {
+    DeleteComma,
+    SwapBracket,
+    TruncateString,
+    InsertControlChar,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mutation {
+    pub kind: MutationKind,
+    pub offset: usize,
+    pub document: String,
+    pub expected: ErrorCode,
+}
+
+/// Produces one corrupted variant of `document` per mutation site. Each variant carries the
+/// error the parser is expected to report for it; compare with [`ErrorCode::id`] since
+/// recovery may attribute the mistake to an enclosing container.
+pub fn mutations(document: &str) -> Vec<Mutation> {
+    let mut mutations = Vec::<Mutation>::new();
+    let mut containers = Vec::<char>::new();
+    let mut is_in_quotes = false;
+    let mut chars = document.char_indices();
+
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '"' if is_in_quotes => is_in_quotes = false,
+            '\\' if is_in_quotes => {
+                chars.next();
+            }
+            '"' => {
+                is_in_quotes = true;
+                let truncate_at = document[offset + 1..]
+                    .chars()
+                    .next()
+                    .filter(|c| *c != '"' && *c != '\\')
+                    .map_or(offset + 1, |c| offset + 1 + c.len_utf8());
+                mutations.push(Mutation {
+                    kind: MutationKind::TruncateString,
+                    offset,
+                    document: document[..truncate_at].to_string(),
+                    expected: ErrorCode::ExpectedDoubleQuote,
+                });
+            }
+            _ if is_in_quotes => {}
+            '[' => containers.push(']'),
+            '{' => containers.push('}'),
+            ']' | '}' => {
+                containers.pop();
+                let swapped = if c == ']' { '}' } else { ']' };
+                mutations.push(Mutation {
+                    kind: MutationKind::SwapBracket,
+                    offset,
+                    document: replace_at(document, offset, c, &swapped.to_string()),
+                    expected: ErrorCode::ExpectedCommaOrEndWhileParsing(c),
+                });
+            }
+            ',' => {
+                let end = *containers.last().unwrap_or(&']');
+                mutations.push(Mutation {
+                    kind: MutationKind::DeleteComma,
+                    offset,
+                    // Replaced rather than removed so `1,2` doesn't turn into the valid `12`.
+                    document: replace_at(document, offset, c, " "),
+                    expected: ErrorCode::ExpectedCommaOrEndWhileParsing(end),
+                });
+                mutations.push(insert_control_char(document, offset + 1));
+            }
+            ':' => mutations.push(insert_control_char(document, offset + 1)),
+            _ => {}
+        }
+    }
+
+    mutations
+}
+
+fn insert_control_char(document: &str, offset: usize) -> Mutation {
+    Mutation {
+        kind: MutationKind::InsertControlChar,
+        offset,
+        document: format!("{}\u{1}{}", &document[..offset], &document[offset..]),
+        expected: ErrorCode::ExpectedToken,
+    }
+}
+
+fn replace_at(document: &str, offset: usize, c: char, replacement: &str) -> String {
+    format!(
+        "{}{replacement}{}",
+        &document[..offset],
+        &document[offset + c.len_utf8()..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    const DOCUMENT: &str = r#"{
+        "statuses": [
+            {"id": 1, "text": "hi \"there\"", "coordinates": [-65.6, 43.4]},
+            {"id": 2, "user": {"name": "é", "tags": []}}
+        ],
+        "count": 2
+    }"#;
+
+    #[test]
+    fn generate_every_kind() {
+        let mutations = mutations(DOCUMENT);
+        let count = |kind| mutations.iter().filter(|m| m.kind == kind).count();
+
+        assert_eq!(7, count(MutationKind::DeleteComma));
+        assert_eq!(7, count(MutationKind::SwapBracket));
+        assert_eq!(11, count(MutationKind::TruncateString));
+        assert_eq!(7 + 9, count(MutationKind::InsertControlChar));
+        assert_eq!(Ok(()), Parser::parse(DOCUMENT).map(|_| ()));
+    }
+
+    #[test]
+    fn every_mutation_reports_its_expected_error() {
+        for mutation in mutations(DOCUMENT) {
+            let errors = match Parser::parse(&mutation.document) {
+                Ok(_) => panic!("{mutation:?} should not parse"),
+                Err(errors) => errors,
+            };
+            assert!(
+                errors
+                    .iter()
+                    .any(|e| e.code().id() == mutation.expected.id()),
+                "{mutation:?} reported {errors:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    fs::File,
+    io,
+    io::{BufWriter, Write as _},
+    path::{Path, PathBuf},
+};
+
+#[cfg(feature = "gzip")]
+use flate2::{write::GzEncoder, Compression};
+
+use crate::{
+    parsing::Value,
+    serialize::{self, FormatConfig},
+};
+
+/// How [`NdjsonWriter::create`] lays out its files.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct FileOptions {
+    /// Starts a new file once the current one would grow past this many bytes, counted before
+    /// compression. A line is never split, so a line longer than this gets a file to itself.
+    /// Files are numbered from 0 before the extension, as in `events.0.ndjson`.
+    pub max_bytes: Option<u64>,
+    /// Compresses each file with gzip and adds `.gz` to its name.
+    #[cfg(feature = "gzip")]
+    pub gzip: bool,
+}
+
+/// A file [`NdjsonWriter::create`] writes to.
+pub enum Part {
+    Plain(BufWriter<File>),
+    #[cfg(feature = "gzip")]
+    Gzip(GzEncoder<BufWriter<File>>),
+}
+
+impl Part {
+    /// Flushes the file and, for gzip, writes the end of the stream. Dropping a part does the
+    /// same but ignores errors.
+    pub fn finish(self) -> io::Result<()> {
+        match self {
+            Part::Plain(mut out) => out.flush(),
+            #[cfg(feature = "gzip")]
+            Part::Gzip(out) => out.finish()?.flush(),
+        }
+    }
+}
+
+impl io::Write for Part {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Part::Plain(out) => out.write(buf),
+            #[cfg(feature = "gzip")]
+            Part::Gzip(out) => out.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Part::Plain(out) => out.flush(),
+            #[cfg(feature = "gzip")]
+            Part::Gzip(out) => out.flush(),
+        }
+    }
+}
+
+struct Rotation<W> {
+    max_bytes: u64,
+    parts: usize,
+    open: Box<dyn FnMut(usize) -> io::Result<W>>,
+    finish: fn(W) -> io::Result<()>,
+}
+
+/// Writes values as newline-delimited JSON, one compact value per line.
+pub struct NdjsonWriter<W: io::Write> {
+    out: W,
+    config: FormatConfig,
+    /// Bytes written to `out` since it was opened.
+    written: u64,
+    rotation: Option<Rotation<W>>,
+}
+
+impl<W: io::Write> NdjsonWriter<W> {
+    pub fn new(out: W) -> Self {
+        NdjsonWriter {
+            out,
+            config: FormatConfig::compact(),
+            written: 0,
+            rotation: None,
+        }
+    }
+
+    pub fn write(&mut self, value: &Value) -> io::Result<()> {
+        let mut line = serialize::to_string(value, &self.config);
+        line.push('\n');
+        let len = line.len() as u64;
+
+        if let Some(rotation) = &mut self.rotation {
+            if self.written > 0 && self.written + len > rotation.max_bytes {
+                let next = (rotation.open)(rotation.parts)?;
+                rotation.parts += 1;
+                (rotation.finish)(std::mem::replace(&mut self.out, next))?;
+                self.written = 0;
+            }
+        }
+        self.out.write_all(line.as_bytes())?;
+        self.written += len;
+        Ok(())
+    }
+
+    /// Flushes and returns the current output. For [`NdjsonWriter::create`], this is the last
+    /// file, which [`Part::finish`] completes.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.out.flush()?;
+        Ok(self.out)
+    }
+}
+
+impl NdjsonWriter<Part> {
+    /// Writes to the file at `path`, or to numbered files next to it if `options` rotates.
+    pub fn create(path: impl AsRef<Path>, options: FileOptions) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let Some(max_bytes) = options.max_bytes else {
+            return Ok(NdjsonWriter::new(open(path, options)?));
+        };
+
+        let open_part = move |n| open(part_path(&path, n), options);
+        let first = open_part(0)?;
+        Ok(NdjsonWriter {
+            rotation: Some(Rotation {
+                max_bytes,
+                parts: 1,
+                open: Box::new(open_part),
+                finish: Part::finish,
+            }),
+            ..NdjsonWriter::new(first)
+        })
+    }
+}
+
+#[cfg_attr(not(feature = "gzip"), allow(unused_variables))]
+fn open(path: PathBuf, options: FileOptions) -> io::Result<Part> {
+    #[cfg(feature = "gzip")]
+    if options.gzip {
+        let mut name = path.into_os_string();
+        name.push(".gz");
+        let out = BufWriter::new(File::create(name)?);
+        return Ok(Part::Gzip(GzEncoder::new(out, Compression::default())));
+    }
+    Ok(Part::Plain(BufWriter::new(File::create(path)?)))
+}
+
+/// `events.ndjson` becomes `events.{n}.ndjson`.
+fn part_path(path: &Path, n: usize) -> PathBuf {
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(extension) => format!("{stem}.{n}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{n}"),
+    };
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn values() -> Vec<Value> {
+        [
+            "{\"msg\":\"a\\nb\"}",
+            "[1,2]",
+            "null",
+            "\"long enough to rotate\"",
+        ]
+        .into_iter()
+        .map(|json| Parser::parse(json).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn write_lines() {
+        let mut writer = NdjsonWriter::new(Vec::new());
+        for value in values() {
+            writer.write(&value).unwrap();
+        }
+        assert_eq!(
+            "{\"msg\":\"a\\nb\"}\n[1,2]\nnull\n\"long enough to rotate\"\n",
+            String::from_utf8(writer.finish().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(not(feature = "gzip"), allow(clippy::needless_update))]
+    fn rotate_files() {
+        let dir = std::env::temp_dir().join(format!("ndjson-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = FileOptions {
+            max_bytes: Some(20),
+            ..FileOptions::default()
+        };
+        let mut writer = NdjsonWriter::create(dir.join("events.ndjson"), options).unwrap();
+        for value in values() {
+            writer.write(&value).unwrap();
+        }
+        writer.finish().unwrap().finish().unwrap();
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!("{\"msg\":\"a\\nb\"}\n", read("events.0.ndjson"));
+        assert_eq!("[1,2]\nnull\n", read("events.1.ndjson"));
+        assert_eq!("\"long enough to rotate\"\n", read("events.2.ndjson"));
+        assert!(!dir.join("events.3.ndjson").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn compress_files() {
+        use std::io::Read as _;
+
+        let dir = std::env::temp_dir().join(format!("ndjson-gzip-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = FileOptions {
+            gzip: true,
+            ..FileOptions::default()
+        };
+        let mut writer = NdjsonWriter::create(dir.join("events.ndjson"), options).unwrap();
+        for value in values() {
+            writer.write(&value).unwrap();
+        }
+        writer.finish().unwrap().finish().unwrap();
+
+        let mut lines = String::new();
+        flate2::read::GzDecoder::new(File::open(dir.join("events.ndjson.gz")).unwrap())
+            .read_to_string(&mut lines)
+            .unwrap();
+        assert_eq!(4, lines.lines().count());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,61 +1,315 @@
-use std::collections::HashMap;
+use std::{borrow::Cow, cell::RefCell, collections::HashMap, fmt::Write, str::FromStr};
 
+#[cfg(feature = "bytes")]
+use crate::bytes::BytesFormat;
 use crate::{
-    errors::{Error, ErrorCode},
-    lexical,
+    errors::{Error, ErrorCode, Expected, ParseError, Severity},
+    lexical::{self, Unescaper},
+    limits::Limits,
+    map::Map,
+    tuning::ParseProfile,
 };
 
-#[derive(Debug, PartialEq)]
+/// The type of numbers: `f64` by default. The `f32-numbers` feature stores them as `f32`, for
+/// targets without fast double precision, keeping about 7 significant digits. It does not make
+/// a `Value` smaller, whose size is set by its strings and containers.
+#[cfg(not(feature = "f32-numbers"))]
+pub type Float = f64;
+#[cfg(feature = "f32-numbers")]
+pub type Float = f32;
+
+/// Widens a [`Float`] to `f64`, which loses nothing whichever type it is.
+#[allow(clippy::useless_conversion)]
+pub(crate) fn widen(n: Float) -> f64 {
+    f64::from(n)
+}
+
+/// Numbers compare with `==` except that NaN equals NaN, which keeps equality reflexive so
+/// `Value` can be `Eq`. `0` and `-0` are equal, matching [`Value::digest`].
+#[derive(Debug, Clone, Default)]
 pub enum Value {
+    #[default]
     Null,
     Bool(bool),
-    Number(f64),
+    Number(Float),
     String(String),
     Array(Vec<Value>),
-    Object(HashMap<String, Value>),
+    Object(Map),
+    /// Binary data. JSON has no byte strings, so it is written as base64, as
+    /// [`FormatConfig::bytes`](crate::serialize::FormatConfig::bytes) says.
+    #[cfg(feature = "bytes")]
+    Bytes(Vec<u8>),
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (Value::String(a), Value::String(b)) => a == b,
+            #[cfg(feature = "bytes")]
+            (Value::Bytes(a), Value::Bytes(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Value {}
+
+impl FromStr for Value {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Value, ParseError> {
+        Parser::parse(s).map_err(ParseError::from)
+    }
+}
+
+impl Value {
+    /// The elements of an array, taken without copying. Any other value is given back.
+    pub fn into_array(self) -> Result<Vec<Value>, Value> {
+        match self {
+            Value::Array(elements) => Ok(elements),
+            value => Err(value),
+        }
+    }
+
+    /// The members of an object, taken without copying. Any other value is given back.
+    pub fn into_object(self) -> Result<Map, Value> {
+        match self {
+            Value::Object(members) => Ok(members),
+            value => Err(value),
+        }
+    }
+}
+
+enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+/// Extensions to strict JSON and limits on it. Anything the extensions allow is reported with
+/// a warning, so a strict pipeline can still flag it.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct ParseOptions {
+    /// Accept a comma after the last element of an array or member of an object.
+    pub allow_trailing_commas: bool,
+    /// Accept strings in single quotes, such as `{'a': 1}`. Escapes in them follow JSON5, so
+    /// `\'` and `\x41` are allowed as well.
+    pub allow_single_quotes: bool,
+    /// Accept object keys that are ECMAScript identifiers without quotes, such as `{foo: 1}`.
+    pub allow_unquoted_keys: bool,
+    /// The most bytes a string may have, counting its quotes, so that a missing closing quote
+    /// in a large document is reported rather than read as one huge string.
+    pub max_string_len: Option<usize>,
+    /// The most bytes a number may have.
+    pub max_number_len: Option<usize>,
+    /// Bounds on nesting and container size. A container beyond them is skipped without
+    /// being built.
+    pub limits: Limits,
+    /// Read strings that are padded base64, such as `"aGk="`, as [`Value::Bytes`]. Plenty of
+    /// short words are valid base64 as well, so only turn this on for documents known to
+    /// hold binary data.
+    #[cfg(feature = "bytes")]
+    pub detect_bytes: bool,
+    /// What to do with the keys `__proto__`, `constructor`, and `prototype`.
+    pub dangerous_keys: DangerousKeys,
+}
+
+/// How to treat object keys that can pollute JavaScript prototypes, for documents that are
+/// later handled by JavaScript code.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum DangerousKeys {
+    #[default]
+    Allow,
+    /// Report each with a warning.
+    Warn,
+    /// Report each with an error, so the document fails to parse.
+    Reject,
 }
 
 pub struct Parser<'a> {
     reader: lexical::Reader<'a>,
+    options: ParseOptions,
     errors: Vec<Error>,
+    warnings: Vec<Error>,
+    path: Vec<PathSegment>,
+    /// The number of containers open.
+    depth: usize,
+    unescaper: Unescaper,
+    /// The number of elements or members of each container, in the order they open, so that
+    /// they can be allocated at their final size.
+    sizes: std::vec::IntoIter<usize>,
+    /// Sizes to allocate containers at instead, without the prescan.
+    profile: Option<&'a ParseProfile>,
+}
+
+thread_local! {
+    static UNESCAPER: RefCell<Unescaper> = RefCell::default();
 }
 
 impl<'a> Parser<'a> {
     pub fn parse(json: &'a str) -> Result<Value, Vec<Error>> {
-        let mut parser = Parser {
-            reader: lexical::Reader::new(json),
+        if let Some(value) = parse_scalar(json) {
+            return Ok(value);
+        }
+        Parser::parse_with_warnings(json).map(|(value, _)| value)
+    }
+
+    /// Like [`Parser::parse`], but also returns diagnostics with [`Severity::Warning`] for a
+    /// document that parsed successfully.
+    ///
+    /// [`Severity::Warning`]: crate::errors::Severity::Warning
+    pub fn parse_with_warnings(json: &'a str) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        UNESCAPER.with(|unescaper| Parser::parse_with_unescaper(json, &mut unescaper.borrow_mut()))
+    }
+
+    /// Like [`Parser::parse_with_warnings`], but decodes escaped strings in `unescaper`'s
+    /// buffer instead of one kept per thread.
+    pub fn parse_with_unescaper(
+        json: &'a str,
+        unescaper: &mut Unescaper,
+    ) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        Parser::parse_with_unescaper_and_options(json, unescaper, ParseOptions::default())
+    }
+
+    /// Like [`Parser::parse_with_warnings`], but accepts the extensions turned on in
+    /// `options`.
+    pub fn parse_with_options(
+        json: &'a str,
+        options: ParseOptions,
+    ) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        UNESCAPER.with(|unescaper| {
+            Parser::parse_with_unescaper_and_options(json, &mut unescaper.borrow_mut(), options)
+        })
+    }
+
+    /// Parses the value at the start of `json`, such as one embedded in a template, and
+    /// returns it with the number of bytes it took up, counting whitespace before it but not
+    /// after, or on an error the bytes read before stopping. Whatever follows the value is
+    /// not read.
+    pub fn parse_fragment(json: &'a str) -> (Result<Value, Vec<Error>>, usize) {
+        UNESCAPER.with(|unescaper| {
+            let mut parser = Parser::new(
+                json,
+                &mut unescaper.borrow_mut(),
+                ParseOptions::default(),
+                None,
+            );
+            let value = parser.parse_value();
+            let consumed = parser.reader.position().start;
+            let result = match value {
+                Some(value) if parser.errors.is_empty() => Ok(value),
+                _ => Err(std::mem::take(&mut parser.errors)),
+            };
+            *unescaper.borrow_mut() = parser.unescaper;
+            (result, consumed)
+        })
+    }
+
+    /// Like [`Parser::parse_with_options`], but allocates each container at the capacity
+    /// `profile` suggests for its place rather than prescanning `json` for its exact size,
+    /// which saves a pass over documents shaped like those the profile recorded.
+    pub fn parse_with_profile(
+        json: &'a str,
+        options: ParseOptions,
+        profile: &'a ParseProfile,
+    ) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        UNESCAPER.with(|unescaper| {
+            let unescaper = &mut *unescaper.borrow_mut();
+            let mut parser = Parser::new(json, unescaper, options, Some(profile));
+            let result = parser.parse_document();
+            *unescaper = parser.unescaper;
+            result
+        })
+    }
+
+    fn parse_with_unescaper_and_options(
+        json: &'a str,
+        unescaper: &mut Unescaper,
+        options: ParseOptions,
+    ) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        let mut parser = Parser::new(json, unescaper, options, None);
+        let result = parser.parse_document();
+        *unescaper = parser.unescaper;
+        result
+    }
+
+    #[cfg(feature = "bytes")]
+    fn string_value(&self, s: String) -> Value {
+        let bytes = self
+            .options
+            .detect_bytes
+            .then(|| BytesFormat::Base64.decode(&s));
+        match bytes.flatten() {
+            Some(bytes) => Value::Bytes(bytes),
+            None => Value::String(s),
+        }
+    }
+
+    #[cfg(not(feature = "bytes"))]
+    fn string_value(&self, s: String) -> Value {
+        Value::String(s)
+    }
+
+    /// A parser that has taken `unescaper`'s buffer, which should be given back when done.
+    fn new(
+        json: &'a str,
+        unescaper: &mut Unescaper,
+        options: ParseOptions,
+        profile: Option<&'a ParseProfile>,
+    ) -> Self {
+        Parser {
+            reader: lexical::Reader::new(json)
+                .with_single_quotes(options.allow_single_quotes)
+                .with_identifiers(options.allow_unquoted_keys)
+                .with_max_string_len(options.max_string_len)
+                .with_max_number_len(options.max_number_len),
+            options,
             errors: Vec::<Error>::new(),
-        };
+            warnings: Vec::<Error>::new(),
+            path: Vec::<PathSegment>::new(),
+            depth: 0,
+            unescaper: std::mem::take(unescaper),
+            sizes: match profile {
+                Some(_) => Vec::new().into_iter(),
+                None => container_sizes(json).into_iter(),
+            },
+            profile,
+        }
+    }
 
+    fn parse_document(&mut self) -> Result<(Value, Vec<Error>), Vec<Error>> {
+        let parser = self;
         let value_opt = parser.parse_value();
         if !parser.errors.is_empty() {
-            Err(parser.errors)
-        } else if value_opt.is_none()
-            || !parser
-                .reader
-                .next(usize::MAX)
-                .iter()
-                .all(|x| x.clone().is_ok_and(|y| y.is_whitespace()))
-        {
-            Err(vec![parser
+            return Err(std::mem::take(&mut parser.errors));
+        }
+        match value_opt {
+            Some(value) if parser.reader.peek(1).is_empty() => {
+                Ok((value, std::mem::take(&mut parser.warnings)))
+            }
+            _ => Err(vec![parser
                 .reader
-                .create_error(ErrorCode::EndOfFileExpected)])
-        } else {
-            Ok(value_opt.unwrap())
+                .create_error_next(ErrorCode::EndOfFileExpected)
+                .with_expected(Expected::END_OF_FILE)]),
         }
     }
 
     fn parse_value(&mut self) -> Option<Value> {
         match self.reader.peek(1).as_slice() {
             [] => {
-                self.errors.push(
+                self.push_error(
                     self.reader
                         .create_error(ErrorCode::EndOfFileWhileParsingValue),
+                    Expected::VALUE,
                 );
                 None
             }
             [Err(error), ..] => {
-                self.errors.push(*error);
+                self.push_error(error.clone(), Expected::VALUE);
                 self.reader.next(1);
                 None
             }
@@ -64,52 +318,87 @@ impl<'a> Parser<'a> {
                 Some(Value::Null)
             }
             [Ok(lexical::Token::Bool(val)), ..] => {
+                let val = *val;
                 self.reader.next(1);
-                Some(Value::Bool(val.parse().unwrap()))
+                Some(Value::Bool(val))
+            }
+            [Ok(lexical::Token::String(val)), ..] => {
+                self.parse_string(val).map(|s| self.string_value(s))
             }
-            [Ok(lexical::Token::String(val)), ..] => self.parse_string(&val),
-            [Ok(lexical::Token::Number(val)), ..] => self.parse_number(&val),
+            [Ok(lexical::Token::Number(val)), ..] => self.parse_number(val),
             [Ok(lexical::Token::Punctuation(c)), ..] => match *c {
-                '{' => self.parse_object(),
-                '[' => self.parse_array(),
-                ',' | '}' | ']' | '|' => {
-                    self.errors
-                        .push(self.reader.create_error_next(ErrorCode::ExpectedToken));
+                '{' | '[' if !self.options.limits.allows_depth(self.depth + 1) => {
+                    let error = self.reader.create_error_next(ErrorCode::NestingTooDeep);
+                    self.push_error(error, Expected::NOTHING);
+                    self.reader.next(1);
+                    self.skip_to_end();
+                    None
+                }
+                '{' | '[' => {
+                    self.depth += 1;
+                    let value = if *c == '{' {
+                        self.parse_object()
+                    } else {
+                        self.parse_array()
+                    };
+                    self.depth -= 1;
+                    value
+                }
+                ',' | '}' | ']' => {
+                    let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+                    self.push_error(error, Expected::VALUE);
+                    None
+                }
+                _ => {
+                    let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+                    self.push_error(error, Expected::VALUE);
+                    self.reader.next(1);
                     None
                 }
-                a => panic!("{a} is not a valid punctuation in JSON"),
             },
-            a => {
-                panic!("{a:?} Shouldn't be possible to encounter");
+            [Ok(lexical::Token::Identifier(_)), ..] => {
+                let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+                self.push_error(error, Expected::VALUE);
+                self.reader.next(1);
+                None
             }
         }
     }
 
     fn parse_array(&mut self) -> Option<Value> {
+        debug_assert_eq!(
+            vec![Ok(lexical::Token::Punctuation('['))],
+            self.reader.peek(1)
+        );
         match self.reader.peek(2).as_slice() {
             [Err(error), ..] => {
-                self.errors.push(*error);
+                self.push_error(error.clone(), Expected::VALUE);
                 self.reader.next(1);
                 None
             }
             [Ok(lexical::Token::Punctuation('['))] => {
-                self.errors.push(
+                self.push_error(
                     self.reader
                         .create_error(ErrorCode::EndOfFileWhileParsing(']')),
+                    Expected::VALUE | Expected::END_OF_ARRAY,
                 );
                 self.reader.next(1);
                 None
             }
             [Ok(lexical::Token::Punctuation('[')), Ok(lexical::Token::Punctuation(']')), ..] => {
                 self.reader.next(2);
+                self.sizes.next();
                 Some(Value::Array(Vec::new()))
             }
             [Ok(lexical::Token::Punctuation('[')), ..] => {
                 self.reader.next(1);
                 self.parse_array_elements().map(Value::Array)
             }
-            a => {
-                panic!("Arrays must start with '['. Found {a:?}");
+            _ => {
+                let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+                self.push_error(error, Expected::VALUE);
+                self.reader.next(1);
+                None
             }
         }
     }
@@ -123,15 +412,21 @@ impl<'a> Parser<'a> {
             return None;
         }
 
-        let mut elements = Vec::<Value>::new();
-        loop {
-            if let Some(element) = self.parse_value() {
+        let mut elements = Vec::<Value>::with_capacity(self.next_size());
+        for index in 0.. {
+            if !self.options.limits.allows_len(index + 1) {
+                self.reject_extra_item();
+                return None;
+            }
+            self.path.push(PathSegment::Index(index));
+            let element = self.parse_value();
+            self.path.pop();
+            if let Some(element) = element {
                 elements.push(element);
             }
 
-            self.parse_until_comma_or_end(END_OF_ELEMENTS);
-
-            let reached_end = self.parse_sequence_separator(END_OF_ELEMENTS);
+            let reached_end = self.parse_until_comma_or_end(END_OF_ELEMENTS)
+                || self.parse_sequence_separator(END_OF_ELEMENTS);
             if reached_end {
                 break;
             }
@@ -141,63 +436,80 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_object(&mut self) -> Option<Value> {
+        debug_assert_eq!(
+            vec![Ok(lexical::Token::Punctuation('{'))],
+            self.reader.peek(1)
+        );
         match self.reader.peek(2).as_slice() {
             [Err(error), ..] => {
-                self.errors.push(*error);
+                self.push_error(error.clone(), Expected::VALUE);
                 self.reader.next(1);
                 None
             }
             [Ok(lexical::Token::Punctuation('{'))] => {
-                self.errors.push(
+                self.push_error(
                     self.reader
                         .create_error(ErrorCode::EndOfFileWhileParsing('}')),
+                    Expected::KEY | Expected::END_OF_OBJECT,
                 );
                 self.reader.next(1);
                 None
             }
             [Ok(lexical::Token::Punctuation('{')), Ok(lexical::Token::Punctuation('}')), ..] => {
                 self.reader.next(2);
-                Some(Value::Object(HashMap::new()))
+                self.sizes.next();
+                Some(Value::Object(Map::new()))
             }
             [Ok(lexical::Token::Punctuation('{')), ..] => {
                 self.reader.next(1);
                 self.parse_object_members().map(Value::Object)
             }
             _ => {
-                panic!("Objects must start with '{{'");
+                let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+                self.push_error(error, Expected::VALUE);
+                self.reader.next(1);
+                None
             }
         }
     }
 
-    fn parse_object_members(&mut self) -> Option<HashMap<String, Value>> {
+    fn parse_object_members(&mut self) -> Option<Map> {
         const END_OF_MEMBERS: char = '}';
 
         if self.reader.peek(1).is_empty() {
-            self.errors.push(
+            self.push_error(
                 self.reader
                     .create_error(ErrorCode::EndOfFileWhileParsing(END_OF_MEMBERS)),
+                Expected::KEY,
             );
             return None;
         }
 
-        let mut members = HashMap::<String, Value>::new();
+        let mut members = Map::with_capacity(self.next_size());
+        // Keys with case and surrounding spaces removed, mapped to the first key seen.
+        let mut similar_keys = HashMap::<String, String>::new();
 
-        loop {
+        for len in 1.. {
+            if !self.options.limits.allows_len(len) {
+                self.reject_extra_item();
+                return None;
+            }
             match self.reader.peek(2).as_slice() {
+                // Skip the value of a key that is not a token, such as one over the limit, so
+                // that its colon is not read as the end of the member.
+                [Err(error), Ok(lexical::Token::Punctuation(':'))] => {
+                    self.push_error(error.clone(), Expected::KEY);
+                    self.reader.next(2);
+                    self.parse_value();
+                }
                 [Err(error), ..] => {
-                    self.errors.push(*error);
+                    self.push_error(error.clone(), Expected::KEY);
                     self.reader.next(1);
                 }
                 [Ok(lexical::Token::String(s)), Ok(lexical::Token::Punctuation(':')), ..] => {
-                    match self.parse_string(&s) {
-                        Some(Value::String(key)) => {
-                            self.reader.next(1);
-                            if let Some(value) = self.parse_value() {
-                                members.insert(key, value);
-                            }
-                        }
-                        Some(_) => {
-                            panic!("Shouldn't be possible");
+                    match self.parse_string(s) {
+                        Some(key) => {
+                            self.parse_member(key, &mut members, &mut similar_keys);
                         }
                         None => {
                             self.reader.next(1);
@@ -205,37 +517,77 @@ impl<'a> Parser<'a> {
                         }
                     }
                 }
-                [_, Ok(lexical::Token::Punctuation(':')), ..] => {
+                [Ok(token), Ok(lexical::Token::Punctuation(':')), ..]
+                    if self.options.allow_unquoted_keys && unquoted_key(token).is_some() =>
+                {
+                    let key = unquoted_key(token).unwrap_or_default().to_string();
+                    self.reader.next(1);
+                    let warning = self
+                        .reader
+                        .create_error(ErrorCode::UnquotedKey)
+                        .with_path(format!("{}/{}", self.pointer(), escape_key(&key)));
+                    self.warnings.push(warning);
+                    self.parse_member(key, &mut members, &mut similar_keys);
+                }
+                [Ok(token), Ok(lexical::Token::Punctuation(':')), ..] => {
                     self.reader.next(1);
-                    self.errors
-                        .push(self.reader.create_error(ErrorCode::KeyMustBeAString));
+                    self.push_error(
+                        self.reader
+                            .create_error(ErrorCode::KeyMustBeAString)
+                            .with_found(&describe(token)),
+                        Expected::KEY,
+                    );
                     self.reader.next(1);
                     self.parse_value();
                 }
                 [Ok(lexical::Token::Punctuation(':')), ..] => {
                     self.reader.next(1);
-                    self.errors
-                        .push(self.reader.create_error(ErrorCode::KeyMustBeAString));
+                    self.push_error(
+                        self.reader
+                            .create_error(ErrorCode::KeyMustBeAString)
+                            .with_found("':'"),
+                        Expected::KEY,
+                    );
                     self.parse_value();
                 }
-                [Ok(lexical::Token::String(_)), ..] => {
-                    self.reader.next(1);
-                    self.errors
-                        .push(self.reader.create_error(ErrorCode::ExpectedColon));
+                [Ok(lexical::Token::String(s)), ..] => {
+                    if self.parse_string(s).is_some() {
+                        let error = self
+                            .reader
+                            .create_error(ErrorCode::ExpectedColon)
+                            .with_found(&self.describe_next());
+                        self.push_error(error, Expected::COLON);
+                        // Recover as if the colon were there, e.g. `{"a" 1}`.
+                        if !matches!(
+                            self.reader.peek(1).as_slice(),
+                            [] | [Ok(lexical::Token::Punctuation(',' | END_OF_MEMBERS))]
+                        ) {
+                            self.parse_value();
+                        }
+                    }
                 }
-                [_, ..] => {
-                    self.errors
-                        .push(self.reader.create_error(ErrorCode::KeyMustBeAString));
+                [Ok(token), ..] => {
+                    self.push_error(
+                        self.reader
+                            .create_error(ErrorCode::KeyMustBeAString)
+                            .with_found(&describe(token)),
+                        Expected::KEY,
+                    );
                     self.reader.next(1);
                 }
                 [] => {
-                    panic!("Shouldn't be able to get an empty list");
+                    debug_assert!(false, "the end of the input to end the object");
+                    self.push_error(
+                        self.reader
+                            .create_error(ErrorCode::EndOfFileWhileParsing(END_OF_MEMBERS)),
+                        Expected::KEY,
+                    );
+                    return None;
                 }
             }
 
-            self.parse_until_comma_or_end(END_OF_MEMBERS);
-
-            let reached_end = self.parse_sequence_separator(END_OF_MEMBERS);
+            let reached_end = self.parse_until_comma_or_end(END_OF_MEMBERS)
+                || self.parse_sequence_separator(END_OF_MEMBERS);
             if reached_end {
                 break;
             }
@@ -244,13 +596,64 @@ impl<'a> Parser<'a> {
         Some(members)
     }
 
+    /// Parses the value of a member whose key was the last token read, with the colon next.
+    fn parse_member(
+        &mut self,
+        key: String,
+        members: &mut Map,
+        similar_keys: &mut HashMap<String, String>,
+    ) {
+        self.check_key(&key, members, similar_keys);
+        let policy = self.options.dangerous_keys;
+        if policy != DangerousKeys::Allow
+            && matches!(key.as_str(), "__proto__" | "constructor" | "prototype")
+        {
+            let diagnostic = self
+                .reader
+                .create_error(ErrorCode::DangerousKey)
+                .with_path(format!("{}/{}", self.pointer(), escape_key(&key)));
+            match policy {
+                DangerousKeys::Reject => {
+                    self.errors.push(diagnostic.with_severity(Severity::Error))
+                }
+                _ => self.warnings.push(diagnostic),
+            }
+        }
+        self.reader.next(1);
+        self.path.push(PathSegment::Key(key));
+        let value = self.parse_value();
+        if let (Some(PathSegment::Key(key)), Some(value)) = (self.path.pop(), value) {
+            members.insert(key, value);
+        }
+    }
+
     fn parse_number(&mut self, possible_number: &str) -> Option<Value> {
-        assert!(!possible_number.is_empty());
-        let ret = match possible_number.parse::<f64>() {
-            Ok(n) => Some(Value::Number(n)),
-            Err(_) => {
-                self.errors
-                    .push(self.reader.create_error(ErrorCode::InvalidNumber));
+        debug_assert!(!possible_number.is_empty());
+        if let Some(n) = lexical::small_integer(possible_number) {
+            self.reader.next(1);
+            return Some(Value::Number(n as Float));
+        }
+        let number = lexical::validate_number(possible_number)
+            .ok()
+            .and_then(|_| possible_number.parse::<f64>().ok());
+        let ret = match number {
+            Some(n) => {
+                if !is_exact_integer(possible_number, n) {
+                    let warning = self
+                        .reader
+                        .create_error_next(ErrorCode::InexactInteger)
+                        .with_detail(format!("{possible_number} would be read as {n:.0}"))
+                        .with_path(self.pointer());
+                    self.warnings.push(warning);
+                }
+                Some(Value::Number(n as Float))
+            }
+            None => {
+                let span = self.reader.peek_span().expect("the number to be next");
+                let error = self
+                    .reader
+                    .create_error_next(ErrorCode::invalid_number(possible_number, span));
+                self.push_error(error, Expected::NOTHING);
                 None
             }
         };
@@ -258,64 +661,123 @@ impl<'a> Parser<'a> {
         ret
     }
 
-    fn parse_string(&mut self, possible_string: &str) -> Option<Value> {
-        assert!(!possible_string.is_empty());
-
-        let mut chars = possible_string.chars().peekable();
-        let mut num_quotations = 0;
-
-        while let Some(c) = chars.next() {
-            match (c, chars.peek()) {
-                ('\\', Some('"')) => {
-                    chars.next();
-                }
-                ('"', _) => {
-                    num_quotations += 1;
+    /// Warns about a key that was already used in the object, or that differs from one only
+    /// by case or surrounding whitespace. Must be called while the key is the last token read.
+    fn check_key(&mut self, key: &str, members: &Map, similar_keys: &mut HashMap<String, String>) {
+        let (code, detail) = if members.contains_key(key) {
+            (ErrorCode::DuplicateKey, format!("{key:?}"))
+        } else {
+            // Most keys are already lowercase without spaces and need no allocation. Other
+            // keys are remembered by their normalized form.
+            let normalized = normalize_key(key);
+            let similar = similar_keys.get(normalized.as_ref()).cloned().or_else(|| {
+                (matches!(normalized, Cow::Owned(_)) && members.contains_key(&normalized))
+                    .then(|| normalized.to_string())
+            });
+            match similar {
+                Some(similar) => (ErrorCode::SimilarKey, format!("{key:?} and {similar:?}")),
+                None => {
+                    if let Cow::Owned(normalized) = normalized {
+                        similar_keys.insert(normalized, key.to_string());
+                    }
+                    return;
                 }
-                _ => {}
             }
-        }
+        };
+        let warning = self
+            .reader
+            .create_error(code)
+            .with_detail(detail)
+            .with_path(format!("{}/{}", self.pointer(), escape_key(key)));
+        self.warnings.push(warning);
+    }
 
-        let first = possible_string.chars().next().unwrap();
-        assert!(first == '"');
+    fn parse_string(&mut self, possible_string: &str) -> Option<String> {
+        debug_assert!(!possible_string.is_empty());
 
-        let last = possible_string.chars().last().unwrap();
         self.reader.next(1);
-        let ret = if possible_string.len() == 1 || num_quotations != 2 || last != '"' {
-            println!("{possible_string}");
-            self.errors
-                .push(self.reader.create_error(ErrorCode::ExpectedDoubleQuote));
-            None
+        let single_quoted = possible_string.starts_with('\'');
+        let contents = if single_quoted {
+            let warning = self
+                .reader
+                .create_error(ErrorCode::SingleQuotedString)
+                .with_path(self.pointer());
+            self.warnings.push(warning);
+            lexical::single_quoted_contents(possible_string)
         } else {
-            Some(Value::String(
-                possible_string[1..possible_string.len() - 1].to_string(),
-            ))
+            lexical::string_contents(possible_string)
         };
-
-        ret
+        match contents {
+            Some(contents) => {
+                let unescaped = if single_quoted {
+                    self.unescaper.unescape_json5(contents)
+                } else {
+                    self.unescaper.unescape(contents)
+                };
+                match unescaped.map(str::to_string) {
+                    Ok(s) => Some(s),
+                    Err(_) => {
+                        self.push_error(
+                            self.reader.create_error(ErrorCode::InvalidEscape),
+                            Expected::NOTHING,
+                        );
+                        None
+                    }
+                }
+            }
+            None => {
+                self.push_error(
+                    self.reader.create_error(ErrorCode::ExpectedDoubleQuote),
+                    Expected::NOTHING,
+                );
+                None
+            }
+        }
     }
 
     fn parse_sequence_separator(&mut self, end: char) -> bool {
         match self.reader.peek(2).as_slice() {
             [Err(error), ..] => {
-                self.errors.push(*error);
+                self.push_error(error.clone(), Expected::COMMA | Expected::end_of(end));
                 self.reader.next(1);
                 false
             }
-            [] | [Ok(lexical::Token::Punctuation(','))] => {
+            [] => {
+                self.push_error(
+                    self.reader
+                        .create_error(ErrorCode::EndOfFileWhileParsing(end)),
+                    Expected::COMMA | Expected::end_of(end),
+                );
+                true
+            }
+            [Ok(lexical::Token::Punctuation(','))] => {
                 self.reader.next(1);
-                self.errors.push(
+                self.push_error(
                     self.reader
                         .create_error(ErrorCode::EndOfFileWhileParsing(end)),
+                    Expected::element_of(end),
                 );
                 true
             }
+            [Ok(lexical::Token::Punctuation(',')), Ok(lexical::Token::Punctuation(possible_end)), ..]
+                if *possible_end == end && self.options.allow_trailing_commas =>
+            {
+                let warning = self
+                    .reader
+                    .create_error_next(ErrorCode::TrailingComma)
+                    .with_path(self.pointer());
+                self.warnings.push(warning);
+                self.reader.next(2);
+                true
+            }
             [Ok(lexical::Token::Punctuation(',')), Ok(lexical::Token::Punctuation(possible_end)), ..]
                 if *possible_end == end =>
             {
                 self.reader.next(2);
-                self.errors
-                    .push(self.reader.create_error(ErrorCode::ExpectedToken));
+                self.push_error(
+                    self.reader.create_error(ErrorCode::ExpectedToken),
+                    Expected::element_of(end),
+                );
                 true
             }
             [Ok(lexical::Token::Punctuation(',')), ..] => {
@@ -326,10 +788,12 @@ impl<'a> Parser<'a> {
                 self.reader.next(1);
                 true
             }
-            [_, ..] => {
-                self.errors.push(
+            [Ok(token), ..] => {
+                self.push_error(
                     self.reader
-                        .create_error(ErrorCode::EndOfFileWhileParsing(end)),
+                        .create_error(ErrorCode::EndOfFileWhileParsing(end))
+                        .with_found(&describe(token)),
+                    Expected::COMMA | Expected::end_of(end),
                 );
                 self.reader.next(1);
                 false
@@ -337,39 +801,249 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_until_comma_or_end(&mut self, end: char) {
-        let mut seen_non_comma_value = false;
+    /// The next token as [`describe`] shows it, or nothing at the end of the input or an
+    /// invalid token.
+    fn describe_next(&mut self) -> String {
+        match self.reader.peek(1).first() {
+            Some(Ok(token)) => describe(token),
+            _ => String::new(),
+        }
+    }
+
+    fn push_error(&mut self, error: Error, expected: Expected) {
+        let path = self.pointer();
+        self.errors
+            .push(error.with_path(path).with_expected(expected));
+    }
+
+    fn pointer(&self) -> String {
+        let mut path = String::new();
+        for segment in &self.path {
+            path.push('/');
+            match segment {
+                PathSegment::Index(index) => write!(path, "{index}").unwrap(),
+                PathSegment::Key(key) => path.push_str(&escape_key(key)),
+            }
+        }
+        path
+    }
+
+    /// The size of the next container from the prescan or the profile, no more than the
+    /// limits allow.
+    fn next_size(&mut self) -> usize {
+        let size = match self.profile {
+            Some(profile) => {
+                let mut place = String::new();
+                for segment in &self.path {
+                    match segment {
+                        PathSegment::Index(_) => place.push_str("/*"),
+                        PathSegment::Key(key) => write!(place, "/{}", escape_key(key)).unwrap(),
+                    }
+                }
+                profile.capacity(&place)
+            }
+            None => self.sizes.next().unwrap_or(0),
+        };
+        size.min(self.options.limits.max_len.unwrap_or(usize::MAX))
+    }
+
+    /// Reports the next item of the current container as one too many, and skips it along
+    /// with the rest of the container.
+    fn reject_extra_item(&mut self) {
+        let error = self.reader.create_error_next(ErrorCode::ContainerTooLarge);
+        self.push_error(error, Expected::NOTHING);
+        self.skip_to_end();
+    }
+
+    /// Skips past the bracket that closes the current container, without building or checking
+    /// anything in it.
+    fn skip_to_end(&mut self) {
+        let mut depth = 0usize;
+        while let Some(token) = self.reader.next(1).pop() {
+            match token {
+                Ok(lexical::Token::Punctuation('[' | '{')) => depth += 1,
+                Ok(lexical::Token::Punctuation(']' | '}')) if depth == 0 => break,
+                Ok(lexical::Token::Punctuation(']' | '}')) => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Skips to the next `,` or `end` of the current container, stepping over whole nested
+    /// containers so a mistake inside one is reported once rather than once per level.
+    /// Returns true if a mismatched closing bracket was taken as the end of the container.
+    fn parse_until_comma_or_end(&mut self, end: char) -> bool {
+        let mut error = None;
+        let mut depth = 0usize;
+        let mut closed = false;
         loop {
-            match self.reader.peek(1).as_slice() {
-                [] | [Ok(lexical::Token::Punctuation(',')), ..] => {
-                    break;
+            match self.reader.peek(2).as_slice() {
+                [] => break,
+                [Ok(lexical::Token::Punctuation(',')), ..] if depth == 0 => break,
+                [Ok(lexical::Token::Punctuation(c)), ..] if depth == 0 && *c == end => break,
+                [Ok(lexical::Token::Punctuation('[' | '{')), ..] => depth += 1,
+                [Ok(lexical::Token::Punctuation(']' | '}')), ..] if depth > 0 => depth -= 1,
+                // A stray closing bracket right before the real end is skipped; otherwise it
+                // is most likely a typo for `end`.
+                [Ok(lexical::Token::Punctuation(']' | '}')), Ok(lexical::Token::Punctuation(c))]
+                    if *c == end => {}
+                [Ok(lexical::Token::Punctuation(']' | '}')), ..] => closed = true,
+                [_, ..] => {}
+            }
+            if error.is_none() {
+                let found = self.describe_next();
+                error = Some(
+                    self.reader
+                        .create_error_next(ErrorCode::ExpectedCommaOrEndWhileParsing(end))
+                        .with_found(&found),
+                );
+            }
+            self.reader.next(1);
+            if closed {
+                break;
+            }
+        }
+
+        if let Some(error) = error {
+            self.push_error(error, Expected::COMMA | Expected::end_of(end));
+        }
+        closed
+    }
+}
+
+/// A token as an error shows it: punctuation in quotes, as [`Expected`] shows it, and anything
+/// else as written.
+fn describe(token: &lexical::Token) -> String {
+    match token {
+        lexical::Token::Null => "null".to_string(),
+        lexical::Token::Bool(b) => b.to_string(),
+        lexical::Token::Punctuation(c) => format!("'{c}'"),
+        lexical::Token::String(text) | lexical::Token::Number(text) => text.to_string(),
+        lexical::Token::Identifier(text) => text.clone(),
+    }
+}
+
+/// Reads a document that is only a literal, a number, or a string without escapes, as many
+/// queue messages are, without the tokenizer or the prescan. `None` for anything else,
+/// including a scalar that is invalid, which is left to the parser to report.
+fn parse_scalar(json: &str) -> Option<Value> {
+    let json = json.trim_matches([' ', '\t', '\n', '\r']);
+    match json.as_bytes().first()? {
+        b'n' | b't' | b'f' => match json {
+            "null" => Some(Value::Null),
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        b'"' => {
+            let contents = json.strip_prefix('"')?.strip_suffix('"')?;
+            let is_plain = contents
+                .bytes()
+                .all(|b| b >= 0x20 && b != b'"' && b != b'\\');
+            is_plain.then(|| Value::String(contents.to_string()))
+        }
+        b'-' | b'0'..=b'9' => {
+            if let Some(n) = lexical::small_integer(json) {
+                return Some(Value::Number(n as Float));
+            }
+            lexical::validate_number(json).ok()?;
+            json.parse::<f64>().ok().map(|n| Value::Number(n as Float))
+        }
+        _ => None,
+    }
+}
+
+/// Counts the elements or members of every container by counting the commas directly inside
+/// it, without tokenizing. For invalid documents, or ones using the extensions in
+/// [`ParseOptions`], the counts may not match what the parser finds, which only affects how
+/// much is allocated.
+fn container_sizes(json: &str) -> Vec<usize> {
+    let mut sizes = Vec::<usize>::new();
+    // The index into `sizes` of each open container, and whether it has any content yet.
+    let mut open = Vec::<(usize, bool)>::new();
+    let mut bytes = json.bytes();
+    while let Some(b) = bytes.next() {
+        match b {
+            b' ' | b'\t' | b'\n' | b'\r' => continue,
+            b'[' | b'{' => {
+                if let Some((_, nonempty)) = open.last_mut() {
+                    *nonempty = true;
+                }
+                open.push((sizes.len(), false));
+                sizes.push(0);
+                continue;
+            }
+            b']' | b'}' => {
+                if let Some((i, nonempty)) = open.pop() {
+                    sizes[i] += usize::from(nonempty);
                 }
-                [Ok(lexical::Token::Punctuation(possible_end)), ..] if *possible_end == end => {
+                // Nothing after the top-level value is parsed, and a fragment may be followed
+                // by text that is not JSON at all.
+                if open.is_empty() {
                     break;
                 }
-                [Ok(lexical::Token::Punctuation(':')), ..] => {
-                    self.reader.next(1);
-                    self.parse_value();
+                continue;
+            }
+            b',' => {
+                if let Some((i, _)) = open.last() {
+                    sizes[*i] += 1;
                 }
-                [_, ..] => {
-                    self.reader.next(1);
-                    seen_non_comma_value = true;
+            }
+            b'"' => {
+                let mut escaped = false;
+                for b in bytes.by_ref() {
+                    match (escaped, b) {
+                        (false, b'"') => break,
+                        (false, b'\\') => escaped = true,
+                        _ => escaped = false,
+                    }
                 }
             }
+            _ => {}
         }
-
-        if seen_non_comma_value {
-            self.errors.push(
-                self.reader
-                    .create_error_next(ErrorCode::ExpectedCommaOrEndWhileParsing(end)),
-            );
+        if let Some((_, nonempty)) = open.last_mut() {
+            *nonempty = true;
         }
     }
+    sizes
+}
+
+/// The text of a token that can be a key without quotes. Like in JavaScript, the literals are
+/// allowed as well as identifiers.
+pub(crate) fn unquoted_key(token: &lexical::Token) -> Option<&str> {
+    match token {
+        lexical::Token::Identifier(key) => Some(key),
+        lexical::Token::Bool(true) => Some("true"),
+        lexical::Token::Bool(false) => Some("false"),
+        lexical::Token::Null => Some("null"),
+        _ => None,
+    }
+}
+
+fn normalize_key(key: &str) -> Cow<'_, str> {
+    if key.trim().len() == key.len() && !key.chars().any(char::is_uppercase) {
+        Cow::Borrowed(key)
+    } else {
+        Cow::Owned(key.trim().to_lowercase())
+    }
+}
+
+/// Escapes a key for use as a JSON pointer token.
+pub(crate) fn escape_key(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// Integers of up to 15 digits always fit in an f64's 53-bit mantissa; longer ones are exact
+/// only if the float prints back as the same digits.
+fn is_exact_integer(token: &str, n: f64) -> bool {
+    let digits = token.trim_start_matches('-');
+    digits.len() <= 15 || digits.contains(['.', 'e', 'E']) || format!("{:.0}", n.abs()) == digits
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{errors::Severity, lexical::Span};
 
     #[test]
     fn pass_single_value_json() {
@@ -382,7 +1056,7 @@ mod tests {
             Parser::parse("\"Hello World\"")
         );
         assert_eq!(Ok(Value::Array(Vec::new())), Parser::parse("[]"));
-        assert_eq!(Ok(Value::Object(HashMap::new())), Parser::parse("{}"));
+        assert_eq!(Ok(Value::Object(Map::new())), Parser::parse("{}"));
     }
 
     #[test]
@@ -413,8 +1087,10 @@ mod tests {
             Err(vec![Error::new(
                 ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
                 1,
-                11
-            ),]),
+                8
+            )
+            .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+            .with_found(r#""a""#)]),
             Parser::parse(r#"[false "a"]"#)
         );
     }
@@ -423,9 +1099,13 @@ mod tests {
     fn fail_many_commas() {
         assert_eq!(
             Err(vec![
-                Error::new(ErrorCode::ExpectedToken, 1, 2),
-                Error::new(ErrorCode::ExpectedToken, 1, 3),
-                Error::new(ErrorCode::ExpectedToken, 1, 4)
+                Error::new(ErrorCode::ExpectedToken, 1, 2)
+                    .with_path("/0")
+                    .with_expected(Expected::VALUE),
+                Error::new(ErrorCode::ExpectedToken, 1, 3)
+                    .with_path("/1")
+                    .with_expected(Expected::VALUE),
+                Error::new(ErrorCode::ExpectedToken, 1, 4).with_expected(Expected::VALUE)
             ]),
             Parser::parse(r#"[,,]"#)
         );
@@ -438,7 +1118,8 @@ mod tests {
                 ErrorCode::EndOfFileWhileParsing(']'),
                 1,
                 2
-            ),]),
+            )
+            .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)]),
             Parser::parse("[true")
         );
     }
@@ -450,7 +1131,8 @@ mod tests {
                 ErrorCode::EndOfFileWhileParsing(']'),
                 1,
                 6
-            ),]),
+            )
+            .with_expected(Expected::VALUE)]),
             Parser::parse("[true,")
         );
     }
@@ -458,7 +1140,9 @@ mod tests {
     #[test]
     fn fail_more_than_one_json_value() {
         assert_eq!(
-            Err(vec![Error::new(ErrorCode::EndOfFileExpected, 1, 6)]),
+            Err(vec![
+                Error::new(ErrorCode::EndOfFileExpected, 1, 6).with_expected(Expected::END_OF_FILE)
+            ]),
             Parser::parse("null null")
         )
     }
@@ -467,8 +1151,12 @@ mod tests {
     fn fail_unopened_object() {
         assert_eq!(
             Err(vec![
-                Error::new(ErrorCode::ExpectedToken, 1, 9),
-                Error::new(ErrorCode::ExpectedCommaOrEndWhileParsing(']'), 1, 10),
+                Error::new(ErrorCode::ExpectedToken, 1, 9)
+                    .with_path("/1")
+                    .with_expected(Expected::VALUE),
+                Error::new(ErrorCode::ExpectedCommaOrEndWhileParsing(']'), 1, 9)
+                    .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+                    .with_found("'}'"),
             ]),
             Parser::parse("[false, }]")
         )
@@ -478,17 +1166,514 @@ mod tests {
     fn include_elements_errors() {
         assert_eq!(
             Err(vec![
-                Error::new(ErrorCode::ExpectedToken, 1, 4),
-                Error::new(ErrorCode::ExpectedToken, 1, 14),
+                Error::new(ErrorCode::ExpectedToken, 1, 4)
+                    .with_path("/0/0")
+                    .with_expected(Expected::VALUE),
+                Error::new(ErrorCode::ExpectedToken, 1, 14).with_expected(Expected::VALUE),
             ]),
             Parser::parse("[[ , false], ]")
         )
     }
 
+    #[test]
+    fn include_path_in_errors() {
+        let json = r#"{"statuses": [null, {"user": {"a/b~": 1, "name": tru}}]}"#;
+        assert_eq!(
+            Err(vec![Error::new(ErrorCode::ExpectedToken, 1, 50)
+                .with_path("/statuses/1/user/name")
+                .with_expected(Expected::VALUE)]),
+            Parser::parse(json)
+        );
+        assert_eq!(
+            Err(vec![Error::new(
+                ErrorCode::invalid_number("1x", Span::new(9, 11, 1, 10)),
+                1,
+                10
+            )
+            .with_path("/a~1b~0")]),
+            Parser::parse(r#"{"a/b~": 1x}"#)
+        );
+    }
+
+    #[test]
+    fn describe_expected_tokens() {
+        let messages = |json| match Parser::parse(json) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.iter().map(|e| e.message()).collect::<Vec<String>>(),
+        };
+        assert_eq!(
+            vec![r#"Expected ',' or '}' but found "b""#],
+            messages(r#"{"a": 1 "b": 2}"#)
+        );
+        assert_eq!(vec!["Expected ':' but found '}'"], messages(r#"{"a"}"#));
+        assert_eq!(
+            vec!["Expected a string key but found 1"],
+            messages(r#"{1: true}"#)
+        );
+        assert_eq!(vec!["Expected a string key"], messages(r#"{"a": 1, }"#));
+        assert_eq!(vec!["Expected a value"], messages("[1, ]"));
+        assert_eq!(
+            vec!["End of file while parsing an object, expected a string key or '}'"],
+            messages("{")
+        );
+    }
+
+    #[test]
+    fn report_one_error_per_mistake() {
+        let errors = |json| match Parser::parse(json) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors
+                .iter()
+                .map(|e| (e.code(), e.line(), e.col(), e.path().to_string()))
+                .collect::<Vec<(ErrorCode, usize, usize, String)>>(),
+        };
+
+        let json = r#"{
+            "statuses": [
+                {"id": 1, "coordinates": [-65.6 43.4], "text": "a"},
+                {"id": 2, "user": {"name": "b" "tags": [1, 2]}, "count": 2}
+            ],
+            "search": {"count": 2, "query" "x", "next": [[1, 2], [3 [4]]]}
+        }"#;
+        assert_eq!(
+            vec![
+                (
+                    ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
+                    3,
+                    49,
+                    "/statuses/0/coordinates".to_string()
+                ),
+                (
+                    ErrorCode::ExpectedCommaOrEndWhileParsing('}'),
+                    4,
+                    48,
+                    "/statuses/1/user".to_string()
+                ),
+                (ErrorCode::ExpectedColon, 6, 36, "/search".to_string()),
+                (
+                    ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
+                    6,
+                    69,
+                    "/search/next/1".to_string()
+                ),
+            ],
+            errors(json)
+        );
+
+        assert_eq!(
+            vec![(
+                ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
+                1,
+                12,
+                "/a".to_string()
+            )],
+            errors(r#"{"a": [1, 2}, "b": 3}"#)
+        );
+    }
+
+    #[test]
+    fn take_containers_apart() {
+        let value = Parser::parse(r#"[{"a": [1, 2]}, "b"]"#).unwrap();
+        let mut elements = value.into_array().unwrap();
+        assert_eq!(
+            Err(Value::String("b".to_string())),
+            elements.pop().unwrap().into_object()
+        );
+
+        let members = elements.pop().unwrap().into_object().unwrap();
+        let a = members.into_iter().next().unwrap().1;
+        assert_eq!(
+            Ok(vec![Value::Number(1.0), Value::Number(2.0)]),
+            a.into_array()
+        );
+        assert_eq!(Err(Value::Null), Value::Null.into_array());
+    }
+
+    #[test]
+    fn value_equality() {
+        let nan = Value::Array(vec![Value::Number(Float::NAN)]);
+        assert_eq!(nan, nan.clone());
+        assert_eq!(Value::Number(0.0), Value::Number(-0.0));
+        assert_ne!(Value::Number(1.0), Value::String("1".to_string()));
+        assert_eq!(Value::Null, Value::default());
+    }
+
+    #[test]
+    fn warn_on_inexact_integers() {
+        let json = r#"{"id": 505874924095815681, "ok": [9007199254740992, 1e300, -123]}"#;
+        let (_, warnings) = Parser::parse_with_warnings(json).unwrap();
+        assert_eq!(
+            vec![Error::new(ErrorCode::InexactInteger, 1, 8)
+                .with_path("/id")
+                .with_detail("505874924095815681 would be read as 505874924095815680")],
+            warnings
+        );
+        assert_eq!(
+            "1:8: warning: Integer cannot be represented exactly as a 64-bit float: \
+             505874924095815681 would be read as 505874924095815680 at /id",
+            warnings[0].to_string()
+        );
+        assert_eq!(Severity::Warning, warnings[0].severity());
+
+        let (_, warnings) =
+            Parser::parse_with_warnings(&std::fs::read_to_string("tests/twitter.json").unwrap())
+                .unwrap();
+        assert!(warnings.iter().any(|w| w.path() == "/statuses/0/id"));
+    }
+
+    #[test]
+    fn parse_scalars_without_tokenizing() {
+        for json in [
+            "null",
+            " true\n",
+            "false",
+            "0",
+            "-12",
+            "1.5e3",
+            "12345678901234567890",
+            "\"\"",
+            "\"plain é\"",
+        ] {
+            let value = parse_scalar(json).unwrap();
+            assert_eq!(
+                Parser::parse_with_warnings(json).unwrap().0,
+                value,
+                "{json}"
+            );
+        }
+        for json in [
+            "", "nul", "01", "1.", "-", "\"a\\n\"", "\"a\tb\"", "\"a", "\"a\"b\"", "[1]", "1 2",
+        ] {
+            assert_eq!(None, parse_scalar(json), "{json}");
+            assert_eq!(
+                Parser::parse_with_warnings(json).map(|(v, _)| v),
+                Parser::parse(json)
+            );
+        }
+    }
+
+    #[test]
+    fn presize_containers() {
+        assert_eq!(
+            vec![3, 0, 2, 1, 0, 1],
+            container_sizes(r#"[[], {"a": "x,]\"", "b": [{}]}, [" [ "]]"#)
+        );
+
+        let Ok(Value::Array(elements)) = Parser::parse("[1, [2, 3, 4], {}, true]") else {
+            panic!("expected an array");
+        };
+        assert_eq!(4, elements.capacity());
+        let Value::Array(inner) = &elements[1] else {
+            panic!("expected an array");
+        };
+        assert_eq!(3, inner.capacity());
+    }
+
+    #[test]
+    fn warn_on_duplicate_and_similar_keys() {
+        let json = r#"{"id": 1, "a": {"Id": 2, "id": 3, "id": 4}, "ID ": 5, "id": 6}"#;
+        let (value, warnings) = Parser::parse_with_warnings(json).unwrap();
+        assert_eq!(
+            vec![
+                Error::new(ErrorCode::SimilarKey, 1, 26)
+                    .with_path("/a/id")
+                    .with_detail(r#""id" and "Id""#),
+                Error::new(ErrorCode::DuplicateKey, 1, 35)
+                    .with_path("/a/id")
+                    .with_detail(r#""id""#),
+                Error::new(ErrorCode::SimilarKey, 1, 45)
+                    .with_path("/ID ")
+                    .with_detail(r#""ID " and "id""#),
+                Error::new(ErrorCode::DuplicateKey, 1, 55)
+                    .with_path("/id")
+                    .with_detail(r#""id""#),
+            ],
+            warnings
+        );
+        assert_eq!(Some(&Value::Number(4.0)), value.pointer("/a/id"));
+    }
+
+    #[test]
+    fn allow_trailing_commas() {
+        let json = "{\"a\": [1, [],\n], \"b\": {\"c\": 2,},}";
+        let options = ParseOptions {
+            allow_trailing_commas: true,
+            ..ParseOptions::default()
+        };
+        let (value, warnings) = Parser::parse_with_options(json, options).unwrap();
+        assert_eq!(
+            Parser::parse(r#"{"a": [1, []], "b": {"c": 2}}"#).unwrap(),
+            value
+        );
+        assert_eq!(
+            vec![
+                Error::new(ErrorCode::TrailingComma, 1, 13).with_path("/a"),
+                Error::new(ErrorCode::TrailingComma, 2, 16).with_path("/b"),
+                Error::new(ErrorCode::TrailingComma, 2, 18),
+            ],
+            warnings
+        );
+        assert!(warnings.iter().all(|w| w.severity() == Severity::Warning));
+
+        assert!(Parser::parse(json).is_err());
+        assert!(Parser::parse_with_options("[,]", options).is_err());
+        assert!(Parser::parse_with_options("[1,,]", options).is_err());
+    }
+
+    #[test]
+    fn allow_single_quotes() {
+        let json = r#"{'a': 'it\'s "x"', "b": ['\x41', "'"], 'c': 'd\
+e'}"#;
+        let options = ParseOptions {
+            allow_single_quotes: true,
+            ..ParseOptions::default()
+        };
+        let (value, warnings) = Parser::parse_with_options(json, options).unwrap();
+        assert_eq!(
+            Parser::parse(r#"{"a": "it's \"x\"", "b": ["A", "'"], "c": "de"}"#).unwrap(),
+            value
+        );
+        assert_eq!(
+            vec![
+                Error::new(ErrorCode::SingleQuotedString, 1, 2),
+                Error::new(ErrorCode::SingleQuotedString, 1, 7).with_path("/a"),
+                Error::new(ErrorCode::SingleQuotedString, 1, 26).with_path("/b/0"),
+                Error::new(ErrorCode::SingleQuotedString, 1, 40),
+                Error::new(ErrorCode::SingleQuotedString, 1, 45).with_path("/c"),
+            ],
+            warnings
+        );
+
+        assert!(Parser::parse(json).is_err());
+        let errors = Parser::parse_with_options("['a\\1']", options).unwrap_err();
+        assert_eq!(
+            vec![ErrorCode::InvalidEscape],
+            errors.iter().map(Error::code).collect::<Vec<_>>()
+        );
+        assert!(Parser::parse_with_options("['a]", options).is_err());
+    }
+
+    #[test]
+    fn allow_unquoted_keys() {
+        let json = "{$id: 1, \"a\": {null: [2], x_1 : true},\n  é: 3, $id: 4}";
+        let options = ParseOptions {
+            allow_unquoted_keys: true,
+            ..ParseOptions::default()
+        };
+        let (value, warnings) = Parser::parse_with_options(json, options).unwrap();
+        assert_eq!(
+            Parser::parse(r#"{"$id": 4, "a": {"null": [2], "x_1": true}, "é": 3}"#).unwrap(),
+            value
+        );
+        assert_eq!(
+            vec![
+                Error::new(ErrorCode::UnquotedKey, 1, 2).with_path("/$id"),
+                Error::new(ErrorCode::UnquotedKey, 1, 16).with_path("/a/null"),
+                Error::new(ErrorCode::UnquotedKey, 1, 27).with_path("/a/x_1"),
+                Error::new(ErrorCode::UnquotedKey, 2, 3).with_path("/é"),
+                Error::new(ErrorCode::UnquotedKey, 2, 9).with_path("/$id"),
+                Error::new(ErrorCode::DuplicateKey, 2, 9)
+                    .with_path("/$id")
+                    .with_detail(r#""$id""#),
+            ],
+            warnings
+        );
+
+        assert!(Parser::parse(json).is_err());
+        let codes = |json| {
+            Parser::parse_with_options(json, options)
+                .unwrap_err()
+                .iter()
+                .map(Error::code)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec![ErrorCode::ExpectedToken], codes("[foo]"));
+        for json in ["{a-b: 1}", "{1: 1}"] {
+            assert_eq!(
+                Parser::parse(json).unwrap_err(),
+                Parser::parse_with_options(json, options).unwrap_err()
+            );
+        }
+    }
+
+    #[test]
+    fn report_dangerous_keys() {
+        let json =
+            "{\"a\": [{\"__proto__\": {\"isAdmin\": true}}],\n \"constructor\": 1, \"proto\": 2}";
+        assert!(Parser::parse_with_warnings(json).unwrap().1.is_empty());
+
+        let options = ParseOptions {
+            dangerous_keys: DangerousKeys::Warn,
+            ..ParseOptions::default()
+        };
+        let (value, warnings) = Parser::parse_with_options(json, options).unwrap();
+        assert_eq!(Parser::parse(json).unwrap(), value);
+        let expected = vec![
+            Error::new(ErrorCode::DangerousKey, 1, 9).with_path("/a/0/__proto__"),
+            Error::new(ErrorCode::DangerousKey, 2, 2).with_path("/constructor"),
+        ];
+        assert_eq!(expected, warnings);
+
+        let options = ParseOptions {
+            dangerous_keys: DangerousKeys::Reject,
+            ..ParseOptions::default()
+        };
+        assert_eq!(
+            expected
+                .into_iter()
+                .map(|error| error.with_severity(Severity::Error))
+                .collect::<Vec<Error>>(),
+            Parser::parse_with_options(json, options).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn limit_token_lengths() {
+        let options = ParseOptions {
+            max_string_len: Some(7),
+            max_number_len: Some(3),
+            ..ParseOptions::default()
+        };
+        assert!(Parser::parse_with_options(r#"{"key": "value", "n": 123}"#, options).is_ok());
+        assert_eq!(
+            vec![Error::new(ErrorCode::StringTooLong, 1, 9)
+                .with_path("/key")
+                .with_expected(Expected::VALUE)],
+            Parser::parse_with_options(r#"{"key": "values"}"#, options).unwrap_err()
+        );
+        assert_eq!(
+            vec![Error::new(ErrorCode::NumberTooLong, 1, 2)
+                .with_path("/0")
+                .with_expected(Expected::VALUE)],
+            Parser::parse_with_options("[1234]", options).unwrap_err()
+        );
+
+        let unterminated = format!("[\"a, {}]", "1, ".repeat(100));
+        let errors = Parser::parse_with_options(&unterminated, options).unwrap_err();
+        assert_eq!(ErrorCode::StringTooLong, errors[0].code());
+
+        let errors = Parser::parse_with_options(r#"{"abcdefg": 1, "b": 1234}"#, options)
+            .unwrap_err()
+            .iter()
+            .map(Error::to_string)
+            .collect::<Vec<String>>();
+        assert_eq!(
+            vec![
+                "1:2: String is longer than the limit",
+                "1:21: Number is longer than the limit at /b"
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn parse_leading_fragment() {
+        let template = r#" {"a": [1, "}"]} }} and {{ more "#;
+        let (value, consumed) = Parser::parse_fragment(template);
+        assert_eq!(r#"{"a":[1,"}"]}"#, value.unwrap().to_string());
+        assert_eq!(r#" {"a": [1, "}"]}"#, &template[..consumed]);
+
+        let (value, consumed) = Parser::parse_fragment("true, false");
+        assert_eq!((Ok(Value::Bool(true)), 4), (value, consumed));
+        let (value, consumed) = Parser::parse_fragment("[1, }");
+        assert_eq!(ErrorCode::ExpectedToken, value.unwrap_err()[0].code());
+        assert_eq!(5, consumed);
+        assert!(Parser::parse_fragment("").0.is_err());
+    }
+
+    #[test]
+    fn limit_depth_and_length() {
+        let options = ParseOptions {
+            limits: Limits {
+                max_depth: Some(2),
+                max_len: Some(2),
+            },
+            ..ParseOptions::default()
+        };
+        let parse = |json| Parser::parse_with_options(json, options);
+        assert!(parse(r#"{"a": [1, 2], "b": {}}"#).is_ok());
+
+        let errors = parse(r#"{"a": [[1, {"b": 2}], 3], "c": [4, 5, 6]}"#).unwrap_err();
+        assert_eq!(
+            vec![
+                (ErrorCode::NestingTooDeep, 8, "/a/0"),
+                (ErrorCode::ContainerTooLarge, 39, "/c"),
+            ],
+            errors
+                .iter()
+                .map(|e| (e.code(), e.col(), e.path()))
+                .collect::<Vec<_>>()
+        );
+
+        let deep = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        assert_eq!(
+            ErrorCode::NestingTooDeep,
+            parse(&deep).unwrap_err()[0].code()
+        );
+    }
+
+    #[test]
+    fn crlf_is_one_line_break() {
+        assert_eq!(
+            Err(vec![Error::new(
+                ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
+                3,
+                3
+            )
+            .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+            .with_found("3")]),
+            Parser::parse("[\r\n1,\r\n2 3]")
+        );
+        assert!(Parser::parse("[1,\u{0c}2]").is_err());
+    }
+
+    #[test]
+    fn decode_escaped_strings() {
+        assert_eq!(
+            Ok(Value::Array(vec![
+                Value::String("a\"b".to_string()),
+                Value::String("é/".to_string())
+            ])),
+            Parser::parse(r#"["a\"b", "\u00e9\/"]"#)
+        );
+
+        let json = r#"{"a\nb": 1}"#;
+        let mut unescaper = Unescaper::with_capacity(64);
+        let (value, _) = Parser::parse_with_unescaper(json, &mut unescaper).unwrap();
+        let Value::Object(members) = value else {
+            panic!("expected an object")
+        };
+        assert!(members.contains_key("a\nb"));
+
+        assert_eq!(
+            Err(vec![
+                Error::new(ErrorCode::InvalidEscape, 1, 2).with_path("/0")
+            ]),
+            Parser::parse(r#"["\x"]"#)
+        );
+    }
+
+    #[test]
+    fn parse_from_str() {
+        assert_eq!(
+            Ok(Value::Array(vec![Value::Number(1.0), Value::Number(2.0)])),
+            "[1,2]".parse::<Value>()
+        );
+
+        let error = "[,,]".parse::<Value>().unwrap_err();
+        assert_eq!(3, error.errors().len());
+        assert_eq!(
+            "1:2: Expected a value at /0 (and 2 more errors)",
+            error.to_string()
+        );
+    }
+
     #[test]
     fn fail_on_no_key() {
         assert_eq!(
-            Err(vec![Error::new(ErrorCode::KeyMustBeAString, 1, 3)]),
+            Err(vec![Error::new(ErrorCode::KeyMustBeAString, 1, 3)
+                .with_expected(Expected::KEY)
+                .with_found("':'")]),
             Parser::parse(r#"{ : true}"#)
         )
     }
@@ -496,18 +1681,83 @@ mod tests {
     #[test]
     fn fail_on_no_semi_colon() {
         assert_eq!(
-            Err(vec![Error::new(ErrorCode::ExpectedColon, 1, 2),]),
+            Err(vec![Error::new(ErrorCode::ExpectedColon, 1, 2)
+                .with_expected(Expected::COLON)
+                .with_found("'}'")]),
             Parser::parse(r#"{"a"}"#)
         )
     }
 
     #[test]
-    fn fail_on_multiple_quotes_in_one_token() {
+    fn fail_on_unterminated_key() {
+        assert_eq!(
+            Err(vec![
+                Error::new(ErrorCode::ExpectedDoubleQuote, 1, 2),
+                Error::new(ErrorCode::EndOfFileWhileParsing('}'), 1, 2)
+                    .with_expected(Expected::COMMA | Expected::END_OF_OBJECT),
+            ]),
+            Parser::parse(r#"{"a"#)
+        )
+    }
+
+    #[test]
+    fn resync_after_unterminated_string() {
+        let json = "{\n  \"a\": \"one,\n  \"b\": 2,\n  \"c\": tru\n}";
+        assert_eq!(
+            Err(vec![
+                Error::new(ErrorCode::UnterminatedString, 2, 8)
+                    .with_path("/a")
+                    .with_expected(Expected::VALUE),
+                Error::new(ErrorCode::ExpectedCommaOrEndWhileParsing('}'), 3, 3)
+                    .with_expected(Expected::COMMA | Expected::END_OF_OBJECT)
+                    .with_found(r#""b""#),
+                Error::new(ErrorCode::ExpectedToken, 4, 8)
+                    .with_path("/c")
+                    .with_expected(Expected::VALUE),
+            ]),
+            Parser::parse(json)
+        );
+    }
+
+    #[test]
+    fn fail_on_stray_colon() {
+        let error = |col| Error::new(ErrorCode::ExpectedToken, 1, col);
+        assert_eq!(
+            Err(vec![error(1).with_expected(Expected::VALUE)]),
+            Parser::parse(":")
+        );
+        assert_eq!(
+            Err(vec![error(2)
+                .with_path("/0")
+                .with_expected(Expected::VALUE)]),
+            Parser::parse("[:, 1]")
+        );
+        assert_eq!(
+            Err(vec![error(6)
+                .with_path("/a")
+                .with_expected(Expected::VALUE)]),
+            Parser::parse(r#"{"a"::}"#)
+        );
+    }
+
+    #[test]
+    fn fail_on_garbage_after_string() {
         let json = r#"
             "d"fds"potato"
         "#;
-        let expected = vec![Error::new(ErrorCode::ExpectedDoubleQuote, 2, 13)];
+        let expected =
+            vec![Error::new(ErrorCode::EndOfFileExpected, 2, 16)
+                .with_expected(Expected::END_OF_FILE)];
         assert_eq!(Err(expected), Parser::parse(json));
+        assert_eq!(
+            Err(vec![Error::new(
+                ErrorCode::ExpectedCommaOrEndWhileParsing(']'),
+                1,
+                5
+            )
+            .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)]),
+            Parser::parse(r#"["d"fds"potato", 1]"#)
+        );
     }
 
     #[test]
@@ -517,11 +1767,48 @@ mod tests {
         assert_eq!(Err(expected), Parser::parse(json));
     }
 
+    #[test]
+    fn fail_on_unescaped_control_character() {
+        let errors = Parser::parse("[\"a\u{1}b\"]").unwrap_err();
+        assert_eq!(
+            vec![Error::new(ErrorCode::ControlCharacter, 1, 2)
+                .with_path("/0")
+                .with_expected(Expected::VALUE)],
+            errors
+        );
+        assert_eq!(
+            "1:2: String contains an unescaped control character at /0",
+            errors[0].to_string()
+        );
+        assert_eq!(
+            Ok(Value::String("a\u{1}b".to_string())),
+            Parser::parse(r#""a\u0001b""#)
+        );
+    }
+
     #[test]
     fn fail_on_invalid_number() {
-        let json = r#"11.3de2"#;
-        let expected = vec![Error::new(ErrorCode::InvalidNumber, 1, 1)];
-        assert_eq!(Err(expected), Parser::parse(json));
+        for json in ["11.3de2", "01", "1.", "-inf"] {
+            let code = ErrorCode::invalid_number(json, Span::new(0, json.len(), 1, 1));
+            assert_eq!(Err(vec![Error::new(code, 1, 1)]), Parser::parse(json));
+        }
+
+        // The text is cut short, but the span covers all of it.
+        let number = format!("{}x", "1".repeat(100));
+        let json = format!("[\n {number}]");
+        let errors = Parser::parse(&json).unwrap_err();
+        assert_eq!(
+            format!("2:2: Invalid number: {}… at /0", "1".repeat(32)),
+            errors[0].to_string()
+        );
+        let ErrorCode::InvalidNumber(literal, span) = errors[0].code() else {
+            panic!("{:?} is not an invalid number", errors[0].code());
+        };
+        assert_eq!(
+            ("1".repeat(32).as_str(), true),
+            (literal.as_str(), literal.is_truncated())
+        );
+        assert_eq!(Span::new(3, 3 + number.len(), 2, 2), span);
     }
 
     #[test]
@@ -540,7 +1827,7 @@ mod tests {
                 ("a".to_string(), Value::Null),
                 (
                     "b".to_string(),
-                    Value::Array(vec![Value::Null, Value::Object(HashMap::new())]),
+                    Value::Array(vec![Value::Null, Value::Object(Map::new())]),
                 ),
             ]
             .into_iter()
@@ -0,0 +1,383 @@
+use std::{fmt, fmt::Display};
+
+use crate::{
+    errors::ErrorCode,
+    limits::{self, LimitError, Limits},
+    parsing::Value,
+    pointer::{parse_index, parse_pointer},
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum PatchErrorKind {
+    NotAPatch,
+    InvalidOperation,
+    PathNotFound,
+    TestFailed,
+    /// The operation left the document beyond its [`Limits`].
+    LimitExceeded(ErrorCode),
+}
+
+impl Display for PatchErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchErrorKind::NotAPatch => f.write_str("Patch must be an array of operations"),
+            PatchErrorKind::InvalidOperation => f.write_str("Invalid operation"),
+            PatchErrorKind::PathNotFound => f.write_str("Path not found"),
+            PatchErrorKind::TestFailed => f.write_str("Test failed"),
+            PatchErrorKind::LimitExceeded(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+/// The first operation of a patch that could not be applied.
+#[derive(Debug, PartialEq, Clone)]
+pub struct PatchError {
+    index: usize,
+    path: String,
+    kind: PatchErrorKind,
+}
+
+impl PatchError {
+    fn new(index: usize, path: &str, kind: PatchErrorKind) -> Self {
+        PatchError {
+            index,
+            path: path.to_string(),
+            kind,
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn kind(&self) -> PatchErrorKind {
+        self.kind
+    }
+}
+
+impl Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation {}: {}", self.index, self.kind)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Value {
+    /// Applies an RFC 6902 JSON Patch. Either every operation is applied or, on the first one
+    /// that fails, `self` is left unchanged.
+    pub fn apply_patch(&mut self, patch: &Value) -> Result<(), PatchError> {
+        self.apply_operations(patch, None)
+    }
+
+    /// Like [`Value::apply_patch`], but an operation that adds or replaces something beyond
+    /// `limits` fails the whole patch.
+    pub fn apply_patch_with_limits(
+        &mut self,
+        patch: &Value,
+        limits: &Limits,
+    ) -> Result<(), PatchError> {
+        self.apply_operations(patch, Some(limits))
+    }
+
+    fn apply_operations(
+        &mut self,
+        patch: &Value,
+        limits: Option<&Limits>,
+    ) -> Result<(), PatchError> {
+        let Value::Array(operations) = patch else {
+            return Err(PatchError::new(0, "", PatchErrorKind::NotAPatch));
+        };
+
+        let mut patched = self.clone();
+        for (index, operation) in operations.iter().enumerate() {
+            apply(&mut patched, operation)
+                .map_err(|(path, kind)| PatchError::new(index, path, kind))?;
+            if let Some(limits) = limits {
+                check(&patched, operation, limits).map_err(|e| {
+                    PatchError::new(index, e.path(), PatchErrorKind::LimitExceeded(e.code()))
+                })?;
+            }
+        }
+        *self = patched;
+        Ok(())
+    }
+}
+
+fn apply<'a>(doc: &mut Value, operation: &'a Value) -> Result<(), (&'a str, PatchErrorKind)> {
+    let Value::Object(members) = operation else {
+        return Err(("", PatchErrorKind::InvalidOperation));
+    };
+    let string = |key: &str| match members.get(key) {
+        Some(Value::String(s)) => Ok(s.as_str()),
+        _ => Err(("", PatchErrorKind::InvalidOperation)),
+    };
+    let value = || {
+        members
+            .get("value")
+            .ok_or(("", PatchErrorKind::InvalidOperation))
+    };
+
+    let path = string("path")?;
+    let at_path = |kind| (path, kind);
+    match string("op")? {
+        "add" => add(doc, path, value()?.clone()).map_err(at_path),
+        "remove" => remove(doc, path).map(|_| ()).map_err(at_path),
+        "replace" => {
+            let target = doc
+                .pointer_mut(path)
+                .ok_or(at_path(PatchErrorKind::PathNotFound))?;
+            *target = value()?.clone();
+            Ok(())
+        }
+        "move" => {
+            let from = string("from")?;
+            if path.starts_with(from) && path[from.len()..].starts_with('/') {
+                return Err((path, PatchErrorKind::InvalidOperation));
+            }
+            let moved = remove(doc, from).map_err(|kind| (from, kind))?;
+            add(doc, path, moved).map_err(at_path)
+        }
+        "copy" => {
+            let from = string("from")?;
+            let copied = doc
+                .pointer(from)
+                .ok_or((from, PatchErrorKind::PathNotFound))?
+                .clone();
+            add(doc, path, copied).map_err(at_path)
+        }
+        "test" => match doc.pointer(path) {
+            Some(target) if target == value()? => Ok(()),
+            Some(_) => Err(at_path(PatchErrorKind::TestFailed)),
+            None => Err(at_path(PatchErrorKind::PathNotFound)),
+        },
+        _ => Err(("", PatchErrorKind::InvalidOperation)),
+    }
+}
+
+/// Checks the container an applied operation wrote into, and the value it wrote.
+fn check(doc: &Value, operation: &Value, limits: &Limits) -> Result<(), LimitError> {
+    let string = |key: &str| match operation.child(key) {
+        Some(Value::String(s)) => s.as_str(),
+        _ => "",
+    };
+    if !matches!(string("op"), "add" | "replace" | "move" | "copy") {
+        return Ok(());
+    }
+    let path = string("path");
+    let Some((parent_path, last)) = path.rsplit_once('/') else {
+        return doc.check_limits(limits);
+    };
+    let depth = parse_pointer(parent_path).map_or(0, |tokens| tokens.len());
+    let Some(parent) = doc.pointer(parent_path) else {
+        return Ok(());
+    };
+
+    let mut path = path.to_string();
+    let len = match parent {
+        Value::Array(elements) => {
+            if last == "-" {
+                path = format!("{parent_path}/{}", elements.len() - 1);
+            }
+            elements.len()
+        }
+        Value::Object(members) => members.len(),
+        _ => return Ok(()),
+    };
+    limits.check_container(depth + 1, len, parent_path)?;
+    match doc.pointer(&path) {
+        Some(child) => limits::check(child, depth + 1, &mut path, limits),
+        None => Ok(()),
+    }
+}
+
+/// Returns the container the last token of `path` refers into, and that token.
+fn parent<'v>(doc: &'v mut Value, path: &str) -> Option<(&'v mut Value, String)> {
+    let mut tokens = parse_pointer(path)?;
+    let last = tokens.pop()?;
+    let parent = tokens
+        .iter()
+        .try_fold(doc, |value, token| value.child_mut(token))?;
+    Some((parent, last))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> Result<(), PatchErrorKind> {
+    if path.is_empty() {
+        *doc = value;
+        return Ok(());
+    }
+    match parent(doc, path).ok_or(PatchErrorKind::PathNotFound)? {
+        (Value::Object(members), key) => {
+            members.insert(key, value);
+            Ok(())
+        }
+        (Value::Array(elements), token) => {
+            let index = match token.as_str() {
+                "-" => elements.len(),
+                token => parse_index(token)
+                    .filter(|i| *i <= elements.len())
+                    .ok_or(PatchErrorKind::PathNotFound)?,
+            };
+            elements.insert(index, value);
+            Ok(())
+        }
+        _ => Err(PatchErrorKind::PathNotFound),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> Result<Value, PatchErrorKind> {
+    match parent(doc, path).ok_or(PatchErrorKind::PathNotFound)? {
+        (Value::Object(members), key) => members.remove(&key).ok_or(PatchErrorKind::PathNotFound),
+        (Value::Array(elements), token) => match parse_index(&token) {
+            Some(i) if i < elements.len() => Ok(elements.remove(i)),
+            _ => Err(PatchErrorKind::PathNotFound),
+        },
+        _ => Err(PatchErrorKind::PathNotFound),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn patched(doc: &str, patch: &str) -> Result<String, PatchError> {
+        let mut value = Parser::parse(doc).unwrap();
+        value.apply_patch(&Parser::parse(patch).unwrap())?;
+        Ok(value.to_string())
+    }
+
+    #[test]
+    fn apply_operations() {
+        let patch = r#"[
+            {"op": "add", "path": "/b", "value": [1]},
+            {"op": "add", "path": "/b/0", "value": 0},
+            {"op": "add", "path": "/b/-", "value": 2},
+            {"op": "remove", "path": "/a/x"},
+            {"op": "replace", "path": "/a/y", "value": "new"},
+            {"op": "copy", "from": "/b", "path": "/c"},
+            {"op": "move", "from": "/a/y", "path": "/d"},
+            {"op": "test", "path": "/c/1", "value": 1}
+        ]"#;
+        assert_eq!(
+            Ok(r#"{"a":{},"b":[0,1,2],"c":[0,1,2],"d":"new"}"#.to_string()),
+            patched(r#"{"a": {"x": 1, "y": 2}}"#, patch)
+        );
+        assert_eq!(
+            Ok("[1]".to_string()),
+            patched("{}", r#"[{"op": "add", "path": "", "value": [1]}]"#)
+        );
+    }
+
+    #[test]
+    fn fail_with_operation_index_and_path() {
+        let patch = r#"[
+            {"op": "add", "path": "/b", "value": 1},
+            {"op": "test", "path": "/a", "value": 2}
+        ]"#;
+        let mut value = Parser::parse(r#"{"a": 1}"#).unwrap();
+        let error = value
+            .apply_patch(&Parser::parse(patch).unwrap())
+            .unwrap_err();
+        assert_eq!(
+            (1, "/a", PatchErrorKind::TestFailed),
+            (error.index(), error.path(), error.kind())
+        );
+        assert_eq!("operation 1: Test failed at /a", error.to_string());
+        assert_eq!(r#"{"a":1}"#, value.to_string());
+
+        let failures = [
+            (
+                r#"{"op": "remove", "path": "/missing"}"#,
+                "/missing",
+                PatchErrorKind::PathNotFound,
+            ),
+            (
+                r#"{"op": "add", "path": "/a/5", "value": 0}"#,
+                "/a/5",
+                PatchErrorKind::PathNotFound,
+            ),
+            (
+                r#"{"op": "move", "from": "/a", "path": "/a/0"}"#,
+                "/a/0",
+                PatchErrorKind::InvalidOperation,
+            ),
+            (
+                r#"{"op": "copy", "from": "/x", "path": "/y"}"#,
+                "/x",
+                PatchErrorKind::PathNotFound,
+            ),
+            (
+                r#"{"op": "add", "path": "/b"}"#,
+                "",
+                PatchErrorKind::InvalidOperation,
+            ),
+            (
+                r#"{"op": "frobnicate", "path": "/a"}"#,
+                "",
+                PatchErrorKind::InvalidOperation,
+            ),
+        ];
+        for (operation, path, kind) in failures {
+            let error = patched(r#"{"a": [1]}"#, &format!("[{operation}]")).unwrap_err();
+            assert_eq!(
+                (0, path, kind),
+                (error.index(), error.path(), error.kind()),
+                "{operation}"
+            );
+        }
+        assert_eq!(
+            Err(PatchError::new(0, "", PatchErrorKind::NotAPatch)),
+            patched("{}", "{}")
+        );
+    }
+
+    #[test]
+    fn fail_beyond_limits() {
+        let limits = Limits {
+            max_depth: Some(2),
+            max_len: Some(2),
+        };
+        let patch = |patch: &str| Parser::parse(patch).unwrap();
+        let mut value = Parser::parse(r#"{"a": [1], "b": {}}"#).unwrap();
+
+        value
+            .apply_patch_with_limits(
+                &patch(r#"[{"op": "add", "path": "/a/-", "value": 2}]"#),
+                &limits,
+            )
+            .unwrap();
+        let error = value
+            .apply_patch_with_limits(
+                &patch(r#"[{"op": "add", "path": "/a/-", "value": 3}]"#),
+                &limits,
+            )
+            .unwrap_err();
+        assert_eq!(
+            "operation 0: Container has more items than the limit at /a",
+            error.to_string()
+        );
+        let error = value
+            .apply_patch_with_limits(
+                &patch(r#"[{"op": "copy", "from": "/a", "path": "/b/c"}]"#),
+                &limits,
+            )
+            .unwrap_err();
+        assert_eq!(
+            (
+                "/b/c",
+                PatchErrorKind::LimitExceeded(ErrorCode::NestingTooDeep)
+            ),
+            (error.path(), error.kind())
+        );
+        assert_eq!(r#"{"a":[1,2],"b":{}}"#, value.to_string());
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Write;
+
+use crate::parsing::{escape_key, Value};
+
+/// Splits an RFC 6901 JSON pointer into its unescaped reference tokens. The empty pointer
+/// refers to the whole document.
+pub fn parse_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(Vec::<String>::new());
+    }
+    let tokens = pointer.strip_prefix('/')?;
+    Some(
+        tokens
+            .split('/')
+            .map(|token| token.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Reads an array index token, which must not have leading zeros.
+pub(crate) fn parse_index(token: &str) -> Option<usize> {
+    if token.is_empty() || (token.len() > 1 && token.starts_with('0')) {
+        return None;
+    }
+    if !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    token.parse().ok()
+}
+
+impl Value {
+    /// Looks up the value that a JSON pointer such as `/statuses/0/id` refers to.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| value.child(token))
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| value.child_mut(token))
+    }
+
+    /// The JSON pointer of every scalar and empty container, with the value, in document
+    /// order.
+    pub fn leaves(&self) -> Vec<(String, &Value)> {
+        let mut leaves = Vec::new();
+        collect_leaves(self, &mut String::new(), &mut leaves);
+        leaves
+    }
+
+    pub(crate) fn child(&self, token: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.get(token),
+            Value::Array(elements) => elements.get(parse_index(token)?),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn child_mut(&mut self, token: &str) -> Option<&mut Value> {
+        match self {
+            Value::Object(members) => members.get_mut(token),
+            Value::Array(elements) => elements.get_mut(parse_index(token)?),
+            _ => None,
+        }
+    }
+}
+
+fn collect_leaves<'a>(value: &'a Value, path: &mut String, leaves: &mut Vec<(String, &'a Value)>) {
+    let end = path.len();
+    match value {
+        Value::Array(elements) if !elements.is_empty() => {
+            for (i, element) in elements.iter().enumerate() {
+                write!(path, "/{i}").unwrap();
+                collect_leaves(element, path, leaves);
+                path.truncate(end);
+            }
+        }
+        Value::Object(members) if !members.is_empty() => {
+            for (key, member) in members {
+                write!(path, "/{}", escape_key(key)).unwrap();
+                collect_leaves(member, path, leaves);
+                path.truncate(end);
+            }
+        }
+        _ => leaves.push((path.clone(), value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn resolve_pointers() {
+        let value = Parser::parse(r#"{"a/b": [1, {"m~n": true}], "": 2, "01": 3}"#).unwrap();
+        assert_eq!(Some(&value), value.pointer(""));
+        assert_eq!(Some(&Value::Number(2.0)), value.pointer("/"));
+        assert_eq!(Some(&Value::Number(1.0)), value.pointer("/a~1b/0"));
+        assert_eq!(Some(&Value::Bool(true)), value.pointer("/a~1b/1/m~0n"));
+        assert_eq!(Some(&Value::Number(3.0)), value.pointer("/01"));
+        assert_eq!(None, value.pointer("/a~1b/01"));
+        assert_eq!(None, value.pointer("/a~1b/2"));
+        assert_eq!(None, value.pointer("a"));
+
+        let mut value = value;
+        *value.pointer_mut("/a~1b/0").unwrap() = Value::Null;
+        assert_eq!(Some(&Value::Null), value.pointer("/a~1b/0"));
+    }
+
+    #[test]
+    fn list_leaves() {
+        let value = Parser::parse(r#"[{"a/b": [1, []], "c": {}}, "x"]"#).unwrap();
+        let pointers = value
+            .leaves()
+            .into_iter()
+            .map(|(pointer, _)| pointer)
+            .collect::<Vec<String>>();
+        assert_eq!(vec!["/0/a~1b/0", "/0/a~1b/1", "/0/c", "/1"], pointers);
+        assert_eq!(vec![(String::new(), &Value::Null)], Value::Null.leaves());
+    }
+}
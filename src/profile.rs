@@ -0,0 +1,219 @@
+use std::{fmt, fmt::Display};
+
+use indexmap::IndexMap;
+
+use crate::parsing::Value;
+
+const KINDS: [&str; 6] = ["null", "bool", "number", "string", "array", "object"];
+
+fn kind(value: &Value) -> usize {
+    match value {
+        Value::Null => 0,
+        Value::Bool(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        #[cfg(feature = "bytes")]
+        Value::Bytes(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+    }
+}
+
+/// How many records had a field, and how many had each type there.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct FieldStats {
+    present: usize,
+    kinds: [usize; 6],
+}
+
+impl FieldStats {
+    pub fn present(&self) -> usize {
+        self.present
+    }
+
+    pub fn nulls(&self) -> usize {
+        self.kinds[0]
+    }
+
+    pub fn kinds(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        KINDS
+            .iter()
+            .zip(self.kinds)
+            .filter(|(_, count)| *count > 0)
+            .map(|(kind, count)| (*kind, count))
+    }
+}
+
+/// A way in which a record differs from the records before it.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Drift {
+    NewField(String),
+    MissingField(String),
+    NewType(String, &'static str),
+}
+
+impl Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Drift::NewField(path) => write!(f, "new field {path}"),
+            Drift::MissingField(path) => write!(f, "missing field {path}"),
+            Drift::NewType(path, kind) => write!(f, "new type {kind} at {path}"),
+        }
+    }
+}
+
+/// The fields seen across a stream of records, by path such as `$.user.id` or `$.tags[*]`,
+/// in the order they first appeared.
+#[derive(Debug, Default)]
+pub struct Profile {
+    records: usize,
+    fields: IndexMap<String, FieldStats>,
+}
+
+impl Profile {
+    pub fn new() -> Self {
+        Profile::default()
+    }
+
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    pub fn fields(&self) -> impl Iterator<Item = (&str, &FieldStats)> {
+        self.fields
+            .iter()
+            .map(|(path, stats)| (path.as_str(), stats))
+    }
+
+    /// Adds a record and reports how it differs from the records before it: fields that were
+    /// never seen, fields that every earlier record had, and types never seen at a field.
+    pub fn add(&mut self, record: &Value) -> Vec<Drift> {
+        let mut seen = IndexMap::<String, [bool; 6]>::new();
+        collect(record, "$".to_string(), &mut seen);
+
+        let mut drift = Vec::<Drift>::new();
+        if self.records > 0 {
+            for (path, stats) in &self.fields {
+                if stats.present == self.records && !seen.contains_key(path) {
+                    drift.push(Drift::MissingField(path.clone()));
+                }
+            }
+        }
+        for (path, kinds) in seen {
+            if self.records > 0 {
+                match self.fields.get(&path) {
+                    None => drift.push(Drift::NewField(path.clone())),
+                    Some(stats) => drift.extend(
+                        (0..KINDS.len())
+                            .filter(|&i| kinds[i] && stats.kinds[i] == 0)
+                            .map(|i| Drift::NewType(path.clone(), KINDS[i])),
+                    ),
+                }
+            }
+            let stats = self.fields.entry(path).or_default();
+            stats.present += 1;
+            for (count, seen) in stats.kinds.iter_mut().zip(kinds) {
+                *count += usize::from(seen);
+            }
+        }
+        self.records += 1;
+        drift
+    }
+}
+
+fn collect(value: &Value, path: String, seen: &mut IndexMap<String, [bool; 6]>) {
+    seen.entry(path.clone()).or_default()[kind(value)] = true;
+    match value {
+        Value::Object(members) => {
+            for (key, value) in members.iter() {
+                collect(value, child_path(&path, key), seen);
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                collect(element, format!("{path}[*]"), seen);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn child_path(path: &str, key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        format!("{path}.{key}")
+    } else {
+        format!("{path}['{key}']")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn add(profile: &mut Profile, record: &str) -> Vec<String> {
+        let drift = profile.add(&Parser::parse(record).unwrap());
+        drift.iter().map(Drift::to_string).collect()
+    }
+
+    /// The drift `record` adds, in sorted order since the order of fields depends on the map.
+    fn add_sorted(profile: &mut Profile, record: &str) -> Vec<String> {
+        let mut drift = add(profile, record);
+        drift.sort();
+        drift
+    }
+
+    #[test]
+    fn count_fields_and_types() {
+        let mut profile = Profile::new();
+        for record in [
+            r#"{"id": 1, "tags": ["a", "b"], "user name": null}"#,
+            r#"{"id": 2, "tags": [], "user name": "x"}"#,
+            r#"{"id": 3, "tags": [1]}"#,
+            r#"{"id": 4}"#,
+        ] {
+            add(&mut profile, record);
+        }
+        assert_eq!(4, profile.records());
+
+        let fields: Vec<(&str, usize, usize, Vec<&str>)> = profile
+            .fields()
+            .map(|(path, stats)| {
+                let kinds = stats.kinds().map(|(kind, _)| kind).collect();
+                (path, stats.present(), stats.nulls(), kinds)
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("$", 4, 0, vec!["object"]),
+                ("$.id", 4, 0, vec!["number"]),
+                ("$.tags", 3, 0, vec!["array"]),
+                ("$.tags[*]", 2, 0, vec!["number", "string"]),
+                ("$['user name']", 2, 1, vec!["null", "string"]),
+            ],
+            fields
+        );
+    }
+
+    #[test]
+    fn report_drift_from_earlier_records() {
+        let mut profile = Profile::new();
+        assert!(add(&mut profile, r#"{"id": 1, "name": "a"}"#).is_empty());
+        assert!(add(&mut profile, r#"{"id": 2, "name": "b"}"#).is_empty());
+        assert_eq!(
+            vec![
+                "missing field $.name",
+                "new field $.email",
+                "new type string at $.id",
+            ],
+            add_sorted(&mut profile, r#"{"id": "3", "email": null}"#)
+        );
+        assert!(add(&mut profile, r#"{"id": 4, "email": null}"#).is_empty());
+        assert_eq!(
+            vec!["missing field $.id", "new type array at $"],
+            add_sorted(&mut profile, "[]")
+        );
+    }
+}
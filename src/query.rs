@@ -0,0 +1,209 @@
+use crate::{
+    errors::Error,
+    map::Map,
+    parsing::{Float, Value},
+    pointer::{parse_index, parse_pointer},
+    stream::{Event, StreamParser},
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Step {
+    /// An object member, or an array element when the key is an index as in JSON pointers.
+    Key(String),
+    Index(usize),
+}
+
+impl Step {
+    fn matches_key(&self, key: &str) -> bool {
+        matches!(self, Step::Key(k) if k == key)
+    }
+
+    fn index(&self) -> Option<usize> {
+        match self {
+            Step::Key(key) => parse_index(key),
+            Step::Index(index) => Some(*index),
+        }
+    }
+}
+
+/// Parses either a JSON pointer such as `/user/id` or a path such as `$.user.id`,
+/// `$.statuses[0]`, or `$['user name']`.
+pub fn parse_query(query: &str) -> Option<Vec<Step>> {
+    let Some(mut rest) = query.strip_prefix('$') else {
+        return Some(parse_pointer(query)?.into_iter().map(Step::Key).collect());
+    };
+
+    let mut steps = Vec::<Step>::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return None;
+            }
+            steps.push(Step::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']')?;
+            let inner = &after[..end];
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+            steps.push(match quoted {
+                Some(key) => Step::Key(key.to_string()),
+                None => Step::Index(parse_index(inner)?),
+            });
+            rest = &after[end + 1..];
+        } else {
+            return None;
+        }
+    }
+    Some(steps)
+}
+
+/// Reads the value that `query` selects, skipping everything before it without building it.
+/// Returns `None` if the document has no such value. The rest of the document after the
+/// selected value is not read.
+pub fn select(mut reader: StreamParser, query: &[Step]) -> Result<Option<Value>, Error> {
+    match seek(&mut reader, query)? {
+        Some(event) => build(event, &mut reader).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Skips ahead to the value that `query` selects and returns its first event.
+pub(crate) fn seek(reader: &mut StreamParser, query: &[Step]) -> Result<Option<Event>, Error> {
+    let Some(mut event) = next(reader)? else {
+        return Ok(None);
+    };
+    for step in query {
+        event = match event {
+            Event::StartObject => loop {
+                match next(reader)? {
+                    Some(Event::Key(key)) if step.matches_key(&key) => {
+                        break required(reader)?;
+                    }
+                    Some(Event::Key(_)) => reader.skip_value()?,
+                    _ => return Ok(None),
+                }
+            },
+            Event::StartArray => {
+                let Some(index) = step.index() else {
+                    return Ok(None);
+                };
+                for _ in 0..index {
+                    reader.skip_value()?;
+                }
+                match next(reader)? {
+                    Some(Event::EndArray) | None => return Ok(None),
+                    Some(event) => event,
+                }
+            }
+            _ => return Ok(None),
+        };
+    }
+    Ok(Some(event))
+}
+
+fn next(reader: &mut StreamParser) -> Result<Option<Event>, Error> {
+    reader
+        .next_event()
+        .transpose()
+        .map(|event| event.map(|(event, _)| event))
+}
+
+/// The stream parser reports an error rather than ending inside a container.
+fn required(reader: &mut StreamParser) -> Result<Event, Error> {
+    Ok(next(reader)?.expect("a container to be closed before the stream ends"))
+}
+
+pub(crate) fn build(first: Event, reader: &mut StreamParser) -> Result<Value, Error> {
+    let value = match first {
+        Event::Null => Value::Null,
+        Event::Bool(b) => Value::Bool(b),
+        Event::Number(n) => Value::Number(n as Float),
+        Event::String(s) => Value::String(s),
+        Event::StartArray => {
+            let mut elements = Vec::<Value>::new();
+            loop {
+                match required(reader)? {
+                    Event::EndArray => break,
+                    event => elements.push(build(event, reader)?),
+                }
+            }
+            Value::Array(elements)
+        }
+        Event::StartObject => {
+            let mut members = Map::new();
+            while let Event::Key(key) = required(reader)? {
+                let event = required(reader)?;
+                members.insert(key, build(event, reader)?);
+            }
+            Value::Object(members)
+        }
+        event => unreachable!("{event:?} cannot start a value"),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    fn get(json: &str, query: &str) -> Result<Option<String>, Error> {
+        let query = parse_query(query).unwrap();
+        select(StreamParser::new(json), &query).map(|value| value.map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn parse_paths_and_pointers() {
+        assert_eq!(Some(vec![]), parse_query("$"));
+        assert_eq!(
+            Some(vec![
+                Step::Key("user".to_string()),
+                Step::Index(2),
+                Step::Key("a b".to_string()),
+                Step::Key("c".to_string())
+            ]),
+            parse_query("$.user[2]['a b'].c")
+        );
+        assert_eq!(
+            Some(vec![
+                Step::Key("a/b".to_string()),
+                Step::Key("0".to_string())
+            ]),
+            parse_query("/a~1b/0")
+        );
+        for query in ["$.", "$[01]", "$[x]", "$a", "a"] {
+            assert_eq!(None, parse_query(query), "{query}");
+        }
+    }
+
+    #[test]
+    fn select_nested_values() {
+        let json = r#"{"skip": [1, {"x": 2}], "user": {"id": 7, "tags": ["a", {"b": null}]}}"#;
+        assert_eq!(Ok(Some("7".to_string())), get(json, "$.user.id"));
+        assert_eq!(
+            Ok(Some(r#"{"b":null}"#.to_string())),
+            get(json, "/user/tags/1")
+        );
+        assert_eq!(
+            Ok(Some(r#"["a",{"b":null}]"#.to_string())),
+            get(json, "$.user.tags")
+        );
+        assert_eq!(Ok(None), get(json, "$.user.tags[2]"));
+        assert_eq!(Ok(None), get(json, "$.user.id.x"));
+        assert_eq!(Ok(None), get(json, "$.missing"));
+        assert_eq!(Ok(Some("1".to_string())), get(json, "$.skip[0]"));
+    }
+
+    #[test]
+    fn fail_on_invalid_input() {
+        assert_eq!(
+            Err(ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            get(r#"{"a": 1, "b": [1 2]}"#, "$.b").map_err(|e| e.code())
+        );
+        assert!(get(r#"{"a": }"#, "$.b").is_err());
+    }
+}
@@ -0,0 +1,200 @@
+use crate::{
+    array::ArrayError,
+    errors::Error,
+    parsing::Value,
+    query::{self, Step},
+    stream::{Event, StreamParser},
+};
+
+impl Value {
+    /// Picks `n` elements of the array at `pointer` uniformly at random, keeping their order.
+    /// The same seed always picks the same elements, also when sampling with [`sample_array`].
+    pub fn sample_array(&self, pointer: &str, n: usize, seed: u64) -> Result<Value, ArrayError> {
+        let elements = match self.pointer(pointer) {
+            Some(Value::Array(elements)) => elements,
+            Some(_) => return Err(ArrayError::NotAnArray(pointer.to_string())),
+            None => return Err(ArrayError::NotFound(pointer.to_string())),
+        };
+
+        let mut reservoir = Reservoir::new(n, seed);
+        for element in elements {
+            if let Some(slot) = reservoir.slot() {
+                reservoir.store(slot, element.clone());
+            }
+        }
+        Ok(Value::Array(reservoir.into_sorted()))
+    }
+}
+
+/// Samples the array that `query` selects like [`Value::sample_array`], but only builds the
+/// elements that are picked. Returns `None` if the document has no array there.
+pub fn sample_array(
+    mut reader: StreamParser,
+    query: &[Step],
+    n: usize,
+    seed: u64,
+) -> Result<Option<Value>, Error> {
+    let Some(Event::StartArray) = query::seek(&mut reader, query)? else {
+        return Ok(None);
+    };
+
+    let mut reservoir = Reservoir::new(n, seed);
+    while !reader.at_container_end() {
+        match reservoir.slot() {
+            Some(slot) => match reader.next_event().transpose()? {
+                Some((event, _)) => reservoir.store(slot, query::build(event, &mut reader)?),
+                None => break,
+            },
+            None => reader.skip_value()?,
+        }
+    }
+    reader.next_event().transpose()?;
+    Ok(Some(Value::Array(reservoir.into_sorted())))
+}
+
+/// Reservoir sampling (Algorithm R) over a sequence whose length is not known up front.
+struct Reservoir<T> {
+    size: usize,
+    seen: usize,
+    rng: Rng,
+    kept: Vec<(usize, T)>,
+}
+
+impl<T> Reservoir<T> {
+    fn new(size: usize, seed: u64) -> Self {
+        Reservoir {
+            size,
+            seen: 0,
+            rng: Rng(seed),
+            kept: Vec::<(usize, T)>::new(),
+        }
+    }
+
+    /// Decides whether the next item is kept, before it is read, and where.
+    fn slot(&mut self) -> Option<usize> {
+        let index = self.seen;
+        self.seen += 1;
+        if index < self.size {
+            return Some(index);
+        }
+        let slot = self.rng.below(index + 1);
+        (slot < self.size).then_some(slot)
+    }
+
+    fn store(&mut self, slot: usize, item: T) {
+        let entry = (self.seen - 1, item);
+        if slot == self.kept.len() {
+            self.kept.push(entry);
+        } else {
+            self.kept[slot] = entry;
+        }
+    }
+
+    fn into_sorted(mut self) -> Vec<T> {
+        self.kept.sort_by_key(|(index, _)| *index);
+        self.kept.into_iter().map(|(_, item)| item).collect()
+    }
+}
+
+/// SplitMix64, so that samples are the same on every platform and release.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        errors::ErrorCode,
+        parsing::{Float, Parser},
+        query::parse_query,
+    };
+
+    fn numbers(count: usize) -> String {
+        let elements: Vec<String> = (0..count)
+            .map(|i| format!(r#"{{"id": {i}, "tags": ["t{i}"]}}"#))
+            .collect();
+        format!(r#"{{"items": [{}]}}"#, elements.join(", "))
+    }
+
+    fn stream(json: &str, query: &str, n: usize, seed: u64) -> Result<Option<Value>, Error> {
+        sample_array(
+            StreamParser::new(json),
+            &parse_query(query).unwrap(),
+            n,
+            seed,
+        )
+    }
+
+    #[test]
+    fn sample_reproducibly_in_order() {
+        let json = numbers(1000);
+        let value = Parser::parse(&json).unwrap();
+        let sample = value.sample_array("/items", 10, 7).unwrap();
+        let Value::Array(elements) = &sample else {
+            panic!("not an array");
+        };
+        let ids: Vec<Float> = elements
+            .iter()
+            .map(|e| match e.pointer("/id") {
+                Some(Value::Number(id)) => *id,
+                _ => panic!("no id"),
+            })
+            .collect();
+        assert_eq!(10, ids.len());
+        assert!(ids.windows(2).all(|w| w[0] < w[1]), "{ids:?}");
+
+        assert_eq!(sample, value.sample_array("/items", 10, 7).unwrap());
+        assert_ne!(sample, value.sample_array("/items", 10, 8).unwrap());
+        assert_eq!(Ok(Some(sample)), stream(&json, "/items", 10, 7));
+    }
+
+    #[test]
+    fn sample_everything_from_short_arrays() {
+        let value = Parser::parse("[1, 2, 3]").unwrap();
+        assert_eq!(Ok(value.clone()), value.sample_array("", 5, 0));
+        assert_eq!(Ok(Some(value)), stream("[1, 2, 3]", "", 5, 0));
+        assert_eq!(
+            Ok(Some(Value::Array(Vec::<Value>::new()))),
+            stream("[]", "$", 5, 0)
+        );
+        assert_eq!(
+            Ok(Value::Array(Vec::<Value>::new())),
+            Parser::parse("[1]").unwrap().sample_array("", 0, 0)
+        );
+    }
+
+    #[test]
+    fn fail_without_array() {
+        let value = Parser::parse(r#"{"a": 1}"#).unwrap();
+        assert_eq!(
+            Err(ArrayError::NotAnArray("/a".to_string())),
+            value.sample_array("/a", 1, 0)
+        );
+        assert_eq!(
+            Err(ArrayError::NotFound("/b".to_string())),
+            value.sample_array("/b", 1, 0)
+        );
+        assert_eq!(Ok(None), stream(r#"{"a": 1}"#, "/a", 1, 0));
+        assert_eq!(
+            Err(ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            stream("[1, 2 3, 4]", "", 1, 0).map_err(|e| e.code())
+        );
+        assert_eq!(
+            Err(ErrorCode::EndOfFileWhileParsing(']')),
+            stream("[1, 2", "", 1, 0).map_err(|e| e.code())
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    errors::{Error, ErrorCode},
+    map::Map,
+    parsing::{Float, Value},
+};
+
+const SARIF_VERSION: &str = "2.1.0";
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+pub fn report(files: &[(&str, &[Error])]) -> Value {
+    let mut rules = Vec::<ErrorCode>::new();
+    let mut results = Vec::<Value>::new();
+
+    for (uri, errors) in files {
+        for error in errors.iter() {
+            let rule_index = match rules.iter().position(|r| r.id() == error.code().id()) {
+                Some(index) => index,
+                None => {
+                    rules.push(error.code());
+                    rules.len() - 1
+                }
+            };
+            results.push(result(uri, rule_index, error));
+        }
+    }
+
+    let driver = object(vec![
+        ("name", string(env!("CARGO_PKG_NAME"))),
+        ("version", string(env!("CARGO_PKG_VERSION"))),
+        ("rules", Value::Array(rules.iter().map(rule).collect())),
+    ]);
+    let run = object(vec![
+        ("tool", object(vec![("driver", driver)])),
+        ("results", Value::Array(results)),
+    ]);
+
+    object(vec![
+        ("$schema", string(SARIF_SCHEMA)),
+        ("version", string(SARIF_VERSION)),
+        ("runs", Value::Array(vec![run])),
+    ])
+}
+
+fn rule(code: &ErrorCode) -> Value {
+    object(vec![
+        ("id", string(code.id())),
+        ("name", string(code.name())),
+        (
+            "shortDescription",
+            object(vec![("text", string(&code.to_string()))]),
+        ),
+    ])
+}
+
+fn result(uri: &str, rule_index: usize, error: &Error) -> Value {
+    let region = object(vec![
+        ("startLine", Value::Number(error.line() as Float)),
+        ("startColumn", Value::Number(error.col() as Float)),
+    ]);
+    let physical_location = object(vec![
+        ("artifactLocation", object(vec![("uri", string(uri))])),
+        ("region", region),
+    ]);
+
+    let mut location = vec![("physicalLocation", physical_location)];
+    if !error.path().is_empty() {
+        location.push((
+            "logicalLocations",
+            Value::Array(vec![object(vec![(
+                "fullyQualifiedName",
+                string(error.path()),
+            )])]),
+        ));
+    }
+
+    object(vec![
+        ("ruleId", string(error.code().id())),
+        ("ruleIndex", Value::Number(rule_index as Float)),
+        ("level", string(&error.severity().to_string())),
+        ("message", object(vec![("text", string(&error.message()))])),
+        ("locations", Value::Array(vec![object(location)])),
+    ])
+}
+
+fn object(members: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        members
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<Map>(),
+    )
+}
+
+fn string(s: &str) -> Value {
+    Value::String(s.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexical::Span;
+
+    fn get<'a>(value: &'a Value, key: &str) -> &'a Value {
+        match value {
+            Value::Object(members) => &members[key],
+            _ => panic!("{value:?} is not an object"),
+        }
+    }
+
+    fn index(value: &Value, i: usize) -> &Value {
+        match value {
+            Value::Array(elements) => &elements[i],
+            _ => panic!("{value:?} is not an array"),
+        }
+    }
+
+    #[test]
+    fn report_results_and_rules() {
+        let errors = vec![
+            Error::new(ErrorCode::ExpectedColon, 1, 2),
+            Error::new(
+                ErrorCode::invalid_number("1x", Span::new(20, 22, 3, 4)),
+                3,
+                4,
+            ),
+            Error::new(ErrorCode::ExpectedColon, 5, 6).with_path("/a/0"),
+        ];
+        let log = report(&[("data.json", &errors)]);
+        assert_eq!(&string("2.1.0"), get(&log, "version"));
+
+        let run = index(get(&log, "runs"), 0);
+        let rules = get(get(get(run, "tool"), "driver"), "rules");
+        assert_eq!(&string("E003"), get(index(rules, 0), "id"));
+        assert_eq!(&string("E006"), get(index(rules, 1), "id"));
+
+        let third = index(get(run, "results"), 2);
+        assert_eq!(&Value::Number(0.0), get(third, "ruleIndex"));
+        let location = get(index(get(third, "locations"), 0), "physicalLocation");
+        assert_eq!(
+            &string("data.json"),
+            get(get(location, "artifactLocation"), "uri")
+        );
+        assert_eq!(
+            &Value::Number(5.0),
+            get(get(location, "region"), "startLine")
+        );
+        assert_eq!(
+            &Value::Number(6.0),
+            get(get(location, "region"), "startColumn")
+        );
+        let logical = index(
+            get(index(get(third, "locations"), 0), "logicalLocations"),
+            0,
+        );
+        assert_eq!(&string("/a/0"), get(logical, "fullyQualifiedName"));
+    }
+}
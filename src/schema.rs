@@ -0,0 +1,1468 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    fmt::Display,
+    fs,
+    io::{self, BufRead},
+    net::{Ipv4Addr, Ipv6Addr},
+    path::PathBuf,
+    sync::Arc,
+};
+
+use crate::{
+    errors::Error,
+    map::closest_key,
+    parsing::{escape_key, Float, ParseOptions, Parser, Value},
+};
+
+const TYPES: [&str; 7] = [
+    "null", "boolean", "integer", "number", "string", "array", "object",
+];
+
+/// A value of an instance that the schema rejected.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Violation {
+    instance_path: String,
+    keyword_path: String,
+    keyword: String,
+    message: String,
+}
+
+impl Violation {
+    /// JSON pointer to the rejected value in the instance.
+    pub fn instance_path(&self) -> &str {
+        &self.instance_path
+    }
+
+    /// JSON pointer to the keyword in the schema that rejected it, e.g.
+    /// `/properties/id/type`.
+    pub fn keyword_path(&self) -> &str {
+        &self.keyword_path
+    }
+
+    /// The keyword that rejected the value, such as `type`, or `false` for a schema that
+    /// allows no value at all.
+    pub fn keyword(&self) -> &str {
+        &self.keyword
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)?;
+        if !self.instance_path.is_empty() {
+            write!(f, " at {}", self.instance_path)?;
+        }
+        write!(f, " (schema {})", self.keyword_path)
+    }
+}
+
+impl std::error::Error for Violation {}
+
+/// A keyword of a schema whose value is not what the keyword takes.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SchemaError {
+    document: String,
+    path: String,
+    expected: &'static str,
+}
+
+impl SchemaError {
+    fn new(document: &str, path: &str, expected: &'static str) -> Self {
+        SchemaError {
+            document: document.to_string(),
+            path: path.to_string(),
+            expected,
+        }
+    }
+
+    /// The URI of the document the invalid keyword is in, or the empty string for the schema
+    /// itself.
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    /// JSON pointer to the invalid keyword in the schema.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+}
+
+impl Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Invalid schema: expected {}", self.expected)?;
+        if !self.path.is_empty() {
+            write!(f, " at {}", self.path)?;
+        }
+        if !self.document.is_empty() {
+            write!(f, " in {}", self.document)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SchemaError {}
+
+/// The records of an NDJSON stream that failed a schema.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct NdjsonReport {
+    records: usize,
+    failed: Vec<(usize, usize)>,
+    unparsable: Vec<usize>,
+}
+
+impl NdjsonReport {
+    /// How many non-blank lines were read.
+    pub fn records(&self) -> usize {
+        self.records
+    }
+
+    /// The line number and number of violations of each record the schema rejected.
+    pub fn failed(&self) -> &[(usize, usize)] {
+        &self.failed
+    }
+
+    /// The line numbers of the records that were not valid JSON.
+    pub fn unparsable(&self) -> &[usize] {
+        &self.unparsable
+    }
+
+    pub fn violations(&self) -> usize {
+        self.failed.iter().map(|(_, violations)| violations).sum()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.failed.is_empty() && self.unparsable.is_empty()
+    }
+}
+
+type Keyword = Arc<dyn Fn(&Value, &Value) -> Result<(), String> + Send + Sync>;
+
+/// Keywords added to the ones the engine implements, such as `x-company-rule`, each with the
+/// function that validates an instance against its value.
+#[derive(Clone, Default)]
+pub struct Keywords {
+    keywords: HashMap<String, Keyword>,
+}
+
+impl Keywords {
+    pub fn new() -> Self {
+        Keywords::default()
+    }
+
+    /// Adds the keyword `name`. `validate` is called with the keyword's value in the schema
+    /// and the instance, and returns the message of the violation if the instance is
+    /// invalid. A keyword the engine implements, such as `format`, is replaced.
+    pub fn with_keyword(
+        mut self,
+        name: &str,
+        validate: impl Fn(&Value, &Value) -> Result<(), String> + Send + Sync + 'static,
+    ) -> Self {
+        self.keywords.insert(name.to_string(), Arc::new(validate));
+        self
+    }
+}
+
+impl fmt::Debug for Keywords {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set().entries(self.keywords.keys()).finish()
+    }
+}
+
+/// Supplies the documents that `$ref`s to other schemas, such as `common.json#/$defs/id`,
+/// refer to, by the URI before the `#` as it is written in the `$ref`.
+pub trait Resolver {
+    fn resolve(&self, uri: &str) -> Option<Value>;
+}
+
+impl Resolver for HashMap<String, Value> {
+    fn resolve(&self, uri: &str) -> Option<Value> {
+        self.get(uri).cloned()
+    }
+}
+
+/// Reads documents from the files their URIs name, relative to a directory.
+#[derive(Debug, Clone)]
+pub struct DirectoryResolver {
+    dir: PathBuf,
+}
+
+impl DirectoryResolver {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        DirectoryResolver { dir: dir.into() }
+    }
+}
+
+impl Resolver for DirectoryResolver {
+    fn resolve(&self, uri: &str) -> Option<Value> {
+        let json = fs::read_to_string(self.dir.join(uri)).ok()?;
+        Parser::parse(&json).ok()
+    }
+}
+
+/// A JSON Schema (draft 2020-12) for validating documents, supporting the keywords for types,
+/// `enum` and `const`, numbers, string lengths, arrays, objects, and combining schemas, and
+/// any [`Keywords`] it is given. Other keywords, including `pattern` and
+/// `patternProperties`, are ignored.
+///
+/// `$ref` takes a JSON pointer fragment into the same document, such as `#/$defs/node`, or,
+/// with a [`Resolver`], into another. References may be recursive, but a schema is rejected
+/// if following them could return to the same schema without validating a child of the
+/// value.
+///
+/// `format` is only an annotation unless [`Schema::with_format_assertion`] is set.
+#[derive(Debug, Clone)]
+pub struct Schema {
+    documents: Documents,
+    keywords: Keywords,
+    assert_formats: bool,
+}
+
+impl Schema {
+    pub fn new(schema: Value) -> Result<Schema, SchemaError> {
+        Schema::with_keywords(schema, Keywords::new())
+    }
+
+    /// Like [`Schema::new`], but with custom keywords. Their values are not checked.
+    pub fn with_keywords(schema: Value, keywords: Keywords) -> Result<Schema, SchemaError> {
+        Schema::with_resolver(schema, keywords, &HashMap::<String, Value>::new())
+    }
+
+    /// Like [`Schema::with_keywords`], but `$ref`s may refer to the documents `resolver`
+    /// supplies. They are all resolved here, and it is an error if one cannot be.
+    pub fn with_resolver(
+        schema: Value,
+        keywords: Keywords,
+        resolver: &dyn Resolver,
+    ) -> Result<Schema, SchemaError> {
+        let mut external = BTreeMap::<String, Option<Value>>::new();
+        load(&schema, resolver, &mut external);
+        let documents = Documents {
+            root: schema,
+            external: external
+                .into_iter()
+                .filter_map(|(uri, document)| Some((uri, document?)))
+                .collect(),
+        };
+
+        let mut checker = Checker {
+            keywords: &keywords,
+            checked: HashSet::new(),
+        };
+        let scope = Scope {
+            documents: &documents,
+            uri: "",
+        };
+        checker.check(&documents.root, "", scope)?;
+        Ok(Schema {
+            documents,
+            keywords,
+            assert_formats: false,
+        })
+    }
+
+    /// Whether a string must match its `format` if [`is_valid_format`] knows it. Unknown
+    /// formats are always only annotations.
+    pub fn with_format_assertion(mut self, assert: bool) -> Self {
+        self.assert_formats = assert;
+        self
+    }
+
+    /// Every violation in `instance`, in document order within each schema keyword.
+    pub fn validate(&self, instance: &Value) -> Vec<Violation> {
+        let mut violations = Vec::<Violation>::new();
+        let root = &self.documents.root;
+        self.validate_at(root, instance, &Location::root(), &mut violations);
+        violations
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.validate(instance).is_empty()
+    }
+
+    /// Validates each line of `reader` as a record, one at a time, so that memory use does
+    /// not grow with the length of the stream. `on_failure` is called with the line number
+    /// and text of each record that fails, and its violations or parse errors. Blank lines
+    /// are skipped.
+    pub fn validate_ndjson(
+        &self,
+        reader: impl BufRead,
+        on_failure: impl FnMut(usize, &str, Result<&[Violation], &[Error]>),
+    ) -> io::Result<NdjsonReport> {
+        self.validate_ndjson_with_options(reader, ParseOptions::default(), on_failure)
+    }
+
+    /// Like [`Schema::validate_ndjson`], but parses each record with `options`.
+    pub fn validate_ndjson_with_options(
+        &self,
+        mut reader: impl BufRead,
+        options: ParseOptions,
+        mut on_failure: impl FnMut(usize, &str, Result<&[Violation], &[Error]>),
+    ) -> io::Result<NdjsonReport> {
+        let mut report = NdjsonReport::default();
+        let mut line = String::new();
+        let mut number = 0;
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                break;
+            }
+            number += 1;
+            let record = line.trim_end_matches(['\n', '\r']);
+            if record.trim().is_empty() {
+                continue;
+            }
+            report.records += 1;
+            match Parser::parse_with_options(record, options) {
+                Ok((value, _)) => {
+                    let violations = self.validate(&value);
+                    if !violations.is_empty() {
+                        report.failed.push((number, violations.len()));
+                        on_failure(number, record, Ok(&violations));
+                    }
+                }
+                Err(errors) => {
+                    let errors = errors
+                        .into_iter()
+                        .map(|error| error.offset_lines(number - 1))
+                        .collect::<Vec<Error>>();
+                    report.unparsable.push(number);
+                    on_failure(number, record, Err(&errors));
+                }
+            }
+        }
+        Ok(report)
+    }
+}
+
+/// Adds the documents that the `$ref`s in `value` refer to, and those theirs refer to, or
+/// `None` for those `resolver` cannot supply.
+fn load(value: &Value, resolver: &dyn Resolver, documents: &mut BTreeMap<String, Option<Value>>) {
+    match value {
+        Value::Object(members) => {
+            for (key, value) in members {
+                let (true, Value::String(reference)) = (&**key == "$ref", value) else {
+                    load(value, resolver, documents);
+                    continue;
+                };
+                let uri = reference.split('#').next().unwrap_or_default();
+                if uri.is_empty() || documents.contains_key(uri) {
+                    continue;
+                }
+                let document = resolver.resolve(uri);
+                documents.insert(uri.to_string(), document.clone());
+                if let Some(document) = document {
+                    load(&document, resolver, documents);
+                }
+            }
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                load(element, resolver, documents);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// The schema and the documents its `$ref`s refer to, by URI.
+#[derive(Debug, Clone)]
+struct Documents {
+    root: Value,
+    external: BTreeMap<String, Value>,
+}
+
+/// One of the [`Documents`], by its URI, which is empty for the schema itself.
+#[derive(Clone, Copy)]
+struct Scope<'a> {
+    documents: &'a Documents,
+    uri: &'a str,
+}
+
+impl<'a> Scope<'a> {
+    /// The schema that `reference` refers to, such as `#/$defs/a%25` in this document or
+    /// `common.json#/$defs/id` in another, with the document it is in and its pointer there.
+    fn resolve(self, reference: &'a str) -> Option<(Scope<'a>, &'a Value, String)> {
+        let (uri, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let scope = match uri {
+            "" => self,
+            uri => Scope {
+                documents: self.documents,
+                uri,
+            },
+        };
+        let document = match scope.uri {
+            "" => &self.documents.root,
+            uri => self.documents.external.get(uri)?,
+        };
+        let pointer = percent_decode(fragment)?;
+        Some((scope, document.pointer(&pointer)?, pointer))
+    }
+}
+
+struct Checker<'a> {
+    keywords: &'a Keywords,
+    /// The schemas that `$ref`s refer to that have been checked.
+    checked: HashSet<*const Value>,
+}
+
+impl<'a> Checker<'a> {
+    /// Checks that every keyword of `schema` and its subschemas has a value it takes, and
+    /// that every `$ref` refers to a schema.
+    fn check(
+        &mut self,
+        schema: &'a Value,
+        path: &str,
+        scope: Scope<'a>,
+    ) -> Result<(), SchemaError> {
+        let members = match schema {
+            Value::Bool(_) => return Ok(()),
+            Value::Object(members) => members,
+            _ => return Err(SchemaError::new(scope.uri, path, "a schema object or bool")),
+        };
+        for (keyword, value) in members {
+            let path = format!("{path}/{}", escape_key(keyword));
+            if self.keywords.keywords.contains_key(&**keyword) {
+                continue;
+            }
+            let expected = match (&**keyword, value) {
+                ("type", Value::String(name)) if TYPES.contains(&&**name) => continue,
+                ("type", Value::Array(names))
+                    if names.iter().all(
+                        |name| matches!(name, Value::String(name) if TYPES.contains(&&**name)),
+                    ) =>
+                {
+                    continue
+                }
+                ("type", _) => "a type name or an array of them",
+                ("enum", Value::Array(_)) => continue,
+                ("enum", _) => "an array",
+                ("multipleOf", Value::Number(n)) if *n > 0.0 => continue,
+                ("multipleOf", _) => "a number greater than 0",
+                ("minimum" | "maximum" | "exclusiveMinimum" | "exclusiveMaximum", value) => {
+                    match value {
+                        Value::Number(_) => continue,
+                        _ => "a number",
+                    }
+                }
+                (
+                    "minLength" | "maxLength" | "minItems" | "maxItems" | "minContains"
+                    | "maxContains" | "minProperties" | "maxProperties",
+                    value,
+                ) => match count(value) {
+                    Some(_) => continue,
+                    None => "a non-negative integer",
+                },
+                ("format", Value::String(_)) => continue,
+                ("format", _) => "a string",
+                ("uniqueItems", Value::Bool(_)) => continue,
+                ("uniqueItems", _) => "a bool",
+                ("required", Value::Array(keys)) if keys.iter().all(is_string) => continue,
+                ("required", _) => "an array of strings",
+                ("dependentRequired", Value::Object(dependencies))
+                    if dependencies.values().all(
+                        |keys| matches!(keys, Value::Array(keys) if keys.iter().all(is_string)),
+                    ) =>
+                {
+                    continue
+                }
+                ("dependentRequired", _) => "an object of arrays of strings",
+                (
+                    "items"
+                    | "contains"
+                    | "additionalProperties"
+                    | "propertyNames"
+                    | "not"
+                    | "if"
+                    | "then"
+                    | "else",
+                    schema,
+                ) => {
+                    self.check(schema, &path, scope)?;
+                    continue;
+                }
+                ("properties" | "$defs" | "definitions", Value::Object(properties)) => {
+                    for (key, schema) in properties {
+                        self.check(schema, &format!("{path}/{}", escape_key(key)), scope)?;
+                    }
+                    continue;
+                }
+                ("properties" | "$defs" | "definitions", _) => "an object of schemas",
+                ("$ref", Value::String(reference)) => match scope.resolve(reference) {
+                    Some(_) if cycles(schema, scope, &mut Vec::new(), &mut HashSet::new()) => {
+                        "a reference that does not lead back to itself"
+                    }
+                    Some((scope, schema, pointer)) => {
+                        if self.checked.insert(schema) {
+                            self.check(schema, &pointer, scope)?;
+                        }
+                        continue;
+                    }
+                    None => "a reference to a schema in this document or one the resolver supplies",
+                },
+                ("$ref", _) => "a string",
+                ("allOf" | "anyOf" | "oneOf" | "prefixItems", Value::Array(schemas))
+                    if !schemas.is_empty() =>
+                {
+                    for (i, schema) in schemas.iter().enumerate() {
+                        self.check(schema, &format!("{path}/{i}"), scope)?;
+                    }
+                    continue;
+                }
+                ("allOf" | "anyOf" | "oneOf" | "prefixItems", _) => "a non-empty array of schemas",
+                _ => continue,
+            };
+            return Err(SchemaError::new(scope.uri, &path, expected));
+        }
+        Ok(())
+    }
+}
+
+fn is_string(value: &Value) -> bool {
+    matches!(value, Value::String(_))
+}
+
+fn count(value: &Value) -> Option<usize> {
+    match value {
+        Value::Number(n) if *n >= 0.0 && n.fract() == 0.0 => Some(*n as usize),
+        _ => None,
+    }
+}
+
+/// The JSON pointers to the value being validated and to the schema validating it.
+struct Location {
+    instance: String,
+    keyword: String,
+    /// The URI of the document the schema is in.
+    document: String,
+}
+
+impl Location {
+    fn root() -> Location {
+        Location {
+            instance: String::new(),
+            keyword: String::new(),
+            document: String::new(),
+        }
+    }
+
+    fn keyword(&self, keyword: impl Display) -> Location {
+        Location {
+            instance: self.instance.clone(),
+            keyword: format!("{}/{keyword}", self.keyword),
+            document: self.document.clone(),
+        }
+    }
+
+    /// The location of the child `token` of the value, validated by the schema at `keyword`.
+    fn child(&self, token: &str, keyword: impl Display) -> Location {
+        Location {
+            instance: format!("{}/{}", self.instance, escape_key(token)),
+            keyword: format!("{}/{keyword}", self.keyword),
+            document: self.document.clone(),
+        }
+    }
+
+    fn violation(&self, keyword: &str, message: String) -> Violation {
+        Violation {
+            instance_path: self.instance.clone(),
+            keyword_path: format!("{}/{keyword}", self.keyword),
+            keyword: keyword.to_string(),
+            message,
+        }
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        #[cfg(feature = "bytes")]
+        Value::Bytes(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match (name, value) {
+        ("integer", Value::Number(n)) => n.fract() == 0.0,
+        (name, value) => name == type_name(value),
+    }
+}
+
+fn did_you_mean(key: Option<&str>) -> String {
+    match key {
+        Some(key) => format!(", did you mean {}?", Value::String(key.to_string())),
+        None => String::new(),
+    }
+}
+
+fn is_multiple(n: Float, of: Float) -> bool {
+    let quotient = (n / of).round();
+    quotient.is_finite() && (quotient * of - n).abs() <= Float::EPSILON * n.abs()
+}
+
+impl Schema {
+    /// Whether `instance` is valid against the subschema `schema` in the document of `at`.
+    fn matches(&self, schema: &Value, instance: &Value, at: &Location) -> bool {
+        let mut violations = Vec::<Violation>::new();
+        self.validate_at(schema, instance, at, &mut violations);
+        violations.is_empty()
+    }
+
+    fn validate_at(
+        &self,
+        schema: &Value,
+        instance: &Value,
+        at: &Location,
+        violations: &mut Vec<Violation>,
+    ) {
+        let members = match schema {
+            Value::Object(members) => members,
+            Value::Bool(false) => {
+                violations.push(Violation {
+                    instance_path: at.instance.clone(),
+                    keyword_path: at.keyword.clone(),
+                    keyword: "false".to_string(),
+                    message: "No value is allowed here".to_string(),
+                });
+                return;
+            }
+            _ => return,
+        };
+
+        for (keyword, value) in members {
+            if let Some(validate) = self.keywords.keywords.get(&**keyword) {
+                if let Err(message) = validate(value, instance) {
+                    violations.push(at.violation(keyword, message));
+                }
+                continue;
+            }
+            let message = match (&**keyword, value, instance) {
+                ("type", Value::String(name), instance) if !has_type(instance, name) => {
+                    format!("Expected {name}, found {}", type_name(instance))
+                }
+                ("type", Value::Array(names), instance)
+                    if !names.iter().any(
+                        |name| matches!(name, Value::String(name) if has_type(instance, name)),
+                    ) =>
+                {
+                    let names = names.iter().map(|name| match name {
+                        Value::String(name) => name.as_str(),
+                        _ => "",
+                    });
+                    let names = names.collect::<Vec<&str>>().join(" or ");
+                    format!("Expected {names}, found {}", type_name(instance))
+                }
+                ("enum", Value::Array(values), instance) if !values.contains(instance) => {
+                    format!("Expected one of {value}")
+                }
+                ("const", value, instance) if value != instance => format!("Expected {value}"),
+
+                ("multipleOf", Value::Number(of), Value::Number(n)) if !is_multiple(*n, *of) => {
+                    format!("Expected a multiple of {of}")
+                }
+                ("minimum", Value::Number(min), Value::Number(n)) if n < min => {
+                    format!("Expected at least {min}")
+                }
+                ("maximum", Value::Number(max), Value::Number(n)) if n > max => {
+                    format!("Expected at most {max}")
+                }
+                ("exclusiveMinimum", Value::Number(min), Value::Number(n)) if n <= min => {
+                    format!("Expected more than {min}")
+                }
+                ("exclusiveMaximum", Value::Number(max), Value::Number(n)) if n >= max => {
+                    format!("Expected less than {max}")
+                }
+
+                ("format", Value::String(name), Value::String(s))
+                    if self.assert_formats && is_valid_format(name, s) == Some(false) =>
+                {
+                    format!("Expected a string in {name} format")
+                }
+                ("minLength", value, Value::String(s)) if s.chars().count() < count_of(value) => {
+                    format!("Expected at least {value} characters")
+                }
+                ("maxLength", value, Value::String(s)) if s.chars().count() > count_of(value) => {
+                    format!("Expected at most {value} characters")
+                }
+
+                ("minItems", value, Value::Array(elements)) if elements.len() < count_of(value) => {
+                    format!("Expected at least {value} items")
+                }
+                ("maxItems", value, Value::Array(elements)) if elements.len() > count_of(value) => {
+                    format!("Expected at most {value} items")
+                }
+                ("uniqueItems", Value::Bool(true), Value::Array(elements)) => {
+                    let duplicate = (1..elements.len()).find_map(|j| {
+                        Some((elements[..j].iter().position(|e| *e == elements[j])?, j))
+                    });
+                    match duplicate {
+                        Some((i, j)) => format!("Expected unique items, but {i} and {j} are equal"),
+                        None => continue,
+                    }
+                }
+                ("prefixItems", Value::Array(schemas), Value::Array(elements)) => {
+                    for (i, (schema, element)) in schemas.iter().zip(elements).enumerate() {
+                        let at = at.child(&i.to_string(), format!("prefixItems/{i}"));
+                        self.validate_at(schema, element, &at, violations);
+                    }
+                    continue;
+                }
+                ("items", schema, Value::Array(elements)) => {
+                    let prefix = match members.get("prefixItems") {
+                        Some(Value::Array(schemas)) => schemas.len(),
+                        _ => 0,
+                    };
+                    for (i, element) in elements.iter().enumerate().skip(prefix) {
+                        self.validate_at(
+                            schema,
+                            element,
+                            &at.child(&i.to_string(), "items"),
+                            violations,
+                        );
+                    }
+                    continue;
+                }
+                ("contains", schema, Value::Array(elements)) => {
+                    let matches = elements
+                        .iter()
+                        .filter(|e| self.matches(schema, e, at))
+                        .count();
+                    let min = members.get("minContains").map_or(1, count_of);
+                    let max = members.get("maxContains").map_or(usize::MAX, count_of);
+                    if matches < min {
+                        format!("Expected at least {min} items to match contains, found {matches}")
+                    } else if matches > max {
+                        format!("Expected at most {max} items to match contains, found {matches}")
+                    } else {
+                        continue;
+                    }
+                }
+
+                ("properties", Value::Object(properties), Value::Object(instance)) => {
+                    for (key, schema) in properties {
+                        let key_path = escape_key(key);
+                        if let Some(value) = instance.get(key) {
+                            let at = at.child(key, format!("properties/{key_path}"));
+                            self.validate_at(schema, value, &at, violations);
+                        }
+                    }
+                    continue;
+                }
+                ("additionalProperties", schema, Value::Object(instance)) => {
+                    let properties = match members.get("properties") {
+                        Some(Value::Object(properties)) => Some(properties),
+                        _ => None,
+                    };
+                    for (key, value) in instance {
+                        if properties.is_some_and(|properties| properties.contains_key(key)) {
+                            continue;
+                        }
+                        let at = at.child(key, "additionalProperties");
+                        if let Value::Bool(false) = schema {
+                            // Most unknown properties are misspelled known ones.
+                            let closest = properties.and_then(|properties| {
+                                let missing =
+                                    properties.keys().filter(|k| !instance.contains_key(k));
+                                closest_key(key, missing)
+                            });
+                            violations.push(Violation {
+                                instance_path: at.instance.clone(),
+                                keyword_path: at.keyword.clone(),
+                                keyword: "additionalProperties".to_string(),
+                                message: format!(
+                                    "Unknown property {}{}",
+                                    Value::String(key.to_string()),
+                                    did_you_mean(closest)
+                                ),
+                            });
+                        } else {
+                            self.validate_at(schema, value, &at, violations);
+                        }
+                    }
+                    continue;
+                }
+                ("propertyNames", schema, Value::Object(instance)) => {
+                    for key in instance.keys() {
+                        let name = Value::String(key.to_string());
+                        let at = at.child(key, "propertyNames");
+                        self.validate_at(schema, &name, &at, violations);
+                    }
+                    continue;
+                }
+                ("required", Value::Array(keys), Value::Object(instance)) => {
+                    let properties = match members.get("properties") {
+                        Some(Value::Object(properties)) => Some(properties),
+                        _ => None,
+                    };
+                    for key in keys {
+                        if let Value::String(name) = key {
+                            if !instance.contains_key(name) {
+                                let unknown = instance.keys().filter(|k| {
+                                    !properties.is_some_and(|properties| properties.contains_key(k))
+                                });
+                                let message = format!(
+                                    "Missing required property {key}{}",
+                                    did_you_mean(closest_key(name, unknown))
+                                );
+                                violations.push(at.violation("required", message));
+                            }
+                        }
+                    }
+                    continue;
+                }
+                ("dependentRequired", Value::Object(dependencies), Value::Object(instance)) => {
+                    for (key, required) in dependencies {
+                        let (true, Value::Array(required)) = (instance.contains_key(key), required)
+                        else {
+                            continue;
+                        };
+                        for name in required {
+                            if matches!(name, Value::String(s) if !instance.contains_key(s)) {
+                                let message = format!("Missing property {name}, required by {key}");
+                                let keyword = format!("dependentRequired/{}", escape_key(key));
+                                violations.push(at.violation(&keyword, message));
+                            }
+                        }
+                    }
+                    continue;
+                }
+                ("minProperties", value, Value::Object(instance))
+                    if instance.len() < count_of(value) =>
+                {
+                    format!("Expected at least {value} properties")
+                }
+                ("maxProperties", value, Value::Object(instance))
+                    if instance.len() > count_of(value) =>
+                {
+                    format!("Expected at most {value} properties")
+                }
+
+                ("$ref", Value::String(reference), instance) => {
+                    let scope = Scope {
+                        documents: &self.documents,
+                        uri: &at.document,
+                    };
+                    if let Some((scope, schema, _)) = scope.resolve(reference) {
+                        let mut at = at.keyword("$ref");
+                        at.document = scope.uri.to_string();
+                        self.validate_at(schema, instance, &at, violations);
+                    }
+                    continue;
+                }
+                ("allOf", Value::Array(schemas), instance) => {
+                    for (i, schema) in schemas.iter().enumerate() {
+                        self.validate_at(
+                            schema,
+                            instance,
+                            &at.keyword(format!("allOf/{i}")),
+                            violations,
+                        );
+                    }
+                    continue;
+                }
+                ("anyOf", Value::Array(schemas), instance)
+                    if !schemas
+                        .iter()
+                        .any(|schema| self.matches(schema, instance, at)) =>
+                {
+                    "Expected to match at least one schema in anyOf".to_string()
+                }
+                ("oneOf", Value::Array(schemas), instance) => {
+                    match schemas
+                        .iter()
+                        .filter(|schema| self.matches(schema, instance, at))
+                        .count()
+                    {
+                        1 => continue,
+                        n => format!("Expected to match exactly one schema in oneOf, matched {n}"),
+                    }
+                }
+                ("not", schema, instance) if self.matches(schema, instance, at) => {
+                    "Expected not to match the schema in not".to_string()
+                }
+                ("if", schema, instance) => {
+                    let (keyword, schema) = match self.matches(schema, instance, at) {
+                        true => ("then", members.get("then")),
+                        false => ("else", members.get("else")),
+                    };
+                    if let Some(schema) = schema {
+                        self.validate_at(schema, instance, &at.keyword(keyword), violations);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            violations.push(at.violation(keyword, message));
+        }
+    }
+}
+
+/// Whether validating a value against `schema` can lead back to a schema on `stack` without
+/// stepping into a child of the value, as in `{"$defs": {"a": {"not": {"$ref": "#/$defs/a"}}}}`,
+/// which would never end. Schemas in `done` are known not to.
+fn cycles(
+    schema: &Value,
+    scope: Scope,
+    stack: &mut Vec<*const Value>,
+    done: &mut HashSet<*const Value>,
+) -> bool {
+    let Value::Object(members) = schema else {
+        return false;
+    };
+    let id = schema as *const Value;
+    if stack.contains(&id) {
+        return true;
+    }
+    if done.contains(&id) {
+        return false;
+    }
+
+    stack.push(id);
+    let mut applied = Vec::<(Scope, &Value)>::new();
+    for (keyword, value) in members {
+        match (&**keyword, value) {
+            ("$ref", Value::String(reference)) => applied.extend(
+                scope
+                    .resolve(reference)
+                    .map(|(scope, schema, _)| (scope, schema)),
+            ),
+            ("allOf" | "anyOf" | "oneOf", Value::Array(schemas)) => {
+                applied.extend(schemas.iter().map(|schema| (scope, schema)))
+            }
+            ("not" | "if" | "then" | "else", schema) => applied.push((scope, schema)),
+            _ => {}
+        }
+    }
+    let cycles = applied
+        .into_iter()
+        .any(|(scope, schema)| cycles(schema, scope, stack, done));
+    stack.pop();
+    done.insert(id);
+    cycles
+}
+
+/// Decodes the `%` escapes of a URI fragment.
+fn percent_decode(fragment: &str) -> Option<String> {
+    let mut bytes = Vec::<u8>::with_capacity(fragment.len());
+    let mut rest = fragment.as_bytes();
+    while let Some((&b, tail)) = rest.split_first() {
+        rest = tail;
+        match b {
+            b'%' => {
+                let hex = rest
+                    .get(..2)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))?;
+                bytes.push(u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok()?);
+                rest = &rest[2..];
+            }
+            b => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Whether `s` is in the format `name`, one of `date-time`, `date`, `email`, `uuid`, `uri`,
+/// `ipv4`, and `ipv6`, or `None` for other formats.
+pub fn is_valid_format(name: &str, s: &str) -> Option<bool> {
+    let valid = match name {
+        "date-time" => {
+            let (date, time) = (s.get(..10), s.get(11..));
+            matches!(s.as_bytes().get(10), Some(b'T' | b't'))
+                && date.is_some_and(is_date)
+                && time.is_some_and(is_time)
+        }
+        "date" => is_date(s),
+        "email" => is_email(s),
+        "uuid" => {
+            s.len() == 36
+                && s.bytes().enumerate().all(|(i, b)| match i {
+                    8 | 13 | 18 | 23 => b == b'-',
+                    _ => b.is_ascii_hexdigit(),
+                })
+        }
+        "uri" => is_uri(s),
+        "ipv4" => s.parse::<Ipv4Addr>().is_ok(),
+        "ipv6" => s.parse::<Ipv6Addr>().is_ok(),
+        _ => return None,
+    };
+    Some(valid)
+}
+
+/// Reads the digits of a fixed-width field.
+fn digits(s: &str) -> Option<u32> {
+    match s.bytes().all(|b| b.is_ascii_digit()) {
+        true => s.parse().ok(),
+        false => None,
+    }
+}
+
+/// An RFC 3339 `full-date` such as `2024-02-29`.
+fn is_date(s: &str) -> bool {
+    let [year, month, day] = match s.len() == 10 && s.is_ascii() {
+        true => [&s[..4], &s[5..7], &s[8..]].map(digits),
+        false => return false,
+    };
+    let (Some(year), Some(month), Some(day)) = (year, month, day) else {
+        return false;
+    };
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return false,
+    };
+    &s[4..5] == "-" && &s[7..8] == "-" && (1..=days).contains(&day)
+}
+
+/// An RFC 3339 `full-time` such as `23:59:60.5+01:00`, allowing a leap second.
+fn is_time(s: &str) -> bool {
+    if !s.is_ascii() {
+        return false;
+    }
+    let is_hh_mm = |s: &str| {
+        s.len() == 5
+            && &s[2..3] == ":"
+            && digits(&s[..2]).is_some_and(|hour| hour < 24)
+            && digits(&s[3..]).is_some_and(|minute| minute < 60)
+    };
+    let Some((time, offset)) = s.find(['Z', 'z', '+', '-']).map(|i| (&s[..i], &s[i..])) else {
+        return false;
+    };
+    let (time, fraction) = time.split_once('.').unwrap_or((time, "0"));
+    let offset = match offset {
+        "Z" | "z" => true,
+        _ => is_hh_mm(&offset[1..]),
+    };
+    offset
+        && time.len() == 8
+        && is_hh_mm(&time[..5])
+        && &time[5..6] == ":"
+        && digits(&time[6..]).is_some_and(|second| second <= 60)
+        && !fraction.is_empty()
+        && fraction.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// An address such as `a.b+c@example.com`, with a dot-atom local part and a domain name.
+fn is_email(s: &str) -> bool {
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+    let local_valid = local.split('.').all(|atom| {
+        !atom.is_empty()
+            && atom
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+/=?^_`{|}~-".contains(&b))
+    });
+    let domain_valid = domain.split('.').all(|label| {
+        (1..=63).contains(&label.len())
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+    });
+    local.len() <= 64 && local_valid && domain_valid
+}
+
+/// An RFC 3986 URI with a scheme, such as `https://example.com/a?b#c`.
+fn is_uri(s: &str) -> bool {
+    let Some((scheme, rest)) = s.split_once(':') else {
+        return false;
+    };
+    let scheme_valid = scheme.bytes().enumerate().all(|(i, b)| match i {
+        0 => b.is_ascii_alphabetic(),
+        _ => b.is_ascii_alphanumeric() || b"+-.".contains(&b),
+    });
+    let bytes = rest.as_bytes();
+    let rest_valid = bytes.iter().enumerate().all(|(i, &b)| match b {
+        b'%' => bytes
+            .get(i + 1..i + 3)
+            .is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit)),
+        b => b.is_ascii_alphanumeric() || b"-._~:/?#[]@!$&'()*+,;=".contains(&b),
+    });
+    !scheme.is_empty() && scheme_valid && rest_valid
+}
+
+/// A count keyword, which [`check`] has made sure is a non-negative integer.
+fn count_of(value: &Value) -> usize {
+    count(value).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn schema(json: &str) -> Schema {
+        Schema::new(Parser::parse(json).unwrap()).unwrap()
+    }
+
+    fn violations(schema: &Schema, json: &str) -> Vec<(String, String)> {
+        schema
+            .validate(&Parser::parse(json).unwrap())
+            .into_iter()
+            .map(|v| (v.instance_path, v.keyword_path))
+            .collect()
+    }
+
+    /// [`violations`], sorted since keywords and properties are checked in the map's order.
+    fn sorted_violations(schema: &Schema, json: &str) -> Vec<(String, String)> {
+        let mut violations = violations(schema, json);
+        violations.sort();
+        violations
+    }
+
+    #[test]
+    fn validate_instances() {
+        let person = schema(
+            r#"{
+                "type": "object",
+                "required": ["name", "age"],
+                "properties": {
+                    "name": {"type": "string", "minLength": 1},
+                    "age": {"type": "integer", "minimum": 0},
+                    "tags": {
+                        "type": "array",
+                        "items": {"enum": ["a", "b"]},
+                        "uniqueItems": true
+                    },
+                    "a/b": {"const": null}
+                },
+                "additionalProperties": false
+            }"#,
+        );
+        assert!(person.is_valid(&Parser::parse(r#"{"name": "x", "age": 3}"#).unwrap()));
+        assert_eq!(
+            vec![
+                ("".to_string(), "/required".to_string()),
+                ("/a~1b".to_string(), "/properties/a~1b/const".to_string()),
+                (
+                    "/name".to_string(),
+                    "/properties/name/minLength".to_string()
+                ),
+                (
+                    "/tags".to_string(),
+                    "/properties/tags/uniqueItems".to_string()
+                ),
+                (
+                    "/tags/1".to_string(),
+                    "/properties/tags/items/enum".to_string()
+                ),
+                ("/x".to_string(), "/additionalProperties".to_string()),
+            ],
+            sorted_violations(
+                &person,
+                r#"{"name": "", "tags": ["a", "c", "a"], "a/b": 1, "x": 2}"#
+            )
+        );
+        assert_eq!(
+            vec![Violation {
+                instance_path: "/age".to_string(),
+                keyword_path: "/properties/age/type".to_string(),
+                keyword: "type".to_string(),
+                message: "Expected integer, found number".to_string(),
+            }],
+            person.validate(&Parser::parse(r#"{"name": "x", "age": 1.5}"#).unwrap())
+        );
+        assert_eq!(
+            "Missing required property \"age\" (schema /required)",
+            person.validate(&Parser::parse(r#"{"name": "x"}"#).unwrap())[0].to_string()
+        );
+        let mut messages = person
+            .validate(&Parser::parse(r#"{"name": "x", "aeg": 3, "nmae": 1}"#).unwrap())
+            .iter()
+            .map(Violation::to_string)
+            .collect::<Vec<String>>();
+        messages.sort();
+        assert_eq!(
+            vec![
+                "Missing required property \"age\", did you mean \"aeg\"? (schema /required)",
+                "Unknown property \"aeg\", did you mean \"age\"? at /aeg (schema /additionalProperties)",
+                "Unknown property \"nmae\" at /nmae (schema /additionalProperties)",
+            ],
+            messages
+        );
+    }
+
+    #[test]
+    fn combine_schemas() {
+        let schema = schema(
+            r#"{
+                "anyOf": [{"type": "string"}, {"type": "number", "multipleOf": 0.1}],
+                "oneOf": [{"type": "string"}, {"type": "number", "maximum": 5}],
+                "not": {"const": 4},
+                "if": {"type": "string"},
+                "then": {"maxLength": 2},
+                "else": {"exclusiveMinimum": 0}
+            }"#,
+        );
+        assert!(schema.is_valid(&Value::Number(0.3)));
+        assert!(schema.is_valid(&Value::String("ab".to_string())));
+        let keywords = |json| {
+            violations(&schema, json)
+                .into_iter()
+                .map(|(_, keyword)| keyword)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(vec!["/anyOf", "/oneOf"], keywords("[]"));
+        assert_eq!(vec!["/not"], keywords("4"));
+        assert_eq!(vec!["/oneOf"], keywords("6"));
+        assert_eq!(vec!["/else/exclusiveMinimum"], keywords("-1"));
+        assert_eq!(vec!["/then/maxLength"], keywords(r#""abc""#));
+        assert_eq!(vec!["/anyOf"], keywords("0.25"));
+        assert!(Schema::new(Value::Bool(false))
+            .unwrap()
+            .validate(&Value::Null)
+            .iter()
+            .all(|violation| violation.keyword_path().is_empty()));
+    }
+
+    #[test]
+    fn register_keywords() {
+        let keywords = Keywords::new()
+            .with_keyword("x-even", |value, instance| match (value, instance) {
+                (Value::Bool(true), Value::Number(n)) if n % 2.0 != 0.0 => {
+                    Err(format!("Expected an even number, found {n}"))
+                }
+                _ => Ok(()),
+            })
+            .with_keyword("minimum", |_, _| Ok(()));
+        let schema = Schema::with_keywords(
+            Parser::parse(r#"{"items": {"x-even": true, "minimum": "ignored"}}"#).unwrap(),
+            keywords,
+        )
+        .unwrap();
+
+        let violations = schema.validate(&Parser::parse("[2, -4, 5]").unwrap());
+        assert_eq!(1, violations.len());
+        assert_eq!("/2", violations[0].instance_path());
+        assert_eq!("/items/x-even", violations[0].keyword_path());
+        assert_eq!("Expected an even number, found 5", violations[0].message());
+        assert_eq!(
+            Some("/items/minimum".to_string()),
+            Schema::new(Parser::parse(r#"{"items": {"minimum": "ignored"}}"#).unwrap())
+                .err()
+                .map(|error| error.path().to_string())
+        );
+    }
+
+    #[test]
+    fn resolve_references() {
+        let tree = schema(
+            r##"{
+                "$defs": {
+                    "node": {
+                        "type": "object",
+                        "properties": {
+                            "value": {"$ref": "#/definitions/a~1b%25"},
+                            "children": {"type": "array", "items": {"$ref": "#/$defs/node"}}
+                        }
+                    }
+                },
+                "definitions": {"a/b%": {"type": "integer"}},
+                "$ref": "#/$defs/node"
+            }"##,
+        );
+        let instance = r#"{"value": 1, "children": [{"children": [{"value": "x"}]}]}"#;
+        assert_eq!(
+            vec![(
+                "/children/0/children/0/value".to_string(),
+                "/$ref/properties/children/items/$ref/properties/children/items/$ref\
+                 /properties/value/$ref/type"
+                    .to_string()
+            )],
+            violations(&tree, instance)
+        );
+
+        let cyclic = r##"{
+            "$defs": {"a": {"$ref": "#/$defs/b"}, "b": {"not": {"$ref": "#/$defs/a"}}},
+            "properties": {"x": {"$ref": "#/$defs/a"}}
+        }"##;
+        assert_eq!(
+            Some("/$defs/a/$ref".to_string()),
+            Schema::new(Parser::parse(cyclic).unwrap())
+                .err()
+                .map(|error| error.path().to_string())
+        );
+
+        for reference in ["#/$defs/missing", "other.json#/a", "#/a%2"] {
+            let schema = Value::Object(
+                [
+                    ("a", Value::Bool(true)),
+                    ("$ref", Value::String(reference.to_string())),
+                ]
+                .into_iter()
+                .collect(),
+            );
+            assert_eq!(
+                Some("/$ref".to_string()),
+                Schema::new(schema)
+                    .err()
+                    .map(|error| error.path().to_string())
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_external_references() {
+        let documents = [
+            (
+                "common.json",
+                r##"{"$defs": {
+                    "id": {"type": "integer", "not": {"$ref": "#/$defs/zero"}},
+                    "zero": {"const": 0},
+                    "bad": {"type": "int"}
+                }}"##,
+            ),
+            ("a.json", r#"{"$ref": "b.json"}"#),
+            ("b.json", r#"{"allOf": [{"$ref": "a.json"}]}"#),
+        ];
+        let resolver = documents
+            .into_iter()
+            .map(|(uri, json)| (uri.to_string(), Parser::parse(json).unwrap()))
+            .collect::<HashMap<String, Value>>();
+        let schema =
+            |json| Schema::with_resolver(Parser::parse(json).unwrap(), Keywords::new(), &resolver);
+
+        let ids = schema(r#"{"items": {"$ref": "common.json#/$defs/id"}}"#).unwrap();
+        let violations = ids.validate(&Parser::parse("[1, 0, 1.5]").unwrap());
+        assert_eq!(
+            vec!["/items/$ref/not", "/items/$ref/type"],
+            violations
+                .iter()
+                .map(Violation::keyword_path)
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!("/1", violations[0].instance_path());
+
+        let error = schema(r#"{"$ref": "common.json#/$defs/bad"}"#).unwrap_err();
+        assert_eq!(
+            ("common.json", "/$defs/bad/type"),
+            (error.document(), error.path())
+        );
+        assert_eq!(
+            "Invalid schema: expected a type name or an array of them at /$defs/bad/type \
+             in common.json",
+            error.to_string()
+        );
+        let error = schema(r#"{"$ref": "a.json"}"#).unwrap_err();
+        assert_eq!(("", "/$ref"), (error.document(), error.path()));
+        let error = schema(r#"{"$ref": "missing.json#/a"}"#).unwrap_err();
+        assert_eq!(("", "/$ref"), (error.document(), error.path()));
+    }
+
+    #[test]
+    fn validate_ndjson_records() {
+        let schema = schema(r#"{"required": ["id"], "properties": {"id": {"type": "integer"}}}"#);
+        let ndjson = "{\"id\": 1}\n\n{\"id\": \"x\", \"y\": 2}\r\n{\"id\"}\n{}";
+        let mut failures = Vec::<(usize, String, usize)>::new();
+        let report = schema
+            .validate_ndjson(ndjson.as_bytes(), |line, record, result| {
+                let count = match result {
+                    Ok(violations) => violations.len(),
+                    Err(errors) => {
+                        assert_eq!(line, errors[0].line());
+                        errors.len()
+                    }
+                };
+                failures.push((line, record.to_string(), count));
+            })
+            .unwrap();
+
+        assert_eq!(
+            vec![
+                (3, r#"{"id": "x", "y": 2}"#.to_string(), 1),
+                (4, r#"{"id"}"#.to_string(), 1),
+                (5, "{}".to_string(), 1),
+            ],
+            failures
+        );
+        assert_eq!(4, report.records());
+        assert_eq!(&[(3, 1), (5, 1)], report.failed());
+        assert_eq!(&[4], report.unparsable());
+        assert_eq!(2, report.violations());
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn assert_formats() {
+        let valid = [
+            ("date-time", "1985-04-12T23:20:50.52Z"),
+            ("date-time", "1990-12-31t23:59:60-08:00"),
+            ("date", "2024-02-29"),
+            ("email", "first.last+tag@sub.example.com"),
+            ("uuid", "123e4567-E89B-12d3-a456-426614174000"),
+            ("uri", "https://example.com/a%20b?q=1#top"),
+            ("uri", "urn:isbn:0451450523"),
+            ("ipv4", "192.168.0.1"),
+            ("ipv6", "::ffff:192.168.0.1"),
+        ];
+        let invalid = [
+            ("date-time", "1985-04-12 23:20:50Z"),
+            ("date-time", "1985-04-12T23:20:50"),
+            ("date-time", "1985-04-12T24:00:00Z"),
+            ("date-time", "1985-04-12T23:20:50.Z"),
+            ("date", "2023-02-29"),
+            ("date", "2024-13-01"),
+            ("date", "2024-1-01"),
+            ("date", "2024-é-01"),
+            ("date-time", "2024-01-01T00:é:00Z"),
+            ("email", "a..b@example.com"),
+            ("email", "a@-example.com"),
+            ("email", "example.com"),
+            ("uuid", "123e4567e89b12d3a456426614174000"),
+            ("uri", "/relative/path"),
+            ("uri", "http://a b"),
+            ("uri", "http://a/%zz"),
+            ("ipv4", "192.168.0.01"),
+            ("ipv4", "256.0.0.1"),
+            ("ipv6", "1::2::3"),
+        ];
+        for (format, s) in valid {
+            assert_eq!(Some(true), is_valid_format(format, s), "{format} {s}");
+        }
+        for (format, s) in invalid {
+            assert_eq!(Some(false), is_valid_format(format, s), "{format} {s}");
+        }
+        assert_eq!(None, is_valid_format("hostname", "x"));
+
+        let schema =
+            Schema::new(Parser::parse(r#"{"items": {"format": "date"}}"#).unwrap()).unwrap();
+        let instance = Parser::parse(r#"["2024-02-29", "yesterday", 3]"#).unwrap();
+        assert!(schema.is_valid(&instance));
+        let violations = schema.with_format_assertion(true).validate(&instance);
+        assert_eq!(1, violations.len());
+        assert_eq!("/1", violations[0].instance_path());
+        assert_eq!("Expected a string in date format", violations[0].message());
+    }
+
+    #[test]
+    fn reject_invalid_schemas() {
+        let error = |json| Schema::new(Parser::parse(json).unwrap()).err();
+        assert_eq!(
+            Some("/properties/a/minItems"),
+            error(r#"{"properties": {"a": {"minItems": -1}}}"#)
+                .as_ref()
+                .map(SchemaError::path)
+        );
+        assert_eq!(
+            "Invalid schema: expected a type name or an array of them at /type",
+            error(r#"{"type": "int"}"#).unwrap().to_string()
+        );
+        assert!(error(r#"{"allOf": []}"#).is_some());
+        assert!(error(r#"{"items": 1}"#).is_some());
+        assert!(error("[]").is_some());
+        assert!(error(r#"{"x-unknown": 1, "pattern": "^a"}"#).is_none());
+    }
+}
@@ -0,0 +1,549 @@
+use std::{
+    fmt,
+    fmt::{Display, LowerExp, Write},
+};
+
+#[cfg(feature = "bytes")]
+use crate::bytes::BytesFormat;
+use crate::{
+    map::{Key, Map},
+    parsing::Value,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct FormatConfig {
+    pub indent: Option<usize>,
+    /// Write object members sorted by key instead of in insertion order.
+    pub sort_keys: bool,
+    /// When pretty printing, arrays and objects whose single-line form fits within this many
+    /// columns are kept on one line, e.g. `[-65.61, 43.42]`.
+    pub max_width: Option<usize>,
+    pub numbers: NumberFormat,
+    #[cfg(feature = "bytes")]
+    pub bytes: BytesFormat,
+}
+
+/// How numbers are written. The default writes the shortest digits that read back as the same
+/// float, never using exponent notation.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct NumberFormat {
+    /// Use exponent notation when the decimal exponent is at least this, e.g. with `21`,
+    /// `1e21` is written as `1e21` rather than `1000000000000000000000`.
+    pub exponent_at_least: Option<i32>,
+    /// Use exponent notation when the decimal exponent is below this, e.g. with `-6`,
+    /// `0.0000001` is written as `1e-7`.
+    pub exponent_below: Option<i32>,
+    /// Write integral numbers with a fractional part, `1.0` rather than `1`.
+    pub force_decimal_point: bool,
+    /// Round to at most this many significant digits.
+    pub max_precision: Option<usize>,
+}
+
+impl FormatConfig {
+    pub fn compact() -> Self {
+        FormatConfig {
+            indent: None,
+            sort_keys: false,
+            max_width: None,
+            numbers: NumberFormat::default(),
+            #[cfg(feature = "bytes")]
+            bytes: BytesFormat::default(),
+        }
+    }
+
+    pub fn pretty() -> Self {
+        FormatConfig {
+            indent: Some(2),
+            ..FormatConfig::compact()
+        }
+    }
+}
+
+impl Default for FormatConfig {
+    fn default() -> Self {
+        FormatConfig::compact()
+    }
+}
+
+pub fn to_string(value: &Value, config: &FormatConfig) -> String {
+    let mut writer = Writer {
+        config,
+        out: String::new(),
+    };
+    writer.write_value(value, 0);
+    writer.out
+}
+
+/// Compact output with keys sorted by code point and `-0` written as `0`, so equal values
+/// always serialize to the same bytes.
+pub fn to_canonical_string(value: &Value) -> String {
+    let mut out = String::new();
+    write_canonical(&mut out, value);
+    out
+}
+
+fn write_canonical(out: &mut String, value: &Value) {
+    match value {
+        Value::Number(n) if *n == 0.0 => out.push('0'),
+        Value::Array(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(out, element);
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members = members.iter().collect::<Vec<(&Key, &Value)>>();
+            members.sort_by_key(|(key, _)| *key);
+
+            out.push('{');
+            for (i, (key, member)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, key);
+                out.push(':');
+                write_canonical(out, member);
+            }
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&to_string(self, &FormatConfig::compact()))
+    }
+}
+
+impl Value {
+    pub fn preview(&self, max_depth: usize, max_items: usize) -> String {
+        let mut out = String::new();
+        write_preview(&mut out, self, max_depth, max_items);
+        out
+    }
+}
+
+fn write_preview(out: &mut String, value: &Value, depth: usize, max_items: usize) {
+    match value {
+        Value::Array(elements) if elements.is_empty() => out.push_str("[]"),
+        Value::Array(_) if depth == 0 => out.push_str("[...]"),
+        Value::Array(elements) => {
+            out.push('[');
+            for (i, element) in elements.iter().take(max_items).enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_preview(out, element, depth - 1, max_items);
+            }
+            write_remaining(out, elements.len(), max_items);
+            out.push(']');
+        }
+        Value::Object(members) if members.is_empty() => out.push_str("{}"),
+        Value::Object(_) if depth == 0 => out.push_str("{...}"),
+        Value::Object(members) => {
+            out.push('{');
+            for (i, (key, member)) in members.iter().take(max_items).enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                write_string(out, key);
+                out.push_str(": ");
+                write_preview(out, member, depth - 1, max_items);
+            }
+            write_remaining(out, members.len(), max_items);
+            out.push('}');
+        }
+        value => out.push_str(&value.to_string()),
+    }
+}
+
+fn write_remaining(out: &mut String, len: usize, max_items: usize) {
+    if len > max_items {
+        if max_items > 0 {
+            out.push_str(", ");
+        }
+        write!(out, "…{} more", len - max_items).unwrap();
+    }
+}
+
+struct Writer<'a> {
+    config: &'a FormatConfig,
+    out: String,
+}
+
+impl Writer<'_> {
+    fn write_value(&mut self, value: &Value, depth: usize) {
+        if let (Some(_), Some(max_width), Value::Array(_) | Value::Object(_)) =
+            (self.config.indent, self.config.max_width, value)
+        {
+            let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+            let column = self.out[line_start..].chars().count();
+            let mut line = String::new();
+            if self.write_single_line(&mut line, value, max_width.saturating_sub(column)) {
+                self.out.push_str(&line);
+                return;
+            }
+        }
+
+        match value {
+            Value::Array(elements) if elements.is_empty() => self.out.push_str("[]"),
+            Value::Array(elements) => {
+                self.out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.write_newline(depth + 1);
+                    self.write_value(element, depth + 1);
+                }
+                self.write_newline(depth);
+                self.out.push(']');
+            }
+            Value::Object(members) if members.is_empty() => self.out.push_str("{}"),
+            Value::Object(members) => {
+                self.out.push('{');
+                for (i, (key, member)) in self.members(members).into_iter().enumerate() {
+                    if i > 0 {
+                        self.out.push(',');
+                    }
+                    self.write_newline(depth + 1);
+                    write_string(&mut self.out, key);
+                    self.out.push(':');
+                    if self.config.indent.is_some() {
+                        self.out.push(' ');
+                    }
+                    self.write_value(member, depth + 1);
+                }
+                self.write_newline(depth);
+                self.out.push('}');
+            }
+            value => write_scalar(&mut self.out, value, self.config),
+        }
+    }
+
+    fn members<'v>(&self, members: &'v Map) -> Vec<(&'v Key, &'v Value)> {
+        let mut members = members.iter().collect::<Vec<(&Key, &Value)>>();
+        if self.config.sort_keys {
+            members.sort_by_key(|(key, _)| *key);
+        }
+        members
+    }
+
+    fn write_single_line(&self, out: &mut String, value: &Value, budget: usize) -> bool {
+        match value {
+            Value::Array(elements) if !elements.is_empty() => {
+                out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    if !self.write_single_line(out, element, budget) {
+                        return false;
+                    }
+                }
+                out.push(']');
+            }
+            Value::Object(members) if !members.is_empty() => {
+                out.push_str("{ ");
+                for (i, (key, member)) in self.members(members).into_iter().enumerate() {
+                    if i > 0 {
+                        out.push_str(", ");
+                    }
+                    write_string(out, key);
+                    out.push_str(": ");
+                    if !self.write_single_line(out, member, budget) {
+                        return false;
+                    }
+                }
+                out.push_str(" }");
+            }
+            value => write_scalar(out, value, self.config),
+        }
+        out.chars().count() <= budget
+    }
+
+    fn write_newline(&mut self, depth: usize) {
+        if let Some(indent) = self.config.indent {
+            self.out.push('\n');
+            for _ in 0..indent * depth {
+                self.out.push(' ');
+            }
+        }
+    }
+}
+
+fn write_scalar(out: &mut String, value: &Value, config: &FormatConfig) {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => write_number(out, *n, &config.numbers),
+        Value::String(s) => write_string(out, s),
+        #[cfg(feature = "bytes")]
+        Value::Bytes(bytes) => write_string(out, &config.bytes.encode(bytes)),
+        Value::Array(_) => out.push_str("[]"),
+        Value::Object(_) => out.push_str("{}"),
+    }
+}
+
+/// Writes `n` with the digits of its own type, so an `f32` is not written with the noise of
+/// widening it to `f64`.
+pub(crate) fn write_number<N>(out: &mut String, n: N, format: &NumberFormat)
+where
+    N: Copy + Display + LowerExp + Into<f64>,
+{
+    let wide = n.into();
+    if !wide.is_finite() {
+        out.push_str("null");
+        return;
+    }
+    if *format == NumberFormat::default() {
+        write!(out, "{n}").unwrap();
+        return;
+    }
+
+    // `{:e}` gives the shortest round-tripping digits as `d.ddde±x`.
+    let scientific = match format.max_precision {
+        Some(precision) => format!("{:.*e}", precision.max(1) - 1, n),
+        None => format!("{n:e}"),
+    };
+    let (mantissa, exponent) = scientific.trim_start_matches('-').split_once('e').unwrap();
+    let exponent = exponent.parse::<i32>().unwrap();
+    let digits = mantissa.replace('.', "");
+    let digits = match digits.trim_end_matches('0') {
+        "" => "0",
+        digits => digits,
+    };
+
+    if wide.is_sign_negative() {
+        out.push('-');
+    }
+    let use_exponent = format.exponent_at_least.is_some_and(|e| exponent >= e)
+        || format.exponent_below.is_some_and(|e| exponent < e);
+    if use_exponent {
+        let (first, rest) = digits.split_at(1);
+        out.push_str(first);
+        if !rest.is_empty() {
+            write!(out, ".{rest}").unwrap();
+        } else if format.force_decimal_point {
+            out.push_str(".0");
+        }
+        write!(out, "e{exponent}").unwrap();
+    } else if exponent < 0 {
+        out.push_str("0.");
+        for _ in 0..-exponent - 1 {
+            out.push('0');
+        }
+        out.push_str(digits);
+    } else {
+        let integer_len = exponent as usize + 1;
+        if digits.len() > integer_len {
+            write!(out, "{}.{}", &digits[..integer_len], &digits[integer_len..]).unwrap();
+        } else {
+            out.push_str(digits);
+            for _ in digits.len()..integer_len {
+                out.push('0');
+            }
+            if format.force_decimal_point {
+                out.push_str(".0");
+            }
+        }
+    }
+}
+
+pub fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::{Float, Parser};
+
+    fn sample() -> Value {
+        Value::Object(
+            vec![
+                (
+                    "b".to_string(),
+                    Value::Array(vec![Value::Null, Value::Bool(true)]),
+                ),
+                ("a".to_string(), Value::Number(1.5)),
+                ("c".to_string(), Value::Object(Map::new())),
+            ]
+            .into_iter()
+            .collect(),
+        )
+    }
+
+    #[test]
+    fn compact_output_keeps_key_order() {
+        // Members are written in the map's order, which only the default map keeps as inserted.
+        let output = sample().to_string();
+        assert_eq!(Ok(sample()), Parser::parse(&output));
+        #[cfg(not(feature = "btree-map"))]
+        assert_eq!(r#"{"b":[null,true],"a":1.5,"c":{}}"#, output);
+
+        let config = FormatConfig {
+            sort_keys: true,
+            ..FormatConfig::compact()
+        };
+        assert_eq!(
+            r#"{"a":1.5,"b":[null,true],"c":{}}"#,
+            to_string(&sample(), &config)
+        );
+    }
+
+    #[test]
+    fn pretty_output() {
+        let config = FormatConfig {
+            sort_keys: true,
+            ..FormatConfig::pretty()
+        };
+        let expected = "{\n  \"a\": 1.5,\n  \"b\": [\n    null,\n    true\n  ],\n  \"c\": {}\n}";
+        assert_eq!(expected, to_string(&sample(), &config));
+    }
+
+    #[test]
+    fn max_width_keeps_short_containers_on_one_line() {
+        let coordinates = Value::Array(vec![
+            Value::Array(vec![Value::Number(-65.61), Value::Number(43.42)]),
+            Value::Array(vec![Value::Number(-65.62), Value::Number(43.43)]),
+        ]);
+        let config = FormatConfig {
+            max_width: Some(20),
+            ..FormatConfig::pretty()
+        };
+        let expected = "[\n  [-65.61, 43.42],\n  [-65.62, 43.43]\n]";
+        assert_eq!(expected, to_string(&coordinates, &config));
+
+        let config = FormatConfig {
+            max_width: Some(80),
+            sort_keys: true,
+            ..FormatConfig::pretty()
+        };
+        assert_eq!(
+            "[[-65.61, 43.42], [-65.62, 43.43]]",
+            to_string(&coordinates, &config)
+        );
+        assert_eq!(
+            r#"{ "a": 1.5, "b": [null, true], "c": {} }"#,
+            to_string(&sample(), &config)
+        );
+    }
+
+    #[test]
+    fn preview_truncates_depth_and_items() {
+        let statuses = (0..100)
+            .map(|_| {
+                Value::Object(
+                    vec![("id".to_string(), Value::Number(1.0))]
+                        .into_iter()
+                        .collect(),
+                )
+            })
+            .collect();
+        let document = Value::Object(
+            vec![("statuses".to_string(), Value::Array(statuses))]
+                .into_iter()
+                .collect(),
+        );
+
+        assert_eq!("{...}", document.preview(0, 3));
+        assert_eq!(r#"{"statuses": [...]}"#, document.preview(1, 3));
+        assert_eq!(
+            r#"{"statuses": [{...}, {...}, …98 more]}"#,
+            document.preview(2, 2)
+        );
+        assert_eq!(
+            r#"{"statuses": [{"id": 1}, …99 more]}"#,
+            document.preview(3, 1)
+        );
+        assert_eq!("{…1 more}", document.preview(3, 0));
+        assert_eq!("[]", Value::Array(Vec::new()).preview(0, 0));
+    }
+
+    #[test]
+    fn canonical_output() {
+        let value = Value::Array(vec![Value::Number(-0.0), sample()]);
+        assert_eq!(
+            r#"[0,{"a":1.5,"b":[null,true],"c":{}}]"#,
+            to_canonical_string(&value)
+        );
+    }
+
+    #[test]
+    fn escape_strings() {
+        let value = Value::String("a\"b\\c\n\u{1}".to_string());
+        assert_eq!(r#""a\"b\\c\n\u0001""#, value.to_string());
+    }
+
+    #[test]
+    fn number_format_options() {
+        let write = |n: f64, numbers: NumberFormat| {
+            let mut out = String::new();
+            write_number(&mut out, n, &numbers);
+            out
+        };
+        let js = || NumberFormat {
+            exponent_at_least: Some(21),
+            exponent_below: Some(-6),
+            ..NumberFormat::default()
+        };
+
+        assert_eq!("1e21", write(1e21, js()));
+        assert_eq!("123456789012345680000", write(1.2345678901234568e20, js()));
+        assert_eq!("-1.5e-7", write(-1.5e-7, js()));
+        assert_eq!("0.000001", write(1e-6, js()));
+        assert_eq!("-0.25", write(-0.25, js()));
+        assert_eq!("0", write(0.0, js()));
+
+        let decimal = || NumberFormat {
+            force_decimal_point: true,
+            ..js()
+        };
+        assert_eq!("1.0", write(1.0, decimal()));
+        assert_eq!("100.0", write(100.0, decimal()));
+        assert_eq!("1.5", write(1.5, decimal()));
+        assert_eq!("1.0e21", write(1e21, decimal()));
+
+        let precise = |precision| NumberFormat {
+            max_precision: Some(precision),
+            ..NumberFormat::default()
+        };
+        assert_eq!("3.14", write(std::f64::consts::PI, precise(3)));
+        assert_eq!("1200", write(1234.0, precise(2)));
+        assert_eq!("0.1", write(0.1 + 0.2 - 0.2, precise(15)));
+
+        let value = Value::Array(vec![Value::Number(2.0), Value::Number(0.5)]);
+        let config = FormatConfig {
+            max_width: Some(80),
+            numbers: decimal(),
+            ..FormatConfig::pretty()
+        };
+        assert_eq!("[2.0, 0.5]", to_string(&value, &config));
+    }
+
+    #[test]
+    fn non_finite_numbers_are_null() {
+        assert_eq!("null", Value::Number(Float::NAN).to_string());
+        assert_eq!("-3", Value::Number(-3.0).to_string());
+    }
+}
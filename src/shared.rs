@@ -0,0 +1,394 @@
+use std::{collections::HashMap, sync::Arc};
+
+use indexmap::IndexMap;
+
+use crate::{
+    parsing::{widen, Float, Value},
+    pointer::{parse_index, parse_pointer},
+    size::key_size,
+};
+
+/// An immutable document that is `Send + Sync` and cheap to clone: strings, arrays and objects
+/// are reference counted, so a clone shares the whole tree. Convert from a parsed [`Value`]
+/// once and hand clones to other threads or tasks.
+///
+/// Equality follows [`Value`]: NaN equals NaN and object members compare regardless of order.
+#[derive(Debug, Clone, Default)]
+pub enum ArcValue {
+    #[default]
+    Null,
+    Bool(bool),
+    Number(Float),
+    String(Arc<str>),
+    #[cfg(feature = "bytes")]
+    Bytes(Arc<[u8]>),
+    Array(Arc<Vec<ArcValue>>),
+    Object(Arc<IndexMap<Arc<str>, ArcValue>>),
+}
+
+impl PartialEq for ArcValue {
+    fn eq(&self, other: &ArcValue) -> bool {
+        match (self, other) {
+            (ArcValue::Null, ArcValue::Null) => true,
+            (ArcValue::Bool(a), ArcValue::Bool(b)) => a == b,
+            (ArcValue::Number(a), ArcValue::Number(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (ArcValue::String(a), ArcValue::String(b)) => a == b,
+            #[cfg(feature = "bytes")]
+            (ArcValue::Bytes(a), ArcValue::Bytes(b)) => a == b,
+            (ArcValue::Array(a), ArcValue::Array(b)) => Arc::ptr_eq(a, b) || a == b,
+            (ArcValue::Object(a), ArcValue::Object(b)) => Arc::ptr_eq(a, b) || a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for ArcValue {}
+
+impl ArcValue {
+    /// Looks up the value that a JSON pointer such as `/statuses/0/id` refers to.
+    pub fn pointer(&self, pointer: &str) -> Option<&ArcValue> {
+        parse_pointer(pointer)?
+            .iter()
+            .try_fold(self, |value, token| value.child(token))
+    }
+
+    pub fn child(&self, token: &str) -> Option<&ArcValue> {
+        match self {
+            ArcValue::Object(members) => members.get(token),
+            ArcValue::Array(elements) => elements.get(parse_index(token)?),
+            _ => None,
+        }
+    }
+
+    /// Returns a copy with the value at `pointer` set, or `None` if its parent does not exist.
+    /// Like a JSON Patch `add`, a missing key is inserted and `-` appends to an array. Only
+    /// the containers along the path are copied; every other subtree is shared with `self`.
+    pub fn with_set(&self, pointer: &str, value: ArcValue) -> Option<ArcValue> {
+        let tokens = parse_pointer(pointer)?;
+        let Some((last, path)) = tokens.split_last() else {
+            return Some(value);
+        };
+        let mut root = self.clone();
+        match root.pointer_make_mut(path)? {
+            ArcValue::Object(members) => {
+                Arc::make_mut(members).insert(last.as_str().into(), value);
+            }
+            ArcValue::Array(elements) => {
+                let index = match last.as_str() {
+                    "-" => elements.len(),
+                    token => parse_index(token).filter(|i| *i <= elements.len())?,
+                };
+                let elements = Arc::make_mut(elements);
+                if index == elements.len() {
+                    elements.push(value);
+                } else {
+                    elements[index] = value;
+                }
+            }
+            _ => return None,
+        }
+        Some(root)
+    }
+
+    /// Returns a copy without the value at `pointer`, or `None` if there is none, sharing
+    /// unchanged subtrees as [`ArcValue::with_set`] does.
+    pub fn with_removed(&self, pointer: &str) -> Option<ArcValue> {
+        let tokens = parse_pointer(pointer)?;
+        let (last, path) = tokens.split_last()?;
+        let mut root = self.clone();
+        match root.pointer_make_mut(path)? {
+            ArcValue::Object(members) if members.contains_key(last.as_str()) => {
+                Arc::make_mut(members).shift_remove(last.as_str());
+            }
+            ArcValue::Array(elements) => {
+                let index = parse_index(last).filter(|i| *i < elements.len())?;
+                Arc::make_mut(elements).remove(index);
+            }
+            _ => return None,
+        }
+        Some(root)
+    }
+
+    /// Walks `tokens`, unsharing each container on the way so it can be changed in place.
+    fn pointer_make_mut(&mut self, tokens: &[String]) -> Option<&mut ArcValue> {
+        tokens.iter().try_fold(self, |value, token| match value {
+            ArcValue::Object(members) if members.contains_key(token.as_str()) => {
+                Arc::make_mut(members).get_mut(token.as_str())
+            }
+            ArcValue::Array(elements) => {
+                let index = parse_index(token).filter(|i| *i < elements.len())?;
+                Arc::make_mut(elements).get_mut(index)
+            }
+            _ => None,
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            ArcValue::Null => Value::Null,
+            ArcValue::Bool(b) => Value::Bool(*b),
+            ArcValue::Number(n) => Value::Number(*n),
+            ArcValue::String(s) => Value::String(s.to_string()),
+            #[cfg(feature = "bytes")]
+            ArcValue::Bytes(bytes) => Value::Bytes(bytes.to_vec()),
+            ArcValue::Array(elements) => {
+                Value::Array(elements.iter().map(ArcValue::to_value).collect())
+            }
+            ArcValue::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (&**key, value.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// A document whose repeated subtrees share one allocation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dedup {
+    pub value: ArcValue,
+    /// Bytes the repeats took up in the original [`Value`], as [`Value::estimate_size`]
+    /// counts them.
+    pub bytes_saved: usize,
+}
+
+impl Value {
+    /// Converts to an [`ArcValue`] in which identical strings, object keys, arrays and objects
+    /// are stored once and shared. Subtrees are identical when they would serialize the same,
+    /// so objects whose keys are in a different order are kept apart.
+    pub fn dedup_subtrees(&self) -> Dedup {
+        let mut interner = Interner::default();
+        let id = interner.add(self);
+        Dedup {
+            value: interner.values.swap_remove(id),
+            bytes_saved: interner.bytes_saved,
+        }
+    }
+}
+
+/// A subtree in terms of its already interned children.
+#[derive(PartialEq, Eq, Hash)]
+enum Shape {
+    Null,
+    Bool(bool),
+    Number(u64),
+    #[cfg(feature = "bytes")]
+    Bytes(Vec<u8>),
+    Array(Vec<usize>),
+    Object(Vec<(Arc<str>, usize)>),
+}
+
+#[derive(Default)]
+struct Interner {
+    strings: HashMap<Arc<str>, usize>,
+    shapes: HashMap<Shape, usize>,
+    values: Vec<ArcValue>,
+    bytes_saved: usize,
+}
+
+impl Interner {
+    /// Returns the id of the interned copy of `value`.
+    fn add(&mut self, value: &Value) -> usize {
+        let shape = match value {
+            Value::Null => Shape::Null,
+            Value::Bool(b) => Shape::Bool(*b),
+            Value::Number(n) => Shape::Number(widen(*n).to_bits()),
+            Value::String(s) => return self.string(s, s.capacity()).0,
+            #[cfg(feature = "bytes")]
+            Value::Bytes(bytes) => Shape::Bytes(bytes.clone()),
+            Value::Array(elements) => {
+                Shape::Array(elements.iter().map(|element| self.add(element)).collect())
+            }
+            Value::Object(members) => Shape::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (self.string(key, key_size(key)).1, self.add(value)))
+                    .collect(),
+            ),
+        };
+        if let Some(&id) = self.shapes.get(&shape) {
+            // Every child of a repeat is itself a repeat and has been counted already.
+            self.bytes_saved += value.allocation_size();
+            return id;
+        }
+
+        let value = match &shape {
+            Shape::Null => ArcValue::Null,
+            Shape::Bool(b) => ArcValue::Bool(*b),
+            Shape::Number(bits) => ArcValue::Number(f64::from_bits(*bits) as Float),
+            #[cfg(feature = "bytes")]
+            Shape::Bytes(bytes) => ArcValue::Bytes(bytes.as_slice().into()),
+            Shape::Array(ids) => ArcValue::Array(Arc::new(
+                ids.iter().map(|id| self.values[*id].clone()).collect(),
+            )),
+            Shape::Object(members) => ArcValue::Object(Arc::new(
+                members
+                    .iter()
+                    .map(|(key, id)| (Arc::clone(key), self.values[*id].clone()))
+                    .collect(),
+            )),
+        };
+        let id = self.push(value);
+        self.shapes.insert(shape, id);
+        id
+    }
+
+    fn string(&mut self, s: &str, size: usize) -> (usize, Arc<str>) {
+        if let Some((s, &id)) = self.strings.get_key_value(s) {
+            self.bytes_saved += size;
+            return (id, Arc::clone(s));
+        }
+        let s = Arc::<str>::from(s);
+        let id = self.push(ArcValue::String(Arc::clone(&s)));
+        self.strings.insert(Arc::clone(&s), id);
+        (id, s)
+    }
+
+    fn push(&mut self, value: ArcValue) -> usize {
+        self.values.push(value);
+        self.values.len() - 1
+    }
+}
+
+impl From<Value> for ArcValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null => ArcValue::Null,
+            Value::Bool(b) => ArcValue::Bool(b),
+            Value::Number(n) => ArcValue::Number(n),
+            Value::String(s) => ArcValue::String(s.into()),
+            #[cfg(feature = "bytes")]
+            Value::Bytes(bytes) => ArcValue::Bytes(bytes.into()),
+            Value::Array(elements) => {
+                ArcValue::Array(Arc::new(elements.into_iter().map(ArcValue::from).collect()))
+            }
+            Value::Object(members) => ArcValue::Object(Arc::new(
+                members
+                    .into_iter()
+                    .map(|(key, value)| (Arc::from(&*key), ArcValue::from(value)))
+                    .collect(),
+            )),
+        }
+    }
+}
+
+impl From<&ArcValue> for Value {
+    fn from(value: &ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+impl From<ArcValue> for Value {
+    fn from(value: ArcValue) -> Self {
+        value.to_value()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn share_across_threads() {
+        let value = Parser::parse(r#"{"a": [1, {"b": "x"}], "c": null, "n": 0}"#).unwrap();
+        let shared = ArcValue::from(value.clone());
+
+        let handles = (0..4)
+            .map(|_| {
+                let shared = shared.clone();
+                thread::spawn(move || shared.pointer("/a/1/b").cloned())
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(Some(ArcValue::String("x".into())), handle.join().unwrap());
+        }
+
+        let (ArcValue::Object(a), ArcValue::Object(b)) = (&shared, &shared.clone()) else {
+            panic!("expected an object")
+        };
+        assert!(Arc::ptr_eq(a, b));
+        assert_eq!(value, Value::from(&shared));
+        assert_eq!(None, shared.pointer("/a/2"));
+    }
+
+    #[test]
+    fn dedup_repeated_subtrees() {
+        let value = Parser::parse(
+            r#"[{"id": 1, "tags": ["ab"]}, {"id": 1, "tags": ["ab"]}, "ab", {"tags": 1, "id": 1}]"#,
+        )
+        .unwrap();
+        let Dedup {
+            value: dedup,
+            bytes_saved,
+        } = value.dedup_subtrees();
+        assert_eq!(value, Value::from(&dedup));
+
+        let Value::Array(elements) = &value else {
+            panic!("expected an array")
+        };
+        let Value::Object(last) = &elements[3] else {
+            panic!("expected an object")
+        };
+        let repeated = |value: &Value| value.estimate_size() - std::mem::size_of::<Value>();
+        assert_eq!(
+            repeated(&elements[1])
+                + repeated(&elements[2])
+                + last.iter().map(|(key, _)| key_size(key)).sum::<usize>(),
+            bytes_saved
+        );
+
+        let at = |pointer| dedup.pointer(pointer).unwrap();
+        let (ArcValue::Object(a), ArcValue::Object(b), ArcValue::Object(c)) =
+            (at("/0"), at("/1"), at("/3"))
+        else {
+            panic!("expected objects")
+        };
+        assert!(Arc::ptr_eq(a, b));
+        assert!(!Arc::ptr_eq(a, c));
+        let (ArcValue::String(a), ArcValue::String(b)) = (at("/0/tags/0"), at("/2")) else {
+            panic!("expected strings")
+        };
+        assert!(Arc::ptr_eq(a, b));
+
+        assert_eq!(0, Value::Null.dedup_subtrees().bytes_saved);
+    }
+
+    #[test]
+    fn edit_by_copying_the_path() {
+        let v1 = ArcValue::from(Parser::parse(r#"{"a": {"b": 1}, "c": [1, [2]]}"#).unwrap());
+        let v2 = v1.with_set("/a/b", ArcValue::Number(2.0)).unwrap();
+        let v3 = v2
+            .with_set("/c/-", ArcValue::Null)
+            .and_then(|v| v.with_removed("/c/0"))
+            .and_then(|v| v.with_set("/d", ArcValue::Bool(true)))
+            .unwrap();
+
+        let as_string = |value: &ArcValue| Value::from(value).to_string();
+        assert_eq!(r#"{"a":{"b":1},"c":[1,[2]]}"#, as_string(&v1));
+        assert_eq!(r#"{"a":{"b":2},"c":[1,[2]]}"#, as_string(&v2));
+        assert_eq!(r#"{"a":{"b":2},"c":[[2],null],"d":true}"#, as_string(&v3));
+
+        let shared = |a: &ArcValue, b: &ArcValue| match (a, b) {
+            (ArcValue::Array(a), ArcValue::Array(b)) => Arc::ptr_eq(a, b),
+            (ArcValue::Object(a), ArcValue::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        };
+        let at = |value: &ArcValue, pointer| value.pointer(pointer).unwrap().clone();
+        assert!(shared(&at(&v1, "/c"), &at(&v2, "/c")));
+        assert!(shared(&at(&v1, "/c/1"), &at(&v3, "/c/0")));
+        assert!(!shared(&at(&v1, "/a"), &at(&v2, "/a")));
+        assert!(shared(&at(&v2, "/a"), &at(&v3, "/a")));
+
+        assert_eq!(Some(ArcValue::Null), v1.with_set("", ArcValue::Null));
+        assert_eq!(None, v1.with_set("/x/y", ArcValue::Null));
+        assert_eq!(None, v1.with_set("/c/3", ArcValue::Null));
+        assert_eq!(None, v1.with_set("/a/b/c", ArcValue::Null));
+        assert_eq!(None, v1.with_removed("/a/x"));
+        assert_eq!(None, v1.with_removed("/c/2"));
+        assert_eq!(None, v1.with_removed(""));
+    }
+}
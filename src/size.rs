@@ -0,0 +1,109 @@
+use std::mem::size_of;
+
+use crate::{
+    map::{Key, Map},
+    parsing::Value,
+};
+
+impl Value {
+    /// Approximate bytes used by this value, counting its own size plus the heap allocations
+    /// it owns. Allocated capacity is counted rather than length, and each object member also
+    /// pays for the hash and index slot of its map. Allocator bookkeeping is not included.
+    pub fn estimate_size(&self) -> usize {
+        size_of::<Value>() + self.heap_size()
+    }
+
+    pub(crate) fn heap_size(&self) -> usize {
+        self.allocation_size()
+            + match self {
+                Value::Null | Value::Bool(_) | Value::Number(_) | Value::String(_) => 0,
+                #[cfg(feature = "bytes")]
+                Value::Bytes(_) => 0,
+                Value::Array(elements) => elements.iter().map(Value::heap_size).sum::<usize>(),
+                Value::Object(members) => members
+                    .iter()
+                    .map(|(key, value)| key_size(key) + value.heap_size())
+                    .sum::<usize>(),
+            }
+    }
+
+    /// Bytes of the allocation this value owns directly, leaving out its elements' and
+    /// members' own allocations.
+    pub(crate) fn allocation_size(&self) -> usize {
+        match self {
+            Value::Null | Value::Bool(_) | Value::Number(_) => 0,
+            Value::String(s) => s.capacity(),
+            #[cfg(feature = "bytes")]
+            Value::Bytes(bytes) => bytes.capacity(),
+            Value::Array(elements) => elements.capacity() * size_of::<Value>(),
+            Value::Object(members) => members_size(members),
+        }
+    }
+}
+
+/// Small maps store entries as (key, value) in a vector. Larger ones store them as
+/// (hash, key, value) next to a table of indices with a control byte each.
+#[cfg(not(feature = "btree-map"))]
+fn members_size(members: &Map) -> usize {
+    if !members.is_indexed() {
+        return members.capacity() * (size_of::<Key>() + size_of::<Value>());
+    }
+    let entry = size_of::<u64>() + size_of::<Key>() + size_of::<Value>();
+    let index = size_of::<usize>() + 1;
+    members.capacity() * (entry + index)
+}
+
+/// Entries are stored as (key, value) in tree nodes, whose spare slots are not counted.
+#[cfg(feature = "btree-map")]
+fn members_size(members: &Map) -> usize {
+    members.len() * (size_of::<Key>() + size_of::<Value>())
+}
+
+#[cfg(not(any(feature = "box-keys", feature = "arc-keys")))]
+pub(crate) fn key_size(key: &Key) -> usize {
+    key.capacity()
+}
+
+#[cfg(all(feature = "box-keys", not(feature = "arc-keys")))]
+pub(crate) fn key_size(key: &Key) -> usize {
+    key.len()
+}
+
+/// The reference counts are allocated along with the text.
+#[cfg(feature = "arc-keys")]
+pub(crate) fn key_size(key: &Key) -> usize {
+    2 * size_of::<usize>() + key.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{map::Map, parsing::Parser};
+
+    #[test]
+    fn estimate_nested_values() {
+        let value_size = size_of::<Value>();
+        assert_eq!(value_size, Value::Null.estimate_size());
+        assert_eq!(
+            value_size + 10,
+            Value::String(String::with_capacity(10)).estimate_size()
+        );
+
+        let mut elements = Vec::with_capacity(4);
+        elements.push(Value::String("abc".to_string()));
+        assert_eq!(
+            value_size + 4 * value_size + 3,
+            Value::Array(elements).estimate_size()
+        );
+
+        let members = [("key".to_string(), Value::Bool(true))]
+            .into_iter()
+            .collect::<Map>();
+        let object = Value::Object(members);
+        assert!(object.estimate_size() > value_size + 3 + value_size);
+
+        let small = Parser::parse(r#"{"a": [1]}"#).unwrap();
+        let large = Parser::parse(r#"{"a": [1, 2, 3, 4, 5, 6, 7, 8], "b": "xxxxxxxx"}"#).unwrap();
+        assert!(large.estimate_size() > small.estimate_size());
+    }
+}
@@ -0,0 +1,336 @@
+use std::{borrow::Borrow, hash::Hash, ops::Index};
+
+use indexmap::IndexMap;
+
+/// An insertion-ordered map that keeps up to [`SmallMap::INLINE_LEN`] entries in a plain
+/// vector, found by comparing keys in turn, and moves them into an `IndexMap` once it grows
+/// past that. Most objects in real documents are that small, and the vector costs them one
+/// allocation where an `IndexMap` costs two: its entries and its table of hashes.
+#[derive(Debug, Clone)]
+pub struct SmallMap<K, V> {
+    storage: Storage<K, V>,
+}
+
+#[derive(Debug, Clone)]
+enum Storage<K, V> {
+    Inline(Vec<(K, V)>),
+    Indexed(IndexMap<K, V>),
+}
+
+impl<K, V> Default for SmallMap<K, V> {
+    fn default() -> Self {
+        SmallMap {
+            storage: Storage::Inline(Vec::new()),
+        }
+    }
+}
+
+impl<K: Hash + Eq, V> SmallMap<K, V> {
+    /// The most entries kept in the vector.
+    pub const INLINE_LEN: usize = 8;
+
+    pub fn new() -> Self {
+        SmallMap::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let storage = if capacity <= Self::INLINE_LEN {
+            Storage::Inline(Vec::with_capacity(capacity))
+        } else {
+            Storage::Indexed(IndexMap::with_capacity(capacity))
+        };
+        SmallMap { storage }
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(entries) => entries.len(),
+            Storage::Indexed(map) => map.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.storage {
+            Storage::Inline(entries) => entries.capacity(),
+            Storage::Indexed(map) => map.capacity(),
+        }
+    }
+
+    /// Whether the entries have moved into an `IndexMap`.
+    pub fn is_indexed(&self) -> bool {
+        matches!(self.storage, Storage::Indexed(_))
+    }
+
+    pub fn get<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        match &self.storage {
+            Storage::Inline(entries) => entries
+                .iter()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, value)| value),
+            Storage::Indexed(map) => map.get(key),
+        }
+    }
+
+    pub fn get_mut<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        match &mut self.storage {
+            Storage::Inline(entries) => entries
+                .iter_mut()
+                .find(|(k, _)| k.borrow() == key)
+                .map(|(_, value)| value),
+            Storage::Indexed(map) => map.get_mut(key),
+        }
+    }
+
+    pub fn contains_key<Q: ?Sized + Hash + Eq>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    /// Inserts `value` at `key`, returning the value it replaced. A replaced entry keeps its
+    /// position, and a new one goes at the end.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let entries = match &mut self.storage {
+            Storage::Indexed(map) => return map.insert(key, value),
+            Storage::Inline(entries) => entries,
+        };
+        if let Some((_, old)) = entries.iter_mut().find(|(k, _)| *k == key) {
+            return Some(std::mem::replace(old, value));
+        }
+        if entries.len() < Self::INLINE_LEN {
+            entries.push((key, value));
+        } else {
+            let mut map = IndexMap::with_capacity(entries.len() * 2);
+            map.extend(entries.drain(..));
+            map.insert(key, value);
+            self.storage = Storage::Indexed(map);
+        }
+        None
+    }
+
+    /// Removes `key`, shifting the entries after it so the order of the rest is kept.
+    pub fn shift_remove<Q: ?Sized + Hash + Eq>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        match &mut self.storage {
+            Storage::Inline(entries) => {
+                let position = entries.iter().position(|(k, _)| k.borrow() == key)?;
+                Some(entries.remove(position).1)
+            }
+            Storage::Indexed(map) => map.shift_remove(key),
+        }
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&K, &mut V) -> bool) {
+        match &mut self.storage {
+            Storage::Inline(entries) => entries.retain_mut(|(key, value)| keep(key, value)),
+            Storage::Indexed(map) => map.retain(keep),
+        }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + ExactSizeIterator {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + ExactSizeIterator {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn values_mut(&mut self) -> impl DoubleEndedIterator<Item = &mut V> + ExactSizeIterator {
+        self.iter_mut().map(|(_, value)| value)
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        match &self.storage {
+            Storage::Inline(entries) => Iter::Inline(entries.iter()),
+            Storage::Indexed(map) => Iter::Indexed(map.iter()),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        match &mut self.storage {
+            Storage::Inline(entries) => IterMut::Inline(entries.iter_mut()),
+            Storage::Indexed(map) => IterMut::Indexed(map.iter_mut()),
+        }
+    }
+
+    /// Reorders the entries by key, keeping the order of equal keys.
+    pub fn sort_keys(&mut self)
+    where
+        K: Ord,
+    {
+        match &mut self.storage {
+            Storage::Inline(entries) => entries.sort_by(|(a, _), (b, _)| a.cmp(b)),
+            Storage::Indexed(map) => map.sort_keys(),
+        }
+    }
+}
+
+/// Maps are equal if they have the same entries, in any order.
+impl<K: Hash + Eq, V: PartialEq> PartialEq for SmallMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl<K: Hash + Eq, V: Eq> Eq for SmallMap<K, V> {}
+
+impl<K: Hash + Eq + Borrow<Q>, Q: ?Sized + Hash + Eq, V> Index<&Q> for SmallMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &Q) -> &V {
+        self.get(key).expect("the key to be in the map")
+    }
+}
+
+impl<K: Hash + Eq, V> Extend<(K, V)> for SmallMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for SmallMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> IntoIter<K, V> {
+        match self.storage {
+            Storage::Inline(entries) => IntoIter::Inline(entries.into_iter()),
+            Storage::Indexed(map) => IntoIter::Indexed(map.into_iter()),
+        }
+    }
+}
+
+pub enum Iter<'a, K, V> {
+    Inline(std::slice::Iter<'a, (K, V)>),
+    Indexed(indexmap::map::Iter<'a, K, V>),
+}
+
+pub enum IterMut<'a, K, V> {
+    Inline(std::slice::IterMut<'a, (K, V)>),
+    Indexed(indexmap::map::IterMut<'a, K, V>),
+}
+
+pub enum IntoIter<K, V> {
+    Inline(std::vec::IntoIter<(K, V)>),
+    Indexed(indexmap::map::IntoIter<K, V>),
+}
+
+/// Implements the iterator traits for one of the iterators above, given how to turn an item
+/// of its `Inline` variant into an item of the map.
+macro_rules! iterator {
+    ($iter:ty, $item:ty, $inline:expr) => {
+        impl<'a, K, V> Iterator for $iter {
+            type Item = $item;
+
+            fn next(&mut self) -> Option<$item> {
+                match self {
+                    Self::Inline(entries) => entries.next().map($inline),
+                    Self::Indexed(entries) => entries.next(),
+                }
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                match self {
+                    Self::Inline(entries) => entries.size_hint(),
+                    Self::Indexed(entries) => entries.size_hint(),
+                }
+            }
+        }
+
+        impl<'a, K, V> DoubleEndedIterator for $iter {
+            fn next_back(&mut self) -> Option<$item> {
+                match self {
+                    Self::Inline(entries) => entries.next_back().map($inline),
+                    Self::Indexed(entries) => entries.next_back(),
+                }
+            }
+        }
+
+        impl<'a, K, V> ExactSizeIterator for $iter {}
+    };
+}
+
+iterator!(Iter<'a, K, V>, (&'a K, &'a V), |(key, value)| (key, value));
+iterator!(IterMut<'a, K, V>, (&'a K, &'a mut V), |(key, value)| (
+    &*key, value
+));
+iterator!(IntoIter<K, V>, (K, V), |entry| entry);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn move_into_index_past_inline_len() {
+        let mut map = SmallMap::<String, usize>::with_capacity(2);
+        for i in 0..SmallMap::<String, usize>::INLINE_LEN {
+            assert_eq!(None, map.insert(i.to_string(), i));
+        }
+        assert!(!map.is_indexed());
+        assert_eq!(Some(3), map.insert("3".to_string(), 30));
+
+        map.insert("x".to_string(), 9);
+        assert!(map.is_indexed());
+        assert_eq!(9, map.len());
+        assert_eq!(30, map["3"]);
+        assert_eq!(
+            vec!["0", "1", "2", "3", "4", "5", "6", "7", "x"],
+            map.keys().map(String::as_str).collect::<Vec<&str>>()
+        );
+    }
+
+    #[test]
+    fn behave_alike_inline_and_indexed() {
+        let small = SmallMap::<String, usize>::with_capacity(3);
+        let large = SmallMap::<String, usize>::with_capacity(100);
+        assert!(!small.is_indexed() && large.is_indexed());
+
+        for mut map in [small, large] {
+            map.extend([("b", 1), ("a", 2), ("c", 3), ("a", 4)].map(|(k, v)| (k.to_string(), v)));
+            assert_eq!(Some(&4), map.get("a"));
+            assert_eq!(Some(1), map.shift_remove("b"));
+            assert_eq!(None, map.shift_remove("b"));
+            map.insert("0".to_string(), 0);
+            *map.get_mut("c").unwrap() += 10;
+            assert_eq!(
+                vec![("a", 4), ("c", 13), ("0", 0)],
+                map.iter()
+                    .map(|(k, v)| (k.as_str(), *v))
+                    .collect::<Vec<(&str, usize)>>()
+            );
+
+            map.sort_keys();
+            map.retain(|_, value| *value > 0);
+            assert_eq!(vec![&4, &13], map.values().collect::<Vec<&usize>>());
+            assert_eq!(
+                vec![("c".to_string(), 13), ("a".to_string(), 4)],
+                map.clone()
+                    .into_iter()
+                    .rev()
+                    .collect::<Vec<(String, usize)>>()
+            );
+
+            let mut reversed = SmallMap::new();
+            reversed.extend(map.clone().into_iter().rev());
+            assert_eq!(map, reversed);
+        }
+    }
+}
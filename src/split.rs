@@ -0,0 +1,242 @@
+use std::{fmt, fmt::Display, io, io::Write};
+
+use crate::{
+    errors::Error,
+    stream::{Event, StreamParser},
+};
+
+#[derive(Debug)]
+pub enum SplitError {
+    NotAnArray,
+    Parse(Error),
+    Io(io::Error),
+}
+
+impl Display for SplitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplitError::NotAnArray => f.write_str("Expected a top-level array"),
+            SplitError::Parse(error) => write!(f, "{error}"),
+            SplitError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for SplitError {}
+
+impl From<Error> for SplitError {
+    fn from(error: Error) -> Self {
+        SplitError::Parse(error)
+    }
+}
+
+impl From<io::Error> for SplitError {
+    fn from(error: io::Error) -> Self {
+        SplitError::Io(error)
+    }
+}
+
+/// The text of each element of a top-level array, exactly as it appears in the input.
+/// Elements are validated as they are read, as is the end of the document.
+pub struct Elements<'a> {
+    json: &'a str,
+    reader: StreamParser<'a>,
+    done: bool,
+}
+
+pub fn elements(json: &str) -> Result<Elements<'_>, SplitError> {
+    let mut reader = StreamParser::new(json);
+    match reader.next_event().transpose()? {
+        Some((Event::StartArray, _)) => Ok(Elements {
+            json,
+            reader,
+            done: false,
+        }),
+        _ => Err(SplitError::NotAnArray),
+    }
+}
+
+impl<'a> Elements<'a> {
+    fn read_element(&mut self) -> Result<Option<&'a str>, Error> {
+        let Some((event, span)) = self.reader.next_event().transpose()? else {
+            return Ok(None);
+        };
+        if event == Event::EndArray {
+            // Reports anything after the array.
+            self.reader.next_event().transpose()?;
+            return Ok(None);
+        }
+
+        let mut end = span.end;
+        let mut depth = usize::from(matches!(event, Event::StartArray | Event::StartObject));
+        while depth > 0 {
+            let Some((event, span)) = self.reader.next_event().transpose()? else {
+                break;
+            };
+            match event {
+                Event::StartArray | Event::StartObject => depth += 1,
+                Event::EndArray | Event::EndObject => depth -= 1,
+                _ => {}
+            }
+            end = span.end;
+        }
+        Ok(Some(&self.json[span.start..end]))
+    }
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Result<&'a str, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let element = self.read_element().transpose();
+        self.done = !matches!(element, Some(Ok(_)));
+        element
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Split {
+    /// Arrays of at most this many elements.
+    Size(usize),
+    /// This many arrays, whose lengths differ by at most one.
+    Parts(usize),
+}
+
+/// Splits the top-level array in `json` into smaller arrays, passing each one to `write` in
+/// order. Elements are copied as they appear in the input. Returns the number of arrays.
+pub fn split_array(
+    json: &str,
+    split: Split,
+    mut write: impl FnMut(&str) -> io::Result<()>,
+) -> Result<usize, SplitError> {
+    // The first `longer` arrays get one extra element.
+    let (size, longer) = match split {
+        Split::Size(size) => (size.max(1), 0),
+        Split::Parts(parts) => {
+            let count = elements(json)?.try_fold(0, |count, element| element.map(|_| count + 1))?;
+            (count / parts.max(1), count % parts.max(1))
+        }
+    };
+
+    let mut chunks = 0;
+    let mut chunk = Vec::<&str>::new();
+    for element in elements(json)? {
+        chunk.push(element?);
+        if chunk.len() >= size + usize::from(chunks < longer) {
+            write(&format!("[{}]", chunk.join(",")))?;
+            chunk.clear();
+            chunks += 1;
+        }
+    }
+    let total = match split {
+        Split::Size(_) => chunks + usize::from(!chunk.is_empty()),
+        Split::Parts(parts) => parts.max(1),
+    };
+    while chunks < total {
+        write(&format!("[{}]", chunk.join(",")))?;
+        chunk.clear();
+        chunks += 1;
+    }
+    Ok(chunks)
+}
+
+/// Concatenates the elements of top-level arrays into one array, one input at a time.
+pub struct ArrayJoiner<W: Write> {
+    out: W,
+    elements: usize,
+}
+
+impl<W: Write> ArrayJoiner<W> {
+    pub fn new(out: W) -> Self {
+        ArrayJoiner { out, elements: 0 }
+    }
+
+    pub fn append(&mut self, json: &str) -> Result<(), SplitError> {
+        for element in elements(json)? {
+            let element = element?;
+            self.out
+                .write_all(if self.elements == 0 { b"[" } else { b"," })?;
+            self.out.write_all(element.as_bytes())?;
+            self.elements += 1;
+        }
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> io::Result<W> {
+        if self.elements == 0 {
+            self.out.write_all(b"[")?;
+        }
+        self.out.write_all(b"]")?;
+        Ok(self.out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    fn split(json: &str, split: Split) -> Result<Vec<String>, SplitError> {
+        let mut chunks = Vec::<String>::new();
+        let count = split_array(json, split, |chunk| {
+            chunks.push(chunk.to_string());
+            Ok(())
+        })?;
+        assert_eq!(count, chunks.len());
+        Ok(chunks)
+    }
+
+    #[test]
+    fn split_by_size_and_parts() {
+        let json = r#" [1, {"a": [2, "]"]}, [], "x" , null] "#;
+        assert_eq!(
+            vec![r#"[1,{"a": [2, "]"]}]"#, r#"[[],"x"]"#, "[null]"],
+            split(json, Split::Size(2)).unwrap()
+        );
+        assert_eq!(
+            vec![r#"[1,{"a": [2, "]"]},[]]"#, r#"["x",null]"#],
+            split(json, Split::Parts(2)).unwrap()
+        );
+        assert_eq!(
+            vec!["[1]", "[2]", "[]"],
+            split("[1, 2]", Split::Parts(3)).unwrap()
+        );
+        assert!(split("[]", Split::Size(2)).unwrap().is_empty());
+    }
+
+    #[test]
+    fn join_arrays() {
+        let mut joiner = ArrayJoiner::new(Vec::<u8>::new());
+        for part in ["[1, {\"a\": 2}]", "[]", " [[3]] "] {
+            joiner.append(part).unwrap();
+        }
+        let out = joiner.finish().unwrap();
+        assert_eq!(r#"[1,{"a": 2},[3]]"#, String::from_utf8(out).unwrap());
+
+        let out = ArrayJoiner::new(Vec::<u8>::new()).finish().unwrap();
+        assert_eq!("[]", String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn fail_on_invalid_input() {
+        assert!(matches!(
+            split(r#"{"a": []}"#, Split::Size(1)),
+            Err(SplitError::NotAnArray)
+        ));
+        let failures = [
+            ("[1, 2 3]", ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            ("[1, [2}", ErrorCode::ExpectedCommaOrEndWhileParsing(']')),
+            ("[1] 2", ErrorCode::EndOfFileExpected),
+            ("[1, ", ErrorCode::EndOfFileWhileParsing(']')),
+        ];
+        for (json, code) in failures {
+            let Err(SplitError::Parse(error)) = split(json, Split::Size(1)) else {
+                panic!("{json} did not fail");
+            };
+            assert_eq!(code, error.code(), "{json}");
+        }
+    }
+}
@@ -0,0 +1,710 @@
+use crate::{
+    decode::{DecodeError, FromValue},
+    encode::ToValue,
+    errors::{Error, ErrorCode, Expected},
+    lexical::{self, Span, Token, Unescaper},
+    map::Map,
+    parsing::{unquoted_key, ParseOptions, Value},
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Expect {
+    Value,
+    ValueOrEnd,
+    Key,
+    KeyOrEnd,
+    Colon,
+    CommaOrEnd(char),
+    EndOfFile,
+}
+
+impl Expect {
+    const NAMES: [(Expect, &'static str); 6] = [
+        (Expect::Value, "value"),
+        (Expect::ValueOrEnd, "value-or-end"),
+        (Expect::Key, "key"),
+        (Expect::KeyOrEnd, "key-or-end"),
+        (Expect::Colon, "colon"),
+        (Expect::EndOfFile, "end-of-file"),
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Expect::CommaOrEnd(_) => "comma-or-end",
+            _ => Expect::NAMES.iter().find(|(e, _)| *e == self).unwrap().1,
+        }
+    }
+
+    fn from_name(name: &str, containers: &[char]) -> Option<Expect> {
+        match name {
+            "comma-or-end" => containers.last().map(|end| Expect::CommaOrEnd(*end)),
+            _ => Expect::NAMES
+                .iter()
+                .find(|(_, n)| *n == name)
+                .map(|(e, _)| *e),
+        }
+    }
+}
+
+/// Where a [`StreamParser`] is in its input: the byte offset after the last event, and the
+/// containers it is inside. A long-running job can save one, e.g. with [`ToValue`], and
+/// resume from it after a restart without reading the input again from the start.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Checkpoint {
+    position: Span,
+    containers: Vec<char>,
+    expect: Expect,
+}
+
+impl Checkpoint {
+    pub fn offset(&self) -> usize {
+        self.position.end
+    }
+}
+
+impl ToValue for Checkpoint {
+    fn to_value(&self) -> Value {
+        let mut members = Map::new();
+        members.insert("offset".to_string(), self.position.end.to_value());
+        members.insert("line".to_string(), self.position.line.to_value());
+        members.insert("col".to_string(), self.position.col.to_value());
+        members.insert(
+            "containers".to_string(),
+            self.containers.iter().collect::<String>().to_value(),
+        );
+        members.insert("expect".to_string(), self.expect.name().to_value());
+        Value::Object(members)
+    }
+}
+
+impl FromValue for Checkpoint {
+    fn from_value(value: &Value) -> Result<Self, DecodeError> {
+        let Value::Object(members) = value else {
+            return Err(DecodeError::new("an object", value));
+        };
+        let field = |key: &str| members.get(key).unwrap_or(&Value::Null);
+        let number = |key: &str| usize::from_value(field(key)).map_err(|e| e.at(key));
+
+        let (offset, line, col) = (number("offset")?, number("line")?, number("col")?);
+        let containers = String::from_value(field("containers"))
+            .ok()
+            .filter(|c| c.chars().all(|c| c == ']' || c == '}'))
+            .ok_or_else(|| DecodeError::new("closing brackets", field("containers")))
+            .map_err(|e| e.at("containers"))?
+            .chars()
+            .collect::<Vec<char>>();
+        let expect = String::from_value(field("expect"))
+            .ok()
+            .and_then(|name| Expect::from_name(&name, &containers))
+            .ok_or_else(|| DecodeError::new("a parser state", field("expect")))
+            .map_err(|e| e.at("expect"))?;
+        Ok(Checkpoint {
+            position: Span::new(offset, offset, line, col),
+            containers,
+            expect,
+        })
+    }
+}
+
+pub struct StreamParser<'a> {
+    reader: lexical::Reader<'a>,
+    containers: Vec<char>,
+    expect: Expect,
+    failed: bool,
+    unescaper: Unescaper,
+    options: ParseOptions,
+}
+
+impl<'a> StreamParser<'a> {
+    pub fn new(json: &'a str) -> StreamParser<'a> {
+        StreamParser::with_options(json, ParseOptions::default())
+    }
+
+    /// A parser that accepts the extensions to JSON that `options` allow: trailing commas,
+    /// single quotes, and unquoted keys. Unlike [`Parser`](crate::parsing::Parser), it
+    /// reports no warnings for them.
+    pub fn with_options(json: &'a str, options: ParseOptions) -> StreamParser<'a> {
+        StreamParser {
+            reader: lexical::Reader::new(json)
+                .with_single_quotes(options.allow_single_quotes)
+                .with_identifiers(options.allow_unquoted_keys),
+            containers: Vec::<char>::new(),
+            expect: Expect::Value,
+            failed: false,
+            unescaper: Unescaper::new(),
+            options,
+        }
+    }
+
+    /// Continues parsing `json` from a checkpoint taken on the same input. Returns `None` if
+    /// the checkpoint's offset is not in `json`.
+    pub fn resume(json: &'a str, checkpoint: &Checkpoint) -> Option<StreamParser<'a>> {
+        Some(StreamParser {
+            reader: lexical::Reader::resume(json, checkpoint.position)?,
+            containers: checkpoint.containers.clone(),
+            expect: checkpoint.expect,
+            failed: false,
+            unescaper: Unescaper::new(),
+            options: ParseOptions::default(),
+        })
+    }
+
+    /// Records where the parser is, between events.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            position: self.reader.position(),
+            containers: self.containers.clone(),
+            expect: self.expect,
+        }
+    }
+
+    pub fn next_event(&mut self) -> Option<Result<(Event, Span), Error>> {
+        if self.failed {
+            return None;
+        }
+        match self.read_event()? {
+            Ok(event) => Some(Ok((event, self.reader.last_span()))),
+            Err(error) => {
+                self.failed = true;
+                Some(Err(error))
+            }
+        }
+    }
+
+    /// Skips the next value, including everything nested inside it, by counting brackets.
+    /// Strings and numbers inside the skipped value are not decoded or validated.
+    pub fn skip_value(&mut self) -> Result<(), Error> {
+        if self.failed {
+            return Ok(());
+        }
+        let result = self.skip();
+        if result.is_err() {
+            self.failed = true;
+        }
+        result
+    }
+
+    /// Whether the next event closes the innermost container.
+    pub fn at_container_end(&mut self) -> bool {
+        matches!(
+            self.reader.peek(1).as_slice(),
+            [Ok(Token::Punctuation(']' | '}'))]
+        )
+    }
+
+    fn skip(&mut self) -> Result<(), Error> {
+        match self.expect {
+            Expect::Colon => self.read_colon()?,
+            Expect::CommaOrEnd(']') => match self.reader.peek(1).as_slice() {
+                [Ok(Token::Punctuation(','))] => {
+                    self.reader.next(1);
+                    self.expect = self.after_comma(']');
+                }
+                [Ok(Token::Punctuation(']'))] => return Ok(()),
+                _ => match self.read_event() {
+                    Some(Err(error)) => return Err(error),
+                    _ => unreachable!("anything but a comma or the end is an error"),
+                },
+            },
+            _ => {}
+        }
+        if !matches!(self.expect, Expect::Value | Expect::ValueOrEnd) {
+            let error = self.reader.create_error_next(ErrorCode::ExpectedToken);
+            return Err(error.with_expected(Expected::VALUE));
+        }
+        if let [Ok(Token::Punctuation(']'))] = self.reader.peek(1).as_slice() {
+            if self.expect == Expect::ValueOrEnd {
+                return Ok(());
+            }
+        }
+
+        let mut depth = 0usize;
+        loop {
+            let token = match self.reader.next(1).pop() {
+                Some(token) => token?,
+                None if depth == 0 => return Err(self.error(ErrorCode::EndOfFileWhileParsingValue)),
+                None => {
+                    let end = *self.containers.last().unwrap_or(&']');
+                    return Err(self
+                        .reader
+                        .create_error(ErrorCode::EndOfFileWhileParsing(end))
+                        .with_expected(Expected::COMMA | Expected::end_of(end)));
+                }
+            };
+            match token {
+                Token::Punctuation('{' | '[') => depth += 1,
+                Token::Punctuation('}' | ']') if depth > 0 => depth -= 1,
+                Token::Punctuation(_) if depth == 0 => {
+                    return Err(self.error(ErrorCode::ExpectedToken))
+                }
+                _ => {}
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+
+        self.after_value();
+        Ok(())
+    }
+
+    fn read_event(&mut self) -> Option<Result<Event, Error>> {
+        if self.expect == Expect::Colon {
+            if let Err(error) = self.read_colon() {
+                return Some(Err(error));
+            }
+        }
+
+        let token = match self.reader.next(1).pop() {
+            Some(Ok(token)) => token,
+            Some(Err(error)) => return Some(Err(error)),
+            None => {
+                return match (self.expect, self.containers.last()) {
+                    (Expect::EndOfFile, _) => None,
+                    (_, Some(end)) => Some(Err(self.error(ErrorCode::EndOfFileWhileParsing(*end)))),
+                    (_, None) => Some(Err(self.error(ErrorCode::EndOfFileWhileParsingValue))),
+                }
+            }
+        };
+
+        let event = match (self.expect, token) {
+            (Expect::Value | Expect::ValueOrEnd, token) => self.read_value(token),
+            (Expect::Key | Expect::KeyOrEnd, Token::String(s)) => self.read_string(&s).map(|key| {
+                self.expect = Expect::Colon;
+                Event::Key(key)
+            }),
+            (Expect::KeyOrEnd, Token::Punctuation('}')) => Ok(self.end_container()),
+            (Expect::Key | Expect::KeyOrEnd, token)
+                if self.options.allow_unquoted_keys && unquoted_key(&token).is_some() =>
+            {
+                self.expect = Expect::Colon;
+                Ok(Event::Key(
+                    unquoted_key(&token).unwrap_or_default().to_string(),
+                ))
+            }
+            (Expect::Key | Expect::KeyOrEnd, _) => Err(self.error(ErrorCode::KeyMustBeAString)),
+            (Expect::CommaOrEnd(end), Token::Punctuation(',')) => {
+                self.expect = self.after_comma(end);
+                return self.read_event();
+            }
+            (Expect::CommaOrEnd(end), Token::Punctuation(c)) if c == end => {
+                Ok(self.end_container())
+            }
+            (Expect::CommaOrEnd(end), _) => {
+                Err(self.error(ErrorCode::ExpectedCommaOrEndWhileParsing(end)))
+            }
+            (Expect::EndOfFile, _) => Err(self.error(ErrorCode::EndOfFileExpected)),
+            (Expect::Colon, _) => unreachable!("colons are consumed before reading a token"),
+        };
+        Some(event)
+    }
+
+    fn read_value(&mut self, token: Token) -> Result<Event, Error> {
+        let event = match token {
+            Token::Punctuation('{') => {
+                self.containers.push('}');
+                self.expect = Expect::KeyOrEnd;
+                return Ok(Event::StartObject);
+            }
+            Token::Punctuation('[') => {
+                self.containers.push(']');
+                self.expect = Expect::ValueOrEnd;
+                return Ok(Event::StartArray);
+            }
+            Token::Punctuation(']') if self.expect == Expect::ValueOrEnd => {
+                return Ok(self.end_container());
+            }
+            Token::Null => Event::Null,
+            Token::Bool(b) => Event::Bool(b),
+            Token::Number(n) => Event::Number(self.read_number(&n)?),
+            Token::String(s) => Event::String(self.read_string(&s)?),
+            _ => return Err(self.error(ErrorCode::ExpectedToken)),
+        };
+        self.after_value();
+        Ok(event)
+    }
+
+    fn read_number(&self, token: &str) -> Result<f64, Error> {
+        if let Some(n) = lexical::small_integer(token) {
+            return Ok(n);
+        }
+        match lexical::validate_number(token).map(|_| token.parse::<f64>()) {
+            Ok(Ok(n)) => Ok(n),
+            _ => Err(self
+                .reader
+                .create_error(ErrorCode::invalid_number(token, self.reader.last_span()))),
+        }
+    }
+
+    fn read_string(&mut self, token: &str) -> Result<String, Error> {
+        let single_quoted = token.starts_with('\'');
+        let contents = if single_quoted {
+            lexical::single_quoted_contents(token)
+        } else {
+            lexical::string_contents(token)
+        };
+        let contents =
+            contents.ok_or_else(|| self.reader.create_error(ErrorCode::ExpectedDoubleQuote))?;
+        let unescaped = if single_quoted {
+            self.unescaper.unescape_json5(contents)
+        } else {
+            self.unescaper.unescape(contents)
+        };
+        unescaped
+            .map(str::to_string)
+            .map_err(|_| self.reader.create_error(ErrorCode::InvalidEscape))
+    }
+
+    fn read_colon(&mut self) -> Result<(), Error> {
+        match self.reader.next(1).pop() {
+            Some(Ok(Token::Punctuation(':'))) => {
+                self.expect = Expect::Value;
+                Ok(())
+            }
+            Some(Err(error)) => Err(error),
+            _ => Err(self.error(ErrorCode::ExpectedColon)),
+        }
+    }
+
+    fn error(&self, code: ErrorCode) -> Error {
+        let expected = match self.expect {
+            Expect::Value => Expected::VALUE,
+            Expect::ValueOrEnd => Expected::VALUE | Expected::END_OF_ARRAY,
+            Expect::Key => Expected::KEY,
+            Expect::KeyOrEnd => Expected::KEY | Expected::END_OF_OBJECT,
+            Expect::Colon => Expected::COLON,
+            Expect::CommaOrEnd(end) => Expected::COMMA | Expected::end_of(end),
+            Expect::EndOfFile => Expected::END_OF_FILE,
+        };
+        self.reader.create_error(code).with_expected(expected)
+    }
+
+    /// What may follow a comma in the container that `end` closes.
+    fn after_comma(&self, end: char) -> Expect {
+        match (end, self.options.allow_trailing_commas) {
+            ('}', false) => Expect::Key,
+            ('}', true) => Expect::KeyOrEnd,
+            (_, false) => Expect::Value,
+            (_, true) => Expect::ValueOrEnd,
+        }
+    }
+
+    fn end_container(&mut self) -> Event {
+        let end = self.containers.pop();
+        self.after_value();
+        match end {
+            Some('}') => Event::EndObject,
+            _ => Event::EndArray,
+        }
+    }
+
+    fn after_value(&mut self) {
+        self.expect = match self.containers.last() {
+            Some(end) => Expect::CommaOrEnd(*end),
+            None => Expect::EndOfFile,
+        };
+    }
+}
+
+impl Iterator for StreamParser<'_> {
+    type Item = Result<(Event, Span), Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    fn events(json: &str) -> Vec<Result<Event, Error>> {
+        StreamParser::new(json)
+            .map(|e| e.map(|(event, _)| event))
+            .collect()
+    }
+
+    fn next(parser: &mut StreamParser) -> Option<Result<Event, Error>> {
+        parser.next_event().map(|e| e.map(|(event, _)| event))
+    }
+
+    #[test]
+    fn pass_nested_document() {
+        assert_eq!(
+            vec![
+                Ok(Event::StartObject),
+                Ok(Event::Key("a".to_string())),
+                Ok(Event::StartArray),
+                Ok(Event::Number(1.0)),
+                Ok(Event::String("x".to_string())),
+                Ok(Event::StartObject),
+                Ok(Event::EndObject),
+                Ok(Event::StartArray),
+                Ok(Event::EndArray),
+                Ok(Event::EndArray),
+                Ok(Event::Key("b".to_string())),
+                Ok(Event::Null),
+                Ok(Event::Key("c".to_string())),
+                Ok(Event::Bool(false)),
+                Ok(Event::EndObject),
+            ],
+            events(r#"{"a": [1, "x", {}, []], "b": null, "c": false}"#)
+        );
+        assert_eq!(vec![Ok(Event::Bool(true))], events(" true "));
+    }
+
+    #[test]
+    fn fail_and_stop_on_first_error() {
+        assert_eq!(
+            vec![
+                Ok(Event::StartArray),
+                Ok(Event::Bool(false)),
+                Err(
+                    Error::new(ErrorCode::ExpectedCommaOrEndWhileParsing(']'), 1, 8)
+                        .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+                ),
+            ],
+            events(r#"[false "a"]"#)
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::StartObject),
+                Err(Error::new(ErrorCode::KeyMustBeAString, 1, 2)
+                    .with_expected(Expected::KEY | Expected::END_OF_OBJECT))
+            ],
+            events("{1: 2}")
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::StartArray),
+                Ok(Event::Bool(true)),
+                Err(Error::new(ErrorCode::EndOfFileWhileParsing(']'), 1, 2)
+                    .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)),
+            ],
+            events("[true")
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::Null),
+                Err(Error::new(ErrorCode::EndOfFileExpected, 1, 6)
+                    .with_expected(Expected::END_OF_FILE))
+            ],
+            events("null null")
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::StartArray),
+                Err(Error::new(
+                    ErrorCode::invalid_number("01", Span::new(3, 5, 2, 2)),
+                    2,
+                    2
+                )),
+            ],
+            events("[\n 01]")
+        );
+    }
+
+    #[test]
+    fn events_carry_spans() {
+        let json = "{\n  \"é\": [1.5, true]\n}";
+        let spans = StreamParser::new(json)
+            .map(|e| e.unwrap().1)
+            .collect::<Vec<Span>>();
+        assert_eq!(
+            vec![
+                Span::new(0, 1, 1, 1),
+                Span::new(4, 8, 2, 3),
+                Span::new(10, 11, 2, 8),
+                Span::new(11, 14, 2, 9),
+                Span::new(16, 20, 2, 14),
+                Span::new(20, 21, 2, 18),
+                Span::new(22, 23, 3, 1),
+            ],
+            spans
+        );
+        assert_eq!("\"é\"", &json[spans[1].start..spans[1].end]);
+        assert_eq!("true", &json[spans[4].start..spans[4].end]);
+    }
+
+    #[test]
+    fn resume_from_checkpoint() {
+        let json = "{\n  \"é\": [1.5, true]\n}";
+        let all = StreamParser::new(json).collect::<Vec<_>>();
+        for taken in 0..all.len() {
+            let mut parser = StreamParser::new(json);
+            parser.by_ref().take(taken).for_each(drop);
+            let saved = parser.checkpoint().to_value().to_string();
+            let checkpoint = Parser::parse(&saved)
+                .unwrap()
+                .decode::<Checkpoint>()
+                .unwrap();
+            let resumed = StreamParser::resume(json, &checkpoint).unwrap();
+            assert_eq!(all[taken..], resumed.collect::<Vec<_>>(), "{saved}");
+        }
+
+        let mut parser = StreamParser::new("[1, 2");
+        parser.by_ref().take(2).for_each(drop);
+        let checkpoint = parser.checkpoint();
+        assert_eq!(2, checkpoint.offset());
+        assert_eq!(
+            Some(Err(Error::new(ErrorCode::EndOfFileWhileParsing(']'), 1, 5)
+                .with_expected(Expected::COMMA | Expected::END_OF_ARRAY))),
+            StreamParser::resume("[1, 2", &checkpoint)
+                .unwrap()
+                .map(|e| e.map(|(event, _)| event))
+                .nth(1)
+        );
+        assert!(StreamParser::resume("[", &checkpoint).is_none());
+    }
+
+    #[test]
+    fn fail_to_decode_checkpoint() {
+        let decode = |json: &str| {
+            Parser::parse(json)
+                .unwrap()
+                .decode::<Checkpoint>()
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(
+            Err("Expected closing brackets, found a string at /containers".to_string()),
+            decode(r#"{"offset": 1, "line": 1, "col": 2, "containers": "[", "expect": "value"}"#)
+        );
+        assert_eq!(
+            Err("Expected a parser state, found a string at /expect".to_string()),
+            decode(
+                r#"{"offset": 1, "line": 1, "col": 2, "containers": "", "expect": "comma-or-end"}"#
+            )
+        );
+        assert_eq!(
+            Err("Expected an integer in range of usize, found null at /col".to_string()),
+            decode(r#"{"offset": 1, "line": 1}"#)
+        );
+    }
+
+    #[test]
+    fn skip_member_values() {
+        let mut parser = StreamParser::new(
+            r#"{"skip": {"a": [1, {"b": "\"]"}], "c": 2}, "keep": [true], "last": 3}"#,
+        );
+        assert_eq!(Some(Ok(Event::StartObject)), next(&mut parser));
+        assert_eq!(Some(Ok(Event::Key("skip".to_string()))), next(&mut parser));
+        assert_eq!(Ok(()), parser.skip_value());
+        assert_eq!(Some(Ok(Event::Key("keep".to_string()))), next(&mut parser));
+        assert_eq!(Some(Ok(Event::StartArray)), next(&mut parser));
+        assert_eq!(Ok(()), parser.skip_value());
+        assert_eq!(Some(Ok(Event::EndArray)), next(&mut parser));
+        assert_eq!(Some(Ok(Event::Key("last".to_string()))), next(&mut parser));
+        assert_eq!(Ok(()), parser.skip_value());
+        assert_eq!(Some(Ok(Event::EndObject)), next(&mut parser));
+        assert_eq!(None, next(&mut parser));
+    }
+
+    #[test]
+    fn skip_array_elements() {
+        let mut parser = StreamParser::new("[[1, [2]], 3, {}]");
+        assert_eq!(Some(Ok(Event::StartArray)), next(&mut parser));
+        assert_eq!(Ok(()), parser.skip_value());
+        assert_eq!(Some(Ok(Event::Number(3.0))), next(&mut parser));
+        assert_eq!(Ok(()), parser.skip_value());
+        assert_eq!(Some(Ok(Event::EndArray)), next(&mut parser));
+        assert_eq!(None, next(&mut parser));
+    }
+
+    #[test]
+    fn fail_skip_after_missing_comma() {
+        let mut parser = StreamParser::new("[1 2]");
+        assert_eq!(Some(Ok(Event::StartArray)), next(&mut parser));
+        assert_eq!(Some(Ok(Event::Number(1.0))), next(&mut parser));
+        assert!(!parser.at_container_end());
+        assert_eq!(
+            Err(
+                Error::new(ErrorCode::ExpectedCommaOrEndWhileParsing(']'), 1, 4)
+                    .with_expected(Expected::COMMA | Expected::END_OF_ARRAY)
+            ),
+            parser.skip_value()
+        );
+        assert_eq!(None, next(&mut parser));
+    }
+
+    #[test]
+    fn fail_skip_unclosed_value() {
+        let mut parser = StreamParser::new(r#"{"a": [1, 2"#);
+        assert_eq!(Some(Ok(Event::StartObject)), next(&mut parser));
+        assert_eq!(Some(Ok(Event::Key("a".to_string()))), next(&mut parser));
+        assert_eq!(
+            Err(Error::new(ErrorCode::EndOfFileWhileParsing('}'), 1, 11)
+                .with_expected(Expected::COMMA | Expected::END_OF_OBJECT)),
+            parser.skip_value()
+        );
+        assert_eq!(None, next(&mut parser));
+    }
+
+    #[test]
+    fn pass_lenient_document_with_options() {
+        let options = ParseOptions {
+            allow_trailing_commas: true,
+            allow_single_quotes: true,
+            allow_unquoted_keys: true,
+            ..ParseOptions::default()
+        };
+        let lenient = StreamParser::with_options("{a: ['x\\'y', 1,], 'b': 2,}", options)
+            .map(|e| e.map(|(event, _)| event))
+            .collect::<Vec<Result<Event, Error>>>();
+        assert_eq!(
+            vec![
+                Ok(Event::StartObject),
+                Ok(Event::Key("a".to_string())),
+                Ok(Event::StartArray),
+                Ok(Event::String("x'y".to_string())),
+                Ok(Event::Number(1.0)),
+                Ok(Event::EndArray),
+                Ok(Event::Key("b".to_string())),
+                Ok(Event::Number(2.0)),
+                Ok(Event::EndObject),
+            ],
+            lenient
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::StartArray),
+                Ok(Event::Number(1.0)),
+                Err(Error::new(ErrorCode::ExpectedToken, 1, 4).with_expected(Expected::VALUE))
+            ],
+            events("[1,]")
+        );
+    }
+
+    #[test]
+    fn decode_escaped_strings() {
+        assert_eq!(
+            vec![
+                Ok(Event::StartObject),
+                Ok(Event::Key("a\tb".to_string())),
+                Ok(Event::String("\u{1f600}".to_string())),
+                Ok(Event::EndObject),
+            ],
+            events(r#"{"a\tb": "\ud83d\ude00"}"#)
+        );
+        assert_eq!(
+            vec![
+                Ok(Event::StartArray),
+                Err(Error::new(ErrorCode::InvalidEscape, 1, 2))
+            ],
+            events(r#"["\q"]"#)
+        );
+    }
+}
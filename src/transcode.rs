@@ -0,0 +1,407 @@
+use std::{collections::HashMap, fmt, fmt::Display, fmt::Write as _, io};
+
+use crate::{
+    encode::ToValue,
+    errors::Error,
+    lexical::Span,
+    parsing::{escape_key, Value},
+    stream::{Event, StreamParser},
+    writer::JsonWriter,
+};
+
+#[derive(Debug)]
+pub enum TranscodeError {
+    Parse(Error),
+    Io(io::Error),
+}
+
+impl Display for TranscodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TranscodeError::Parse(error) => write!(f, "{error}"),
+            TranscodeError::Io(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for TranscodeError {}
+
+impl From<Error> for TranscodeError {
+    fn from(error: Error) -> Self {
+        TranscodeError::Parse(error)
+    }
+}
+
+impl From<io::Error> for TranscodeError {
+    fn from(error: io::Error) -> Self {
+        TranscodeError::Io(error)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Decision {
+    Keep,
+    Drop,
+    /// Writes an object member under a different key. Ignored for array elements.
+    Rename(String),
+}
+
+/// Decides the fate of each object member and array element as it is read. `parent` is the
+/// JSON pointer of the enclosing container and indices count elements in the input.
+pub trait Filter {
+    fn decide(&mut self, parent: &str, segment: Segment) -> Decision;
+
+    /// Applies `self` first, then `next` to whatever `self` keeps, with the renamed key if any.
+    fn and<F: Filter>(self, next: F) -> Chain<Self, F>
+    where
+        Self: Sized,
+    {
+        Chain(self, next)
+    }
+}
+
+pub struct Chain<A, B>(A, B);
+
+impl<A: Filter, B: Filter> Filter for Chain<A, B> {
+    fn decide(&mut self, parent: &str, segment: Segment) -> Decision {
+        match (self.0.decide(parent, segment), segment) {
+            (Decision::Drop, _) => Decision::Drop,
+            (Decision::Rename(key), Segment::Key(_)) => {
+                match self.1.decide(parent, Segment::Key(&key)) {
+                    Decision::Keep => Decision::Rename(key),
+                    decision => decision,
+                }
+            }
+            (_, segment) => self.1.decide(parent, segment),
+        }
+    }
+}
+
+/// Drops members with any of the given keys, at any depth.
+pub struct DropKeys(Vec<String>);
+
+impl DropKeys {
+    pub fn new<I: IntoIterator<Item = S>, S: Into<String>>(keys: I) -> Self {
+        DropKeys(keys.into_iter().map(Into::into).collect())
+    }
+}
+
+impl Filter for DropKeys {
+    fn decide(&mut self, _: &str, segment: Segment) -> Decision {
+        match segment {
+            Segment::Key(key) if self.0.iter().any(|k| k == key) => Decision::Drop,
+            _ => Decision::Keep,
+        }
+    }
+}
+
+/// Renames members from the first key of each pair to the second, at any depth.
+pub struct RenameKeys(HashMap<String, String>);
+
+impl RenameKeys {
+    pub fn new<I: IntoIterator<Item = (S, S)>, S: Into<String>>(renames: I) -> Self {
+        RenameKeys(
+            renames
+                .into_iter()
+                .map(|(from, to)| (from.into(), to.into()))
+                .collect(),
+        )
+    }
+}
+
+impl Filter for RenameKeys {
+    fn decide(&mut self, _: &str, segment: Segment) -> Decision {
+        match segment {
+            Segment::Key(key) => match self.0.get(key) {
+                Some(to) => Decision::Rename(to.clone()),
+                None => Decision::Keep,
+            },
+            Segment::Index(_) => Decision::Keep,
+        }
+    }
+}
+
+/// Keeps the first of every `n` array elements.
+pub struct SampleEvery {
+    n: usize,
+    at: Option<String>,
+}
+
+impl SampleEvery {
+    pub fn new(n: usize) -> Self {
+        SampleEvery {
+            n: n.max(1),
+            at: None,
+        }
+    }
+
+    /// Only samples the array at this JSON pointer instead of every array.
+    pub fn at(mut self, pointer: impl Into<String>) -> Self {
+        self.at = Some(pointer.into());
+        self
+    }
+}
+
+impl Filter for SampleEvery {
+    fn decide(&mut self, parent: &str, segment: Segment) -> Decision {
+        match segment {
+            Segment::Index(i)
+                if i % self.n != 0 && self.at.as_ref().is_none_or(|at| at == parent) =>
+            {
+                Decision::Drop
+            }
+            _ => Decision::Keep,
+        }
+    }
+}
+
+struct KeepAll;
+
+impl Filter for KeepAll {
+    fn decide(&mut self, _: &str, _: Segment) -> Decision {
+        Decision::Keep
+    }
+}
+
+/// Where each key, scalar, and bracket of transcoded output was read from in the input, so
+/// that a position in the output, such as that of an error found in it, can be traced back.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct SourceMap {
+    /// Output and input spans, in output order.
+    mappings: Vec<(Span, Span)>,
+}
+
+impl SourceMap {
+    pub fn mappings(&self) -> &[(Span, Span)] {
+        &self.mappings
+    }
+
+    /// The input span of the output token at `line` and `col`, or of the last one before it.
+    pub fn to_input(&self, line: usize, col: usize) -> Option<Span> {
+        let after = self
+            .mappings
+            .partition_point(|(output, _)| (output.line, output.col) <= (line, col));
+        Some(self.mappings.get(after.checked_sub(1)?)?.1)
+    }
+}
+
+/// An array of `[output line, output column, input line, input column]` for each token.
+impl ToValue for SourceMap {
+    fn to_value(&self) -> Value {
+        Value::Array(
+            self.mappings
+                .iter()
+                .map(|(output, input)| [output.line, output.col, input.line, input.col].to_value())
+                .collect(),
+        )
+    }
+}
+
+pub fn transcode<W: io::Write>(
+    reader: StreamParser,
+    writer: JsonWriter<W>,
+) -> Result<W, TranscodeError> {
+    transcode_filtered(reader, writer, &mut KeepAll)
+}
+
+/// Like [`transcode`], but also maps every token of the output back to the input. The map
+/// grows with the size of the document.
+pub fn transcode_with_source_map<W: io::Write>(
+    reader: StreamParser,
+    writer: JsonWriter<W>,
+) -> Result<(W, SourceMap), TranscodeError> {
+    let mut source_map = SourceMap::default();
+    let out = copy(reader, writer, &mut KeepAll, Some(&mut source_map))?;
+    Ok((out, source_map))
+}
+
+/// Copies every event from `reader` to `writer`, skipping or renaming members and elements as
+/// `filter` decides. Memory use is bounded by nesting depth, not document size.
+pub fn transcode_filtered<W: io::Write>(
+    reader: StreamParser,
+    writer: JsonWriter<W>,
+    filter: &mut impl Filter,
+) -> Result<W, TranscodeError> {
+    copy(reader, writer, filter, None)
+}
+
+fn copy<W: io::Write>(
+    mut reader: StreamParser,
+    mut writer: JsonWriter<W>,
+    filter: &mut impl Filter,
+    mut source_map: Option<&mut SourceMap>,
+) -> Result<W, TranscodeError> {
+    // For each open container: the length of `pointer` before it, and the next element index
+    // if it is an array.
+    let mut containers = Vec::<(usize, Option<usize>)>::new();
+    let mut pointer = String::new();
+    let mut segment = String::new();
+
+    while let Some(event) = reader.next_event() {
+        let (event, span) = event?;
+
+        if let (Some((_, Some(index))), false) = (containers.last_mut(), event == Event::EndArray) {
+            let i = *index;
+            *index += 1;
+            if filter.decide(&pointer, Segment::Index(i)) == Decision::Drop {
+                skip_rest(&mut reader, &event)?;
+                continue;
+            }
+            segment.clear();
+            write!(segment, "{i}").unwrap();
+        }
+
+        match event {
+            Event::Key(key) => match filter.decide(&pointer, Segment::Key(&key)) {
+                Decision::Drop => {
+                    reader.skip_value()?;
+                    continue;
+                }
+                Decision::Rename(to) => {
+                    writer.key(&to)?;
+                    segment = to;
+                }
+                Decision::Keep => {
+                    writer.key(&key)?;
+                    segment = key;
+                }
+            },
+            Event::StartObject | Event::StartArray => {
+                containers.push((pointer.len(), (event == Event::StartArray).then_some(0)));
+                if containers.len() > 1 {
+                    write!(pointer, "/{}", escape_key(&segment)).unwrap();
+                }
+                writer.write_event(&event)?;
+            }
+            Event::EndObject | Event::EndArray => {
+                if let Some((len, _)) = containers.pop() {
+                    pointer.truncate(len);
+                }
+                writer.write_event(&event)?;
+            }
+            event => writer.write_event(&event)?,
+        }
+        if let Some(source_map) = source_map.as_deref_mut() {
+            source_map.mappings.push((writer.last_span(), span));
+        }
+    }
+
+    Ok(writer.finish()?)
+}
+
+/// Consumes the rest of a value whose first event has already been read.
+fn skip_rest(reader: &mut StreamParser, first: &Event) -> Result<(), TranscodeError> {
+    let mut depth = match first {
+        Event::StartObject | Event::StartArray => 1,
+        _ => return Ok(()),
+    };
+    while depth > 0 {
+        match reader.next_event() {
+            Some(Ok((Event::StartObject | Event::StartArray, _))) => depth += 1,
+            Some(Ok((Event::EndObject | Event::EndArray, _))) => depth -= 1,
+            Some(Ok(_)) => {}
+            Some(Err(error)) => return Err(error.into()),
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::FormatConfig;
+
+    const DOCUMENT: &str = r#"{
+        "statuses": [
+            {"id": 1, "id_str": "1", "user": {"id": 9, "tags": [1, 2, 3]}},
+            {"id": 2, "id_str": "2", "user": null},
+            {"id": 3, "id_str": "3", "user": {"id": 8, "tags": []}}
+        ],
+        "count": 3
+    }"#;
+
+    fn run(filter: &mut impl Filter) -> String {
+        let writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        let out = transcode_filtered(StreamParser::new(DOCUMENT), writer, filter).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn map_output_to_input() {
+        let json = "{\n  \"a\": [1,\n    \"x\"],\n  \"b\": {}\n}";
+        let writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        let (out, source_map) = transcode_with_source_map(StreamParser::new(json), writer).unwrap();
+        assert_eq!(r#"{"a":[1,"x"],"b":{}}"#, String::from_utf8(out).unwrap());
+
+        let position = |span: Option<Span>| span.map(|span| (span.line, span.col));
+        // `"x"`, `]`, and `}` of the output.
+        assert_eq!(Some((3, 5)), position(source_map.to_input(1, 9)));
+        assert_eq!(Some((3, 8)), position(source_map.to_input(1, 12)));
+        assert_eq!(Some((5, 1)), position(source_map.to_input(1, 20)));
+        // Inside `"x"` and on the comma after it.
+        assert_eq!(Some((3, 5)), position(source_map.to_input(1, 10)));
+        assert_eq!(Some((3, 5)), position(source_map.to_input(1, 11)));
+        assert_eq!(None, source_map.to_input(0, 1));
+        assert_eq!(Some((5, 1)), position(source_map.to_input(2, 1)));
+
+        assert_eq!(10, source_map.mappings().len());
+        assert_eq!(
+            "[[1,1,1,1],[1,2,2,3]]",
+            Value::Array(match source_map.to_value() {
+                Value::Array(mappings) => mappings[..2].to_vec(),
+                _ => panic!("expected an array"),
+            })
+            .to_string()
+        );
+    }
+
+    #[test]
+    fn copy_without_filters() {
+        let writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        let out = transcode(StreamParser::new(r#"[1, {"a": [true]}]"#), writer).unwrap();
+        assert_eq!(r#"[1,{"a":[true]}]"#, String::from_utf8(out).unwrap());
+    }
+
+    #[test]
+    fn drop_and_rename_keys() {
+        assert_eq!(
+            r#"{"statuses":[{"id":"1"},{"id":"2"},{"id":"3"}],"count":3}"#,
+            run(&mut DropKeys::new(["id", "user"]).and(RenameKeys::new([("id_str", "id")])))
+        );
+    }
+
+    #[test]
+    fn sample_array_elements() {
+        let writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        let out = transcode_filtered(
+            StreamParser::new("[[1, 2, 3, 4, 5], [6], [7, 8]]"),
+            writer,
+            &mut SampleEvery::new(2),
+        )
+        .unwrap();
+        assert_eq!("[[1,3,5],[7]]", String::from_utf8(out).unwrap());
+
+        assert_eq!(
+            r#"{"statuses":[{"id":1},{"id":3}],"count":3}"#,
+            run(&mut SampleEvery::new(2)
+                .at("/statuses")
+                .and(DropKeys::new(["id_str", "user"])))
+        );
+    }
+
+    #[test]
+    fn fail_on_invalid_input() {
+        let writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        assert!(matches!(
+            transcode(StreamParser::new("[1 2]"), writer),
+            Err(TranscodeError::Parse(_))
+        ));
+    }
+}
@@ -0,0 +1,300 @@
+use crate::{
+    map::Map,
+    parsing::Value,
+    pointer::{parse_index, parse_pointer},
+};
+
+/// What [`Value::prune`] removes.
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
+pub struct PruneOptions {
+    /// Object members whose value is `null`. Array elements keep their positions, so `null`
+    /// elements are kept.
+    pub nulls: bool,
+    /// Empty objects and arrays, as members or elements, including those left empty by
+    /// pruning.
+    pub empty: bool,
+}
+
+/// A conversion between key naming conventions for [`Value::rename_keys`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Case {
+    /// `user_id` to `userId`.
+    SnakeToCamel,
+    /// `userId` to `user_id`, and `HTTPServer` to `http_server`.
+    CamelToSnake,
+    /// `user_id` to `user-id`.
+    SnakeToKebab,
+    /// `user-id` to `user_id`.
+    KebabToSnake,
+}
+
+impl Case {
+    /// Converts one key. Leading underscores, as in `_id`, are kept.
+    pub fn convert(self, key: &str) -> String {
+        match self {
+            Case::SnakeToCamel => {
+                let words = key.trim_start_matches('_');
+                let mut camel = key[..key.len() - words.len()].to_string();
+                for (i, word) in words.split('_').enumerate() {
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) if i > 0 => {
+                            camel.extend(first.to_uppercase());
+                            camel.push_str(chars.as_str());
+                        }
+                        _ => camel.push_str(word),
+                    }
+                }
+                camel
+            }
+            Case::CamelToSnake => {
+                let chars = key.chars().collect::<Vec<char>>();
+                let mut snake = String::with_capacity(key.len() + 4);
+                for (i, &c) in chars.iter().enumerate() {
+                    let after_word = i > 0
+                        && (chars[i - 1].is_lowercase()
+                            || chars[i - 1].is_ascii_digit()
+                            || chars[i - 1].is_uppercase()
+                                && chars.get(i + 1).is_some_and(|c| c.is_lowercase()));
+                    if c.is_uppercase() && after_word {
+                        snake.push('_');
+                    }
+                    snake.extend(c.to_lowercase());
+                }
+                snake
+            }
+            Case::SnakeToKebab => key.replace('_', "-"),
+            Case::KebabToSnake => key.replace('-', "_"),
+        }
+    }
+}
+
+impl Value {
+    /// Renames the keys of every object, at every depth, from one naming convention to
+    /// another.
+    pub fn rename_keys(&mut self, case: Case) {
+        self.rename_keys_with(|key| case.convert(key));
+    }
+
+    /// Renames the keys of every object, at every depth, to what `rename` returns for them.
+    /// If two keys of an object get the same name, the member that came later replaces the
+    /// earlier one but takes its position.
+    pub fn rename_keys_with(&mut self, mut rename: impl FnMut(&str) -> String) {
+        rename_keys(self, &mut rename);
+    }
+
+    /// A copy holding only the values at `pointers`, such as `/user/name`, and the objects
+    /// and arrays that lead to them, where a `*` token matches every member or element.
+    /// Arrays keep only the elements that are selected, in order. Objects and arrays in which
+    /// nothing is selected are left out, and pointers that are not valid select nothing.
+    pub fn project(&self, pointers: &[&str]) -> Value {
+        let paths = pointers
+            .iter()
+            .filter_map(|pointer| parse_pointer(pointer))
+            .collect::<Vec<Vec<String>>>();
+        let paths = paths.iter().map(Vec::as_slice).collect::<Vec<&[String]>>();
+        project(self, &paths).unwrap_or_else(|| match self {
+            Value::Object(_) => Value::Object(Map::new()),
+            Value::Array(_) => Value::Array(Vec::<Value>::new()),
+            _ => Value::Null,
+        })
+    }
+
+    /// Removes nulls, empty containers, or both, as `options` says, at every depth. The value
+    /// itself is never removed, even if it ends up empty. Returns how many values were
+    /// removed.
+    pub fn prune(&mut self, options: PruneOptions) -> usize {
+        let mut removed = 0;
+        // Whether to remove `value`, a member if `member`, after pruning it.
+        let mut prune = |value: &mut Value, member: bool| {
+            removed += value.prune(options);
+            let remove = match value {
+                Value::Null => member && options.nulls,
+                Value::Array(elements) => options.empty && elements.is_empty(),
+                Value::Object(members) => options.empty && members.is_empty(),
+                _ => false,
+            };
+            removed += usize::from(remove);
+            !remove
+        };
+        match self {
+            Value::Object(members) => members.retain(|_, value| prune(value, true)),
+            Value::Array(elements) => elements.retain_mut(|element| prune(element, false)),
+            _ => {}
+        }
+        removed
+    }
+}
+
+/// The projection of `value` on `paths`, the reference tokens left of each pointer, or `None`
+/// if nothing in it is selected.
+fn project(value: &Value, paths: &[&[String]]) -> Option<Value> {
+    if paths.iter().any(|path| path.is_empty()) {
+        return Some(value.clone());
+    }
+    // The rest of the paths whose next token selects a child.
+    let rest = |selects: &dyn Fn(&str) -> bool| {
+        paths
+            .iter()
+            .filter(|path| path[0] == "*" || selects(&path[0]))
+            .map(|path| &path[1..])
+            .collect::<Vec<&[String]>>()
+    };
+    match value {
+        Value::Object(members) => {
+            let members = members
+                .iter()
+                .filter_map(|(key, value)| {
+                    let value = project(value, &rest(&|token| token == &**key))?;
+                    Some((key.clone(), value))
+                })
+                .collect::<Map>();
+            (!members.is_empty()).then_some(Value::Object(members))
+        }
+        Value::Array(elements) => {
+            let elements = elements
+                .iter()
+                .enumerate()
+                .filter_map(|(i, element)| {
+                    project(element, &rest(&|token| parse_index(token) == Some(i)))
+                })
+                .collect::<Vec<Value>>();
+            (!elements.is_empty()).then_some(Value::Array(elements))
+        }
+        _ => None,
+    }
+}
+
+fn rename_keys(value: &mut Value, rename: &mut dyn FnMut(&str) -> String) {
+    match value {
+        Value::Object(members) => {
+            *members = std::mem::take(members)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    rename_keys(&mut value, rename);
+                    (rename(&key), value)
+                })
+                .collect();
+        }
+        Value::Array(elements) => {
+            for element in elements {
+                rename_keys(element, rename);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn convert_cases() {
+        let cases = [
+            (Case::SnakeToCamel, "user_id", "userId"),
+            (Case::SnakeToCamel, "_private_key", "_privateKey"),
+            (Case::SnakeToCamel, "é_été", "éÉté"),
+            (Case::SnakeToCamel, "id", "id"),
+            (Case::CamelToSnake, "userId", "user_id"),
+            (Case::CamelToSnake, "HTTPServer", "http_server"),
+            (Case::CamelToSnake, "getV2Url", "get_v2_url"),
+            (Case::CamelToSnake, "UserID", "user_id"),
+            (Case::SnakeToKebab, "a_b_c", "a-b-c"),
+            (Case::KebabToSnake, "a-b-c", "a_b_c"),
+        ];
+        for (case, key, expected) in cases {
+            assert_eq!(expected, case.convert(key), "{case:?} {key}");
+        }
+    }
+
+    #[test]
+    fn rename_keys_recursively() {
+        let mut value =
+            Parser::parse(r#"{"user_id": 1, "tags": [{"tag_name": "a_b"}], "aB": 2, "a_b": 3}"#)
+                .unwrap();
+        value.rename_keys(Case::SnakeToCamel);
+        assert_eq!(
+            Parser::parse(r#"{"userId":1,"tags":[{"tagName":"a_b"}],"aB":3}"#).unwrap(),
+            value
+        );
+
+        value.rename_keys_with(|key| key.to_uppercase());
+        assert_eq!(
+            Parser::parse(r#"{"USERID":1,"TAGS":[{"TAGNAME":"a_b"}],"AB":3}"#).unwrap(),
+            value
+        );
+    }
+
+    #[test]
+    fn project_pointers() {
+        let value = Parser::parse(
+            r#"{
+                "id": 7,
+                "user": {"name": "a", "email": "a@example.com", "address": {"city": "x"}},
+                "tags": [{"name": "t", "secret": 1}, {"secret": 2}, {"name": "u"}],
+                "a/b": [1, 2, 3]
+            }"#,
+        )
+        .unwrap();
+        let projected = |pointers: &[&str]| value.project(pointers);
+
+        assert_eq!(
+            Parser::parse(r#"{"id":7,"user":{"name":"a"},"tags":[{"name":"t"},{"name":"u"}]}"#)
+                .unwrap(),
+            projected(&["/id", "/user/name", "/tags/*/name"])
+        );
+        assert_eq!(
+            Parser::parse(r#"{"user":{"address":{"city":"x"}},"a/b":[2]}"#).unwrap(),
+            projected(&["/user/address", "/user/address/city", "/a~1b/1", "/missing"])
+        );
+        assert_eq!(
+            Parser::parse(r#"{"user":{"name":"a","email":"a@example.com"}}"#).unwrap(),
+            projected(&["/user/*/0", "/user/name", "/user/email"])
+        );
+        assert_eq!(value, projected(&[""]));
+        assert_eq!(Parser::parse("{}").unwrap(), projected(&["/nope", "id"]));
+        assert_eq!(Value::Null, Value::Number(1.0).project(&["/a"]));
+    }
+
+    #[test]
+    fn prune_nulls_and_empty_containers() {
+        let json = r#"{"a": null, "b": {"c": null}, "d": [null, [], {}], "e": "", "f": [[{}]]}"#;
+        let pruned = |nulls, empty| {
+            let mut value = Parser::parse(json).unwrap();
+            let removed = value.prune(PruneOptions { nulls, empty });
+            (value.to_string(), removed)
+        };
+
+        assert_eq!((json.replace(' ', ""), 0), pruned(false, false));
+        assert_eq!(
+            (
+                r#"{"b":{},"d":[null,[],{}],"e":"","f":[[{}]]}"#.to_string(),
+                2
+            ),
+            pruned(true, false)
+        );
+        assert_eq!(
+            (
+                r#"{"a":null,"b":{"c":null},"d":[null],"e":""}"#.to_string(),
+                5
+            ),
+            pruned(false, true)
+        );
+        assert_eq!(
+            (r#"{"d":[null],"e":""}"#.to_string(), 8),
+            pruned(true, true)
+        );
+
+        let mut empty = Parser::parse(r#"{"a": {}}"#).unwrap();
+        assert_eq!(
+            1,
+            empty.prune(PruneOptions {
+                nulls: true,
+                empty: true
+            })
+        );
+        assert_eq!(Parser::parse("{}").unwrap(), empty);
+    }
+}
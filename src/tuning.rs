@@ -0,0 +1,108 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use crate::parsing::{escape_key, Value};
+
+/// Container sizes seen in earlier documents, by where the containers were, for parsing more
+/// documents of the same shape with [`crate::parsing::Parser::parse_with_profile`]. Places
+/// are JSON pointers with every array index written as `*`, such as `/statuses/*/entities`.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ParseProfile {
+    /// How many containers at each place had each number of elements or members.
+    histograms: HashMap<String, BTreeMap<usize, usize>>,
+}
+
+impl ParseProfile {
+    pub fn new() -> Self {
+        ParseProfile::default()
+    }
+
+    /// Adds the size of every container in `value`.
+    pub fn record(&mut self, value: &Value) {
+        record(value, &mut String::new(), &mut self.histograms);
+    }
+
+    /// A size that nine in ten of the containers recorded at `place` fit in, or 0 if there
+    /// were none.
+    pub fn capacity(&self, place: &str) -> usize {
+        let Some(histogram) = self.histograms.get(place) else {
+            return 0;
+        };
+        let total = histogram.values().sum::<usize>();
+        let mut seen = 0;
+        for (&size, &count) in histogram {
+            seen += count;
+            if seen * 10 >= total * 9 {
+                return size;
+            }
+        }
+        0
+    }
+}
+
+fn record(
+    value: &Value,
+    place: &mut String,
+    histograms: &mut HashMap<String, BTreeMap<usize, usize>>,
+) {
+    let len = match value {
+        Value::Array(elements) => elements.len(),
+        Value::Object(members) => members.len(),
+        _ => return,
+    };
+    *histograms
+        .entry(place.clone())
+        .or_default()
+        .entry(len)
+        .or_default() += 1;
+
+    let end = place.len();
+    match value {
+        Value::Array(elements) => {
+            place.push_str("/*");
+            for element in elements {
+                record(element, place, histograms);
+            }
+        }
+        Value::Object(members) => {
+            for (key, member) in members {
+                write!(place, "/{}", escape_key(key)).unwrap();
+                record(member, place, histograms);
+                place.truncate(end);
+            }
+        }
+        _ => {}
+    }
+    place.truncate(end);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::{ParseOptions, Parser};
+
+    #[test]
+    fn hint_capacities_from_earlier_documents() {
+        let mut profile = ParseProfile::new();
+        for n in 1..=10 {
+            let tags = vec!["\"t\""; n].join(", ");
+            let json = format!(r#"{{"statuses": [{{"id": 1, "tags": [{tags}]}}]}}"#);
+            profile.record(&Parser::parse(&json).unwrap());
+        }
+        assert_eq!(9, profile.capacity("/statuses/*/tags"));
+        assert_eq!(2, profile.capacity("/statuses/*"));
+        assert_eq!(1, profile.capacity(""));
+        assert_eq!(0, profile.capacity("/missing"));
+
+        let json = r#"{"statuses": [{"id": 2, "tags": ["a", "b"]}, {"tags": []}]}"#;
+        let (value, _) =
+            Parser::parse_with_profile(json, ParseOptions::default(), &profile).unwrap();
+        assert_eq!(Parser::parse(json).unwrap(), value);
+        let Some(Value::Array(tags)) = value.pointer("/statuses/0/tags") else {
+            panic!("expected tags");
+        };
+        assert_eq!(9, tags.capacity());
+    }
+}
@@ -0,0 +1,297 @@
+use std::{
+    fmt,
+    fmt::{Display, Write},
+};
+
+use indexmap::IndexMap;
+
+use crate::{
+    parsing::{escape_key, Float, Value},
+    schema::is_valid_format,
+};
+
+/// An RFC 3339 `date-time` such as `1985-04-12T23:20:50.52Z`, broken into its fields. The
+/// text it was read from is kept, so it is written back exactly as it was.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DateTime {
+    text: String,
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+    nanosecond: u32,
+    offset_minutes: i16,
+}
+
+impl DateTime {
+    /// Reads `s` if it is a valid RFC 3339 `date-time`, as the `date-time` format of a JSON
+    /// Schema requires.
+    pub fn parse(s: &str) -> Option<DateTime> {
+        if is_valid_format("date-time", s) != Some(true) {
+            return None;
+        }
+        // The format has been checked, so every field is where it should be.
+        let field = |range: std::ops::Range<usize>| s[range].parse::<u16>().unwrap();
+        let offset_at = s[19..].find(['Z', 'z', '+', '-']).unwrap() + 19;
+        let fraction = s[19..offset_at].trim_start_matches('.');
+        let nanosecond = fraction
+            .bytes()
+            .chain(std::iter::repeat(b'0'))
+            .take(9)
+            .fold(0, |n, digit| n * 10 + u32::from(digit - b'0'));
+        let offset_minutes = match &s[offset_at..offset_at + 1] {
+            "Z" | "z" => 0,
+            sign => {
+                let minutes = (field(offset_at + 1..offset_at + 3) * 60
+                    + field(offset_at + 4..offset_at + 6)) as i16;
+                if sign == "-" {
+                    -minutes
+                } else {
+                    minutes
+                }
+            }
+        };
+
+        Some(DateTime {
+            text: s.to_string(),
+            year: field(0..4),
+            month: field(5..7) as u8,
+            day: field(8..10) as u8,
+            hour: field(11..13) as u8,
+            minute: field(14..16) as u8,
+            second: field(17..19) as u8,
+            nanosecond,
+            offset_minutes,
+        })
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    pub fn year(&self) -> u16 {
+        self.year
+    }
+
+    pub fn month(&self) -> u8 {
+        self.month
+    }
+
+    pub fn day(&self) -> u8 {
+        self.day
+    }
+
+    pub fn hour(&self) -> u8 {
+        self.hour
+    }
+
+    pub fn minute(&self) -> u8 {
+        self.minute
+    }
+
+    /// 60 for a leap second.
+    pub fn second(&self) -> u8 {
+        self.second
+    }
+
+    pub fn nanosecond(&self) -> u32 {
+        self.nanosecond
+    }
+
+    /// The offset from UTC, where `Z` is 0.
+    pub fn offset_minutes(&self) -> i16 {
+        self.offset_minutes
+    }
+
+    /// Seconds since 1970-01-01T00:00:00Z, ignoring the fraction. A leap second counts as
+    /// the first second of the next minute.
+    pub fn unix_timestamp(&self) -> i64 {
+        // Days from the civil date, after Howard Hinnant's `days_from_civil`.
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+
+        let seconds =
+            i64::from(self.hour) * 3600 + i64::from(self.minute) * 60 + i64::from(self.second);
+        days * 86_400 + seconds - i64::from(self.offset_minutes) * 60
+    }
+}
+
+impl Display for DateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+/// Which strings [`Value::to_typed`] reads as [`TypedValue::DateTime`].
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DateTimes<'a> {
+    /// Only the strings at these JSON pointers, when they are valid timestamps.
+    At(&'a [&'a str]),
+    /// Every string that is a valid timestamp.
+    Any,
+}
+
+/// A document like [`Value`] in which some strings are recognized as timestamps. Converting
+/// back with [`TypedValue::to_value`] gives the original document.
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypedValue {
+    Null,
+    Bool(bool),
+    Number(Float),
+    String(String),
+    DateTime(DateTime),
+    #[cfg(feature = "bytes")]
+    Bytes(Vec<u8>),
+    Array(Vec<TypedValue>),
+    Object(IndexMap<String, TypedValue>),
+}
+
+impl TypedValue {
+    pub fn to_value(&self) -> Value {
+        match self {
+            TypedValue::Null => Value::Null,
+            TypedValue::Bool(b) => Value::Bool(*b),
+            TypedValue::Number(n) => Value::Number(*n),
+            TypedValue::String(s) => Value::String(s.clone()),
+            TypedValue::DateTime(date_time) => Value::String(date_time.text.clone()),
+            #[cfg(feature = "bytes")]
+            TypedValue::Bytes(bytes) => Value::Bytes(bytes.clone()),
+            TypedValue::Array(elements) => {
+                Value::Array(elements.iter().map(TypedValue::to_value).collect())
+            }
+            TypedValue::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, member)| (key.as_str(), member.to_value()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Display for TypedValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_value())
+    }
+}
+
+impl Value {
+    /// Converts to a [`TypedValue`], reading the strings `date_times` picks out as timestamps.
+    pub fn to_typed(&self, date_times: DateTimes) -> TypedValue {
+        to_typed(self, date_times, &mut String::new())
+    }
+}
+
+fn to_typed(value: &Value, date_times: DateTimes, path: &mut String) -> TypedValue {
+    let end = path.len();
+    match value {
+        Value::Null => TypedValue::Null,
+        Value::Bool(b) => TypedValue::Bool(*b),
+        Value::Number(n) => TypedValue::Number(*n),
+        Value::String(s) => {
+            let wanted = match date_times {
+                DateTimes::At(pointers) => pointers.contains(&path.as_str()),
+                DateTimes::Any => true,
+            };
+            match wanted.then(|| DateTime::parse(s)).flatten() {
+                Some(date_time) => TypedValue::DateTime(date_time),
+                None => TypedValue::String(s.clone()),
+            }
+        }
+        #[cfg(feature = "bytes")]
+        Value::Bytes(bytes) => TypedValue::Bytes(bytes.clone()),
+        Value::Array(elements) => TypedValue::Array(
+            elements
+                .iter()
+                .enumerate()
+                .map(|(i, element)| {
+                    write!(path, "/{i}").unwrap();
+                    let typed = to_typed(element, date_times, path);
+                    path.truncate(end);
+                    typed
+                })
+                .collect(),
+        ),
+        Value::Object(members) => TypedValue::Object(
+            members
+                .iter()
+                .map(|(key, member)| {
+                    write!(path, "/{}", escape_key(key)).unwrap();
+                    let typed = to_typed(member, date_times, path);
+                    path.truncate(end);
+                    (key.to_string(), typed)
+                })
+                .collect(),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn read_date_times() {
+        let date_time = DateTime::parse("1990-12-31t15:59:60.123-08:00").unwrap();
+        assert_eq!(
+            (1990, 12, 31, 15, 59, 60, 123_000_000, -480),
+            (
+                date_time.year(),
+                date_time.month(),
+                date_time.day(),
+                date_time.hour(),
+                date_time.minute(),
+                date_time.second(),
+                date_time.nanosecond(),
+                date_time.offset_minutes()
+            )
+        );
+        assert_eq!(662_688_000, date_time.unix_timestamp());
+        assert_eq!(
+            0,
+            DateTime::parse("1970-01-01T00:00:00Z")
+                .unwrap()
+                .unix_timestamp()
+        );
+        assert_eq!(None, DateTime::parse("1985-04-12"));
+    }
+
+    #[test]
+    fn recognize_date_times() {
+        let value = Parser::parse(
+            r#"{"at": "2024-02-29T12:00:00+01:00", "log": ["2024-03-01T00:00:00Z", "soon"]}"#,
+        )
+        .unwrap();
+
+        let typed = value.to_typed(DateTimes::Any);
+        let TypedValue::Object(members) = &typed else {
+            panic!("{typed:?}")
+        };
+        assert!(matches!(members["at"], TypedValue::DateTime(_)));
+        let TypedValue::Array(log) = &members["log"] else {
+            panic!("{typed:?}")
+        };
+        assert!(matches!(log[0], TypedValue::DateTime(_)));
+        assert_eq!(TypedValue::String("soon".to_string()), log[1]);
+        assert_eq!(value, typed.to_value());
+        assert_eq!(value.to_string(), typed.to_string());
+
+        let typed = value.to_typed(DateTimes::At(&["/log/0", "/log/1"]));
+        let TypedValue::Object(members) = &typed else {
+            panic!("{typed:?}")
+        };
+        assert!(matches!(members["at"], TypedValue::String(_)));
+        assert!(matches!(
+            members["log"],
+            TypedValue::Array(ref log) if matches!(log[0], TypedValue::DateTime(_))
+        ));
+    }
+}
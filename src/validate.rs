@@ -0,0 +1,232 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+};
+
+use crate::{
+    errors::{Error, Severity},
+    parsing::{DangerousKeys, ParseOptions, Parser, Value},
+};
+
+/// The diagnostics for one file, or why it could not be read.
+#[derive(Debug)]
+pub struct FileReport {
+    path: PathBuf,
+    diagnostics: Result<Vec<Error>, io::Error>,
+}
+
+impl FileReport {
+    fn new(path: &Path, options: ParseOptions) -> Self {
+        let diagnostics =
+            fs::read_to_string(path).map(|json| match Parser::parse_with_options(&json, options) {
+                Ok((_, warnings)) => warnings,
+                Err(errors) => errors,
+            });
+        FileReport {
+            path: path.to_path_buf(),
+            diagnostics,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn diagnostics(&self) -> Result<&[Error], &io::Error> {
+        self.diagnostics.as_deref()
+    }
+
+    pub fn into_diagnostics(self) -> Result<Vec<Error>, io::Error> {
+        self.diagnostics
+    }
+
+    /// Whether the file was read and has no diagnostics with error severity.
+    pub fn is_valid(&self) -> bool {
+        self.diagnostics()
+            .is_ok_and(|errors| errors.iter().all(|e| e.severity() != Severity::Error))
+    }
+}
+
+/// Parses every file on `threads` threads, or one per CPU if `threads` is 0, and returns
+/// their reports in the order of `paths`.
+pub fn validate_files<P: AsRef<Path> + Sync>(paths: &[P], threads: usize) -> Vec<FileReport> {
+    validate_files_with_progress(paths, threads, |_, _| {})
+}
+
+/// Like [`validate_files`], but parses with `options` rather than as strict JSON with a
+/// warning for each dangerous key.
+pub fn validate_files_with_options<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    threads: usize,
+    options: ParseOptions,
+) -> Vec<FileReport> {
+    run_in_pool(
+        paths,
+        threads,
+        |path| FileReport::new(path.as_ref(), options),
+        |_, _| {},
+    )
+}
+
+/// Like [`validate_files`], but calls `progress` with each report as soon as it is ready,
+/// along with how many files are done so far. Reports arrive in the order they finish.
+pub fn validate_files_with_progress<P: AsRef<Path> + Sync>(
+    paths: &[P],
+    threads: usize,
+    progress: impl FnMut(&FileReport, usize),
+) -> Vec<FileReport> {
+    let options = ParseOptions {
+        dangerous_keys: DangerousKeys::Warn,
+        ..ParseOptions::default()
+    };
+    run_in_pool(
+        paths,
+        threads,
+        |path| FileReport::new(path.as_ref(), options),
+        progress,
+    )
+}
+
+/// Parses every document on `threads` threads, or one per CPU if `threads` is 0, and returns
+/// the results in the order of `docs`. Each thread takes the next document as it finishes
+/// one, so a few large documents do not hold up the rest.
+pub fn parse_batch(docs: &[&str], threads: usize) -> Vec<Result<Value, Vec<Error>>> {
+    run_in_pool(docs, threads, |doc| Parser::parse(doc), |_, _| {})
+}
+
+/// Calls `work` on every item on up to `threads` threads, calling `progress` with each result
+/// as it is ready, and returns the results in the order of `items`.
+fn run_in_pool<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    work: impl Fn(&T) -> R + Sync,
+    mut progress: impl FnMut(&R, usize),
+) -> Vec<R> {
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let next = AtomicUsize::new(0);
+    let mut results = Vec::<Option<R>>::new();
+    results.resize_with(items.len(), || None);
+
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel::<(usize, R)>();
+        for _ in 0..threads.min(items.len()) {
+            let sender = sender.clone();
+            let (next, work) = (&next, &work);
+            scope.spawn(move || loop {
+                let i = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(i) else {
+                    break;
+                };
+                if sender.send((i, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        for (done, (i, result)) in receiver.iter().enumerate() {
+            progress(&result, done + 1);
+            results[i] = Some(result);
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| result.expect("every item to be done"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ErrorCode;
+
+    #[test]
+    fn validate_in_order_with_progress() {
+        let invalid = std::env::temp_dir().join("json-parser-validate-invalid.json");
+        fs::write(&invalid, r#"{"a": 1, "a": [1 2]}"#).unwrap();
+        let paths = [
+            Path::new("tests/twitter.json"),
+            &invalid,
+            Path::new("tests/missing.json"),
+            Path::new("tests/canada.json"),
+        ];
+
+        let mut done = Vec::<usize>::new();
+        let reports = validate_files_with_progress(&paths, 2, |_, count| done.push(count));
+        assert_eq!(vec![1, 2, 3, 4], done);
+        assert_eq!(
+            paths.to_vec(),
+            reports.iter().map(FileReport::path).collect::<Vec<_>>()
+        );
+
+        assert!(reports[0].is_valid());
+        assert!(reports[3].is_valid());
+        assert_eq!(
+            vec![ErrorCode::ExpectedCommaOrEndWhileParsing(']')],
+            reports[1]
+                .diagnostics()
+                .unwrap()
+                .iter()
+                .map(Error::code)
+                .collect::<Vec<_>>()
+        );
+        assert!(!reports[1].is_valid());
+        assert!(reports[2].diagnostics().is_err());
+        assert!(!reports[2].is_valid());
+
+        let reports = validate_files(&paths[..1], 0);
+        assert!(reports[0].is_valid());
+        assert!(validate_files::<&str>(&[], 4).is_empty());
+    }
+
+    #[test]
+    fn validate_with_options() {
+        let lenient = std::env::temp_dir().join("json-parser-validate-lenient.json");
+        fs::write(&lenient, "[1, 2,]").unwrap();
+        let paths = [&lenient];
+        assert!(!validate_files(&paths, 1)[0].is_valid());
+
+        let options = ParseOptions {
+            allow_trailing_commas: true,
+            ..ParseOptions::default()
+        };
+        let reports = validate_files_with_options(&paths, 1, options);
+        assert!(reports[0].is_valid());
+        assert_eq!(
+            vec![ErrorCode::TrailingComma],
+            reports[0]
+                .diagnostics()
+                .unwrap()
+                .iter()
+                .map(Error::code)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn parse_batch_in_order() {
+        let docs = (0..50)
+            .map(|i| match i % 7 {
+                0 => format!("[{i},"),
+                _ => format!(r#"{{"n": {i}}}"#),
+            })
+            .collect::<Vec<String>>();
+        let docs = docs.iter().map(String::as_str).collect::<Vec<&str>>();
+        let results = parse_batch(&docs, 3);
+        assert_eq!(50, results.len());
+        for (doc, result) in docs.iter().zip(&results) {
+            assert_eq!(&Parser::parse(doc), result);
+        }
+        assert!(results[7].is_err());
+        assert!(parse_batch(&[], 0).is_empty());
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use crate::parsing::{escape_key, Value};
+
+enum Edit {
+    Insert(String, Value),
+    Remove(String),
+}
+
+/// Changes to the members of the object holding the value being visited. They are made in
+/// order once every member of that object has been visited, so the traversal sees the
+/// members as they were and inserted members are not visited.
+#[derive(Default)]
+pub struct Siblings {
+    edits: Vec<Edit>,
+}
+
+impl Siblings {
+    /// Inserts or replaces the member `key`, keeping the position of a replaced member.
+    pub fn insert(&mut self, key: &str, value: Value) {
+        self.edits.push(Edit::Insert(key.to_string(), value));
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.edits.push(Edit::Remove(key.to_string()));
+    }
+}
+
+/// Visits every value of a document, parents before children, with [`Value::walk_mut`].
+pub trait VisitorMut {
+    /// Called with the JSON pointer to `value`, and with the object's pending edits when
+    /// `value` is a member of one. Changes to `value` itself are made at once, and its
+    /// children are visited as they are after the call.
+    fn visit(&mut self, pointer: &str, value: &mut Value, siblings: Option<&mut Siblings>);
+}
+
+impl<F: FnMut(&str, &mut Value, Option<&mut Siblings>)> VisitorMut for F {
+    fn visit(&mut self, pointer: &str, value: &mut Value, siblings: Option<&mut Siblings>) {
+        self(pointer, value, siblings)
+    }
+}
+
+impl Value {
+    pub fn walk_mut(&mut self, visitor: &mut impl VisitorMut) {
+        visitor.visit("", self, None);
+        walk_children(self, visitor, &mut String::new());
+    }
+}
+
+fn walk_children(value: &mut Value, visitor: &mut impl VisitorMut, path: &mut String) {
+    let end = path.len();
+    match value {
+        Value::Array(elements) => {
+            for (i, element) in elements.iter_mut().enumerate() {
+                write!(path, "/{i}").unwrap();
+                visitor.visit(path, element, None);
+                walk_children(element, visitor, path);
+                path.truncate(end);
+            }
+        }
+        Value::Object(members) => {
+            let mut siblings = Siblings::default();
+            for (key, member) in members.iter_mut() {
+                write!(path, "/{}", escape_key(key)).unwrap();
+                visitor.visit(path, member, Some(&mut siblings));
+                walk_children(member, visitor, path);
+                path.truncate(end);
+            }
+            for edit in siblings.edits {
+                match edit {
+                    Edit::Insert(key, value) => {
+                        members.insert(key, value);
+                    }
+                    Edit::Remove(key) => {
+                        members.remove(&key);
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::Parser;
+
+    #[test]
+    fn edit_siblings_while_walking() {
+        let mut value =
+            Parser::parse(r#"{"password": "x", "user": {"name": "a", "tmp": 1}, "n": [1, 2]}"#)
+                .unwrap();
+        let mut visited = Vec::new();
+        value.walk_mut(
+            &mut |pointer: &str, value: &mut Value, siblings: Option<&mut Siblings>| {
+                visited.push(pointer.to_string());
+                if let (Some(siblings), Value::String(s)) = (siblings, &*value) {
+                    if pointer == "/password" {
+                        siblings.remove("password");
+                        siblings.insert("password_set", Value::Bool(true));
+                    } else {
+                        siblings.insert("name_len", Value::Number(s.len() as _));
+                        siblings.remove("tmp");
+                    }
+                }
+                if let Value::Number(n) = value {
+                    *n *= 10.0;
+                }
+            },
+        );
+        // Members are visited in the map's order, but always after their parent.
+        for (i, pointer) in visited.iter().enumerate().skip(1) {
+            let parent = &pointer[..pointer.rfind('/').unwrap()];
+            assert!(visited[..i].iter().any(|p| p == parent), "{pointer}");
+        }
+        visited.sort();
+        assert_eq!(
+            vec![
+                "",
+                "/n",
+                "/n/0",
+                "/n/1",
+                "/password",
+                "/user",
+                "/user/name",
+                "/user/tmp"
+            ],
+            visited
+        );
+        assert_eq!(
+            Parser::parse(r#"{"user":{"name":"a","name_len":1},"n":[10,20],"password_set":true}"#)
+                .unwrap(),
+            value
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use std::{
+    fmt,
+    fmt::{Display, Write},
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    errors::Error,
+    parsing::{escape_key, Parser, Value},
+};
+
+#[derive(Debug)]
+pub enum WatchError {
+    Io(io::Error),
+    Parse(Vec<Error>),
+}
+
+impl Display for WatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WatchError::Io(error) => write!(f, "{error}"),
+            WatchError::Parse(errors) => match errors.first() {
+                Some(error) => write!(f, "{error}"),
+                None => f.write_str("Invalid JSON"),
+            },
+        }
+    }
+}
+
+impl std::error::Error for WatchError {}
+
+impl From<io::Error> for WatchError {
+    fn from(error: io::Error) -> Self {
+        WatchError::Io(error)
+    }
+}
+
+type Callback = Box<dyn FnMut(&str, Option<&Value>)>;
+
+/// Keeps the parsed contents of a config file and, when the file changes, tells callbacks
+/// which values changed.
+///
+/// Nothing is watched in the background: call [`ConfigWatcher::poll`] from a timer or event
+/// loop. A file that fails to parse leaves the previous contents in place.
+pub struct ConfigWatcher {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    value: Value,
+    callbacks: Vec<(String, Callback)>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, WatchError> {
+        let mut watcher = ConfigWatcher {
+            path: path.as_ref().to_path_buf(),
+            modified: None,
+            value: Value::Null,
+            callbacks: Vec::new(),
+        };
+        (watcher.modified, watcher.value) = watcher.read()?;
+        Ok(watcher)
+    }
+
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Calls `callback` with the pointer and new value, or `None` if it was removed, of each
+    /// change at or below `pointer`. A change above `pointer`, such as replacing the whole
+    /// object it is in, is reported once at `pointer` itself.
+    pub fn on_change(
+        &mut self,
+        pointer: &str,
+        callback: impl FnMut(&str, Option<&Value>) + 'static,
+    ) {
+        self.callbacks
+            .push((pointer.to_string(), Box::new(callback)));
+    }
+
+    /// Reloads the file if its modification time changed. Returns whether it was reloaded.
+    pub fn poll(&mut self) -> Result<bool, WatchError> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        if modified.is_some() && modified == self.modified {
+            return Ok(false);
+        }
+        self.reload()?;
+        Ok(true)
+    }
+
+    /// Reloads the file and calls the callbacks for whatever changed.
+    pub fn reload(&mut self) -> Result<(), WatchError> {
+        let (modified, value) = self.read()?;
+        self.modified = modified;
+        let old = std::mem::replace(&mut self.value, value);
+
+        let changes = diff(&old, &self.value);
+        for (watched, callback) in &mut self.callbacks {
+            for change in &changes {
+                if is_within(change, watched) {
+                    callback(change, self.value.pointer(change));
+                } else if is_within(watched, change) {
+                    let value = self.value.pointer(watched);
+                    if old.pointer(watched) != value {
+                        callback(watched, value);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read(&self) -> Result<(Option<SystemTime>, Value), WatchError> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        let json = fs::read_to_string(&self.path)?;
+        let value = Parser::parse(&json).map_err(WatchError::Parse)?;
+        Ok((modified, value))
+    }
+}
+
+/// Whether `pointer` is `ancestor` or below it.
+fn is_within(pointer: &str, ancestor: &str) -> bool {
+    pointer
+        .strip_prefix(ancestor)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+/// The JSON pointers of the values that differ between `old` and `new`: members and elements
+/// that were added, removed, or changed, and any scalar or container whose type changed.
+/// Containers of the same type are compared member by member rather than reported whole.
+pub fn diff(old: &Value, new: &Value) -> Vec<String> {
+    let mut changes = Vec::new();
+    diff_at(old, new, &mut String::new(), &mut changes);
+    changes
+}
+
+fn diff_at(old: &Value, new: &Value, path: &mut String, changes: &mut Vec<String>) {
+    let end = path.len();
+    match (old, new) {
+        (Value::Object(old), Value::Object(new)) => {
+            for (key, old_member) in old {
+                write!(path, "/{}", escape_key(key)).unwrap();
+                match new.get(key) {
+                    Some(new_member) => diff_at(old_member, new_member, path, changes),
+                    None => changes.push(path.clone()),
+                }
+                path.truncate(end);
+            }
+            for key in new.keys().filter(|key| !old.contains_key(key)) {
+                changes.push(format!("{path}/{}", escape_key(key)));
+            }
+        }
+        (Value::Array(old), Value::Array(new)) => {
+            for i in 0..old.len().max(new.len()) {
+                write!(path, "/{i}").unwrap();
+                match (old.get(i), new.get(i)) {
+                    (Some(old), Some(new)) => diff_at(old, new, path, changes),
+                    _ => changes.push(path.clone()),
+                }
+                path.truncate(end);
+            }
+        }
+        (old, new) if old != new => changes.push(path.clone()),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    #[test]
+    fn diff_documents() {
+        let parse = |json| Parser::parse(json).unwrap();
+        let old = parse(r#"{"a": 1, "b": {"c": [1, 2], "d": "x"}, "e/f": true}"#);
+        let new = parse(r#"{"a": 1, "b": {"c": [1, 3, 4], "d": 0}, "g": null}"#);
+        assert_eq!(
+            vec!["/b/c/1", "/b/c/2", "/b/d", "/e~1f", "/g"],
+            diff(&old, &new)
+        );
+        assert!(diff(&old, &old).is_empty());
+        assert_eq!(vec![""], diff(&old, &Value::Null));
+    }
+
+    #[test]
+    fn report_changes_on_reload() {
+        let path = std::env::temp_dir().join(format!("watch-{}.json", std::process::id()));
+        fs::write(&path, r#"{"db": {"host": "a", "port": 1}, "debug": false}"#).unwrap();
+
+        let mut watcher = ConfigWatcher::new(&path).unwrap();
+        let seen = Rc::new(RefCell::new(Vec::<(String, Option<Value>)>::new()));
+        let record = |seen: &Rc<RefCell<Vec<_>>>| {
+            let seen = Rc::clone(seen);
+            move |pointer: &str, value: Option<&Value>| {
+                seen.borrow_mut()
+                    .push((pointer.to_string(), value.cloned()))
+            }
+        };
+        watcher.on_change("/db", record(&seen));
+        watcher.on_change("/db/host", record(&seen));
+
+        fs::write(&path, r#"{"db": {"port": 2}, "debug": true}"#).unwrap();
+        watcher.reload().unwrap();
+        assert_eq!(
+            vec![
+                ("/db/host".to_string(), None),
+                ("/db/port".to_string(), Some(Value::Number(2.0))),
+                ("/db/host".to_string(), None),
+            ],
+            seen.take()
+        );
+
+        fs::write(&path, r#"{"db": "postgres://b"}"#).unwrap();
+        watcher.reload().unwrap();
+        let db = Value::String("postgres://b".to_string());
+        assert_eq!(vec![("/db".to_string(), Some(db.clone()))], seen.take());
+        assert!(!watcher.poll().unwrap());
+
+        fs::write(&path, "{").unwrap();
+        assert!(matches!(watcher.reload(), Err(WatchError::Parse(_))));
+        assert_eq!(Some(&db), watcher.value().pointer("/db"));
+        assert!(seen.borrow().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,306 @@
+use std::{io, io::Write as _};
+
+use crate::{
+    lexical::Span,
+    serialize::{self, FormatConfig},
+    stream::Event,
+};
+
+enum Frame {
+    Array { is_empty: bool },
+    Object { is_empty: bool, has_key: bool },
+}
+
+/// Writes JSON incrementally from the same events [`StreamParser`] produces, without building
+/// a [`Value`]. Indentation and number formatting follow the [`FormatConfig`]; `max_width` and
+/// `sort_keys` need the whole container and are ignored.
+///
+/// Calls that would produce invalid JSON, such as a value in an object without a key, fail
+/// with [`io::ErrorKind::InvalidInput`].
+///
+/// [`StreamParser`]: crate::stream::StreamParser
+/// [`Value`]: crate::parsing::Value
+pub struct JsonWriter<W: io::Write> {
+    out: Tracked<W>,
+    config: FormatConfig,
+    frames: Vec<Frame>,
+    has_root: bool,
+    scratch: String,
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    pub fn new(out: W, config: FormatConfig) -> Self {
+        JsonWriter {
+            out: Tracked {
+                inner: out,
+                position: Span::new(0, 0, 1, 1),
+                last_token: Span::new(0, 0, 1, 1),
+            },
+            config,
+            frames: Vec::<Frame>::new(),
+            has_root: false,
+            scratch: String::new(),
+        }
+    }
+
+    pub fn start_object(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.frames.push(Frame::Object {
+            is_empty: true,
+            has_key: false,
+        });
+        self.out.write_token(b"{")
+    }
+
+    pub fn end_object(&mut self) -> io::Result<()> {
+        match self.frames.pop() {
+            Some(Frame::Object {
+                is_empty,
+                has_key: false,
+            }) => self.end('}', is_empty),
+            _ => Err(invalid_input("end_object without a matching start_object")),
+        }
+    }
+
+    pub fn start_array(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.frames.push(Frame::Array { is_empty: true });
+        self.out.write_token(b"[")
+    }
+
+    pub fn end_array(&mut self) -> io::Result<()> {
+        match self.frames.pop() {
+            Some(Frame::Array { is_empty }) => self.end(']', is_empty),
+            _ => Err(invalid_input("end_array without a matching start_array")),
+        }
+    }
+
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        let depth = self.frames.len();
+        match self.frames.last_mut() {
+            Some(Frame::Object { is_empty, has_key }) if !*has_key => {
+                let was_empty = *is_empty;
+                *is_empty = false;
+                *has_key = true;
+                if !was_empty {
+                    self.out.write_all(b",")?;
+                }
+                self.write_newline(depth)?;
+                self.scratch.clear();
+                serialize::write_string(&mut self.scratch, key);
+                self.out.write_token(self.scratch.as_bytes())?;
+                self.out.write_all(match self.config.indent {
+                    Some(_) => b": ",
+                    None => b":",
+                })
+            }
+            _ => Err(invalid_input("key outside of an object")),
+        }
+    }
+
+    pub fn null(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.out.write_token(b"null")
+    }
+
+    pub fn bool(&mut self, b: bool) -> io::Result<()> {
+        self.before_value()?;
+        self.out.write_token(if b { b"true" } else { b"false" })
+    }
+
+    pub fn number(&mut self, n: f64) -> io::Result<()> {
+        self.before_value()?;
+        self.scratch.clear();
+        serialize::write_number(&mut self.scratch, n, &self.config.numbers);
+        self.out.write_token(self.scratch.as_bytes())
+    }
+
+    pub fn string(&mut self, s: &str) -> io::Result<()> {
+        self.before_value()?;
+        self.scratch.clear();
+        serialize::write_string(&mut self.scratch, s);
+        self.out.write_token(self.scratch.as_bytes())
+    }
+
+    pub fn write_event(&mut self, event: &Event) -> io::Result<()> {
+        match event {
+            Event::StartObject => self.start_object(),
+            Event::EndObject => self.end_object(),
+            Event::StartArray => self.start_array(),
+            Event::EndArray => self.end_array(),
+            Event::Key(key) => self.key(key),
+            Event::Null => self.null(),
+            Event::Bool(b) => self.bool(*b),
+            Event::Number(n) => self.number(*n),
+            Event::String(s) => self.string(s),
+        }
+    }
+
+    /// Where in the output the last key, scalar, or bracket was written, like
+    /// [`Reader::last_span`] for the input. The span covers a key's quotes but not its colon.
+    ///
+    /// [`Reader::last_span`]: crate::lexical::Reader::last_span
+    pub fn last_span(&self) -> Span {
+        self.out.last_token
+    }
+
+    /// Returns the underlying writer once a complete document has been written.
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.frames.is_empty() || !self.has_root {
+            return Err(invalid_input("incomplete document"));
+        }
+        self.out.flush()?;
+        Ok(self.out.inner)
+    }
+
+    fn before_value(&mut self) -> io::Result<()> {
+        let depth = self.frames.len();
+        match self.frames.last_mut() {
+            None if self.has_root => Err(invalid_input("more than one root value")),
+            None => {
+                self.has_root = true;
+                Ok(())
+            }
+            Some(Frame::Array { is_empty }) => {
+                let was_empty = *is_empty;
+                *is_empty = false;
+                if !was_empty {
+                    self.out.write_all(b",")?;
+                }
+                self.write_newline(depth)
+            }
+            Some(Frame::Object { has_key, .. }) if *has_key => {
+                *has_key = false;
+                Ok(())
+            }
+            Some(Frame::Object { .. }) => Err(invalid_input("object member without a key")),
+        }
+    }
+
+    fn end(&mut self, close: char, is_empty: bool) -> io::Result<()> {
+        if !is_empty {
+            self.write_newline(self.frames.len())?;
+        }
+        self.out.write_token(&[close as u8])
+    }
+
+    fn write_newline(&mut self, depth: usize) -> io::Result<()> {
+        if let Some(indent) = self.config.indent {
+            write!(self.out, "\n{:1$}", "", indent * depth)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts the lines and columns written, the same way [`Reader`] does for its input.
+///
+/// [`Reader`]: crate::lexical::Reader
+struct Tracked<W: io::Write> {
+    inner: W,
+    /// The offset, line, and column of the next byte.
+    position: Span,
+    last_token: Span,
+}
+
+impl<W: io::Write> Tracked<W> {
+    fn write_token(&mut self, token: &[u8]) -> io::Result<()> {
+        let start = self.position;
+        self.write_all(token)?;
+        self.last_token = Span::new(start.end, self.position.end, start.line, start.col);
+        Ok(())
+    }
+}
+
+impl<W: io::Write> io::Write for Tracked<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        for &b in &buf[..n] {
+            match b {
+                b'\n' => {
+                    self.position.line += 1;
+                    self.position.col = 1;
+                }
+                // Continuation bytes of a multi-byte character.
+                0x80..=0xbf => {}
+                _ => self.position.col += 1,
+            }
+        }
+        self.position.end += n;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn invalid_input(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parsing::Parser, stream::StreamParser};
+
+    const DOCUMENT: &str = r#"{"a": [1, "x", {}, [], {"b": null}], "c": false}"#;
+
+    fn rewrite(json: &str, config: FormatConfig) -> String {
+        let mut writer = JsonWriter::new(Vec::new(), config);
+        for event in StreamParser::new(json) {
+            writer.write_event(&event.unwrap().0).unwrap();
+        }
+        String::from_utf8(writer.finish().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn match_dom_serializer() {
+        let value = Parser::parse(DOCUMENT).unwrap();
+        for config in [FormatConfig::compact(), FormatConfig::pretty()] {
+            assert_eq!(
+                serialize::to_string(&value, &config),
+                rewrite(DOCUMENT, config)
+            );
+        }
+        assert_eq!("2.5", rewrite(" 2.5 ", FormatConfig::pretty()));
+    }
+
+    #[test]
+    fn track_output_spans() {
+        let mut writer = JsonWriter::new(Vec::new(), FormatConfig::pretty());
+        writer.start_object().unwrap();
+        assert_eq!(Span::new(0, 1, 1, 1), writer.last_span());
+        writer.key("é").unwrap();
+        assert_eq!(Span::new(4, 8, 2, 3), writer.last_span());
+        writer.string("x").unwrap();
+        assert_eq!(Span::new(10, 13, 2, 8), writer.last_span());
+        writer.end_object().unwrap();
+        assert_eq!(Span::new(14, 15, 3, 1), writer.last_span());
+        assert_eq!(
+            "{\n  \"é\": \"x\"\n}",
+            String::from_utf8(writer.finish().unwrap()).unwrap()
+        );
+    }
+
+    #[test]
+    fn fail_on_misuse() {
+        let mut writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        writer.start_object().unwrap();
+        assert!(writer.null().is_err());
+        assert!(writer.end_array().is_err());
+
+        let mut writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        writer.start_array().unwrap();
+        assert!(writer.key("a").is_err());
+        writer.end_array().unwrap();
+        assert!(writer.bool(true).is_err());
+
+        let mut writer = JsonWriter::new(Vec::new(), FormatConfig::compact());
+        writer.start_object().unwrap();
+        writer.key("a").unwrap();
+        assert!(writer.end_object().is_err());
+        assert!(JsonWriter::new(Vec::new(), FormatConfig::compact())
+            .finish()
+            .is_err());
+    }
+}
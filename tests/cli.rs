@@ -0,0 +1,105 @@
+use std::{
+    fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+/// A directory of its own for each test, holding `files`, so that a config file in one does
+/// not affect another.
+fn workspace(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("json-parser-cli-{}-{name}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (file, contents) in files {
+        fs::write(dir.join(file), contents).unwrap();
+    }
+    dir
+}
+
+fn run(dir: &PathBuf, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    fs::remove_dir_all(dir).unwrap();
+    output
+}
+
+const LENIENT: &str = r#"{"lenient": {"trailing-commas": true, "single-quotes": true}}"#;
+const SCHEMA: &str = r#"{"type": "object", "properties": {"a": {"type": "string"}}}"#;
+
+#[test]
+fn check_lenient_document_against_schema() {
+    for (name, data) in [("trailing", "{\"a\": 1,}"), ("quotes", "{'a': 1}")] {
+        let dir = workspace(
+            name,
+            &[
+                (".jsonparser.json", LENIENT),
+                ("schema.json", SCHEMA),
+                ("data.json", data),
+            ],
+        );
+        let output = run(&dir, &["check", "--schema", "schema.json", "data.json"]);
+
+        assert_eq!(Some(1), output.status.code(), "{name}");
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.starts_with("data.json:1:7: "), "{name}: {stdout}");
+    }
+}
+
+#[test]
+fn check_lenient_ndjson_records_against_schema() {
+    let dir = workspace(
+        "ndjson",
+        &[
+            (".jsonparser.json", LENIENT),
+            ("schema.json", SCHEMA),
+            ("data.ndjson", "{'a': 'x',}\n{'a': 1}\n"),
+        ],
+    );
+    let output = run(
+        &dir,
+        &[
+            "check",
+            "--schema",
+            "schema.json",
+            "--ndjson",
+            "data.ndjson",
+        ],
+    );
+
+    assert_eq!(Some(1), output.status.code());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("data.ndjson:2:7: "), "{stdout}");
+    assert!(
+        stdout.ends_with("2 records, 1 failed, 1 violations, 0 not valid JSON\n"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn check_standard_input_with_porcelain_output() {
+    let dir = workspace("stdin", &[("schema.json", SCHEMA)]);
+    let mut child = Command::new(env!("CARGO_BIN_EXE_json-parser"))
+        .args(["--porcelain", "check", "--schema", "schema.json", "-"])
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"{\n  \"a\": 1\n}")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    fs::remove_dir_all(dir).unwrap();
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!(
+        "-\t2\t8\ttype\tExpected string, found number\t/properties/a/type\n",
+        String::from_utf8(output.stdout).unwrap()
+    );
+}
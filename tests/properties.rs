@@ -0,0 +1,93 @@
+use json_parser::{
+    map::Map,
+    parsing::{Float, Parser, Value},
+    serialize::{self, FormatConfig},
+};
+use proptest::prelude::*;
+
+/// Any document, with finite numbers and strings that need escaping.
+fn value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::Bool),
+        any::<Float>()
+            .prop_filter("JSON numbers are finite", |n| n.is_finite())
+            .prop_map(Value::Number),
+        any::<i32>().prop_map(|n| Value::Number(n as Float)),
+        any::<String>().prop_map(Value::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::Array),
+            object(inner).prop_map(Value::Object),
+        ]
+    })
+}
+
+fn object(member: impl Strategy<Value = Value>) -> impl Strategy<Value = Map> {
+    prop::collection::vec((any::<String>(), member), 0..8)
+        .prop_map(|members| members.into_iter().collect())
+}
+
+fn parse(json: &str) -> Value {
+    Parser::parse(json).unwrap_or_else(|errors| panic!("{json}: {errors:?}"))
+}
+
+fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+proptest! {
+    #[test]
+    fn parse_what_is_written(value in value()) {
+        prop_assert_eq!(&value, &parse(&value.to_string()));
+        prop_assert_eq!(&value, &parse(&serialize::to_string(&value, &FormatConfig::pretty())));
+    }
+
+    #[test]
+    fn canonical_form_is_a_fixed_point(value in value()) {
+        let canonical = serialize::to_canonical_string(&value);
+        prop_assert_eq!(&canonical, &serialize::to_canonical_string(&parse(&canonical)));
+
+        let pretty = serialize::to_string(&value, &FormatConfig::pretty());
+        prop_assert_eq!(
+            &pretty,
+            &serialize::to_string(&parse(&pretty), &FormatConfig::pretty())
+        );
+    }
+
+    #[test]
+    fn undo_patches(
+        members in object(value()),
+        replace in any::<bool>(),
+        existing in any::<prop::sample::Index>(),
+        new_key in any::<String>(),
+        member in value(),
+    ) {
+        let key = match members.len() {
+            len if replace && len > 0 => {
+                members.keys().nth(existing.index(len)).unwrap().to_string()
+            }
+            _ => new_key,
+        };
+        let path = Value::String(format!("/{}", escape(&key)));
+        let operation = |op: &str, value: Option<&Value>| {
+            let mut operation = Map::new();
+            operation.insert("op", Value::String(op.to_string()));
+            operation.insert("path", path.clone());
+            if let Some(value) = value {
+                operation.insert("value", value.clone());
+            }
+            Value::Object(operation)
+        };
+        let patch = match members.get(&key) {
+            Some(old) => vec![operation("replace", Some(&member)), operation("replace", Some(old))],
+            None => vec![operation("add", Some(&member)), operation("remove", None)],
+        };
+
+        let value = Value::Object(members.clone());
+        let mut patched = value.clone();
+        patched.apply_patch(&Value::Array(patch)).unwrap();
+        prop_assert_eq!(&value, &patched);
+    }
+}